@@ -28,7 +28,7 @@ pub enum IdType {
 }
 
 // CRUD 操作枚举
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum CrudOperation {
     Create,
     Read,
@@ -44,6 +44,7 @@ pub struct CrudEntityConfig {
     pub permission_prefix: LitStr,
     pub id_type: Option<IdType>,
     pub operations: Option<Vec<CrudOperation>>,
+    pub public_operations: Option<Vec<CrudOperation>>, // 无需登录即可访问的操作
     pub create_request_type: Option<Ident>,
     pub custom_queries: Option<Vec<CustomQueryType>>, // 新增：自定义查询类型
     pub custom_list_fn: Option<Ident>,                // 新增：自定义列表查询函数名
@@ -64,6 +65,7 @@ impl Parse for CrudEntityConfig {
         let mut permission_prefix = None;
         let mut id_type = None;
         let mut operations = None;
+        let mut public_operations = None;
         let mut create_request_type = None;
         let mut custom_queries = None;
         let mut custom_list_fn = None;
@@ -98,27 +100,11 @@ impl Parse for CrudEntityConfig {
                 }
                 "operations" => {
                     let array: ExprArray = content.parse()?;
-                    let mut ops = Vec::new();
-
-                    for elem in array.elems {
-                        if let Expr::Lit(lit) = elem {
-                            if let syn::Lit::Str(lit_str) = lit.lit {
-                                match lit_str.value().as_str() {
-                                    "create" => ops.push(CrudOperation::Create),
-                                    "read" => ops.push(CrudOperation::Read),
-                                    "delete" => ops.push(CrudOperation::Delete),
-                                    "list" => ops.push(CrudOperation::List),
-                                    _ => {
-                                        return Err(syn::Error::new_spanned(
-                                            lit_str,
-                                            "Unknown operation",
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    operations = Some(ops);
+                    operations = Some(parse_operations(array)?);
+                }
+                "public_operations" => {
+                    let array: ExprArray = content.parse()?;
+                    public_operations = Some(parse_operations(array)?);
                 }
                 "create_request_type" => {
                     let value: Ident = content.parse()?;
@@ -188,6 +174,7 @@ impl Parse for CrudEntityConfig {
                 .ok_or_else(|| content.error("Missing required field 'permission_prefix'"))?,
             id_type,
             operations,
+            public_operations,
             create_request_type,
             custom_queries,
             custom_list_fn,
@@ -200,12 +187,35 @@ impl Parse for CrudEntityConfig {
     }
 }
 
+// 路由鉴权方式
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RouteAuthKind {
+    Public,   // 公开访问
+    Optional, // 可选登录
+    Required, // 必须登录并校验权限
+}
+
+impl RouteAuthKind {
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "public" => Ok(RouteAuthKind::Public),
+            "optional" => Ok(RouteAuthKind::Optional),
+            "required" => Ok(RouteAuthKind::Required),
+            _ => Err(syn::Error::new_spanned(
+                lit,
+                "auth must be one of \"public\", \"optional\", \"required\"",
+            )),
+        }
+    }
+}
+
 // 路由权限参数
 #[derive(Debug)]
 pub struct RoutePermissionArgs {
     pub path: String,
     pub method: String,
     pub permission: String,
+    pub auth: RouteAuthKind,
 }
 
 impl Parse for RoutePermissionArgs {
@@ -213,6 +223,7 @@ impl Parse for RoutePermissionArgs {
         let mut path = None;
         let mut method = None;
         let mut permission = None;
+        let mut auth = None;
 
         while !input.is_empty() {
             let key: syn::Ident = input.parse()?;
@@ -235,6 +246,20 @@ impl Parse for RoutePermissionArgs {
                         permission = Some(lit.value());
                     }
                 }
+                "public" => {
+                    if let Lit::Bool(lit) = value {
+                        auth = Some(if lit.value() {
+                            RouteAuthKind::Public
+                        } else {
+                            RouteAuthKind::Required
+                        });
+                    }
+                }
+                "auth" => {
+                    if let Lit::Str(lit) = value {
+                        auth = Some(RouteAuthKind::from_lit(&lit)?);
+                    }
+                }
                 _ => {}
             }
 
@@ -243,15 +268,46 @@ impl Parse for RoutePermissionArgs {
             }
         }
 
+        let auth = auth.unwrap_or(RouteAuthKind::Required);
+        // 公开/可选登录的路由可以不声明权限码
+        let permission = match (permission, auth) {
+            (Some(permission), _) => permission,
+            (None, RouteAuthKind::Public | RouteAuthKind::Optional) => String::new(),
+            (None, RouteAuthKind::Required) => {
+                return Err(input.error("permission attribute is required"));
+            }
+        };
+
         Ok(RoutePermissionArgs {
             path: path.ok_or_else(|| input.error("path attribute is required"))?,
             method: method.ok_or_else(|| input.error("method attribute is required"))?,
-            permission: permission
-                .ok_or_else(|| input.error("permission attribute is required"))?,
+            permission,
+            auth,
         })
     }
 }
 
+fn parse_operations(array: ExprArray) -> syn::Result<Vec<CrudOperation>> {
+    let mut ops = Vec::new();
+
+    for elem in array.elems {
+        if let Expr::Lit(lit) = elem {
+            if let syn::Lit::Str(lit_str) = lit.lit {
+                match lit_str.value().as_str() {
+                    "create" => ops.push(CrudOperation::Create),
+                    "read" => ops.push(CrudOperation::Read),
+                    "delete" => ops.push(CrudOperation::Delete),
+                    "list" => ops.push(CrudOperation::List),
+                    _ => {
+                        return Err(syn::Error::new_spanned(lit_str, "Unknown operation"));
+                    }
+                }
+            }
+        }
+    }
+    Ok(ops)
+}
+
 fn parse_openapi_config(input: &ParseStream) -> syn::Result<OpenApiConfig> {
     let content;
     syn::braced!(content in input);
@@ -16,6 +16,7 @@ pub fn crud_entity(input: TokenStream) -> TokenStream {
     let custom_queries = config.custom_queries.unwrap_or_default();
     let custom_list_fn = config.custom_list_fn;
     let custom_read_fn = config.custom_read_fn;
+    let public_operations = config.public_operations.unwrap_or_default();
     let operations = config.operations.unwrap_or_else(|| {
         vec![
             CrudOperation::Create,
@@ -64,6 +65,12 @@ pub fn crud_entity(input: TokenStream) -> TokenStream {
         || custom_queries.contains(&CustomQueryType::Read);
 
     for operation in &operations {
+        // 公开操作无需登录，其余操作必须登录并校验权限码
+        let auth = if public_operations.contains(operation) {
+            "public"
+        } else {
+            "required"
+        };
         match operation {
             CrudOperation::Create => {
                 create_code = generate_create_code(
@@ -71,6 +78,7 @@ pub fn crud_entity(input: TokenStream) -> TokenStream {
                     route_prefix,
                     permission_prefix,
                     &config.create_request_type,
+                    auth,
                 );
                 operation_logs.push(format!(
                     "创建操作: create_{}_handler",
@@ -96,6 +104,7 @@ pub fn crud_entity(input: TokenStream) -> TokenStream {
                     id_type_str,
                     use_custom_read,
                     &custom_read_fn,
+                    auth,
                 );
                 operation_logs.push(format!(
                     "读取操作: get_{}_handler",
@@ -118,6 +127,7 @@ pub fn crud_entity(input: TokenStream) -> TokenStream {
                     &call_expr,
                     &openapi_gen,
                     id_type_str,
+                    auth,
                 );
                 operation_logs.push(format!(
                     "删除操作: delete_{}_handler",
@@ -136,6 +146,7 @@ pub fn crud_entity(input: TokenStream) -> TokenStream {
                     route_prefix,
                     permission_prefix,
                     &openapi_gen,
+                    auth,
                     // use_custom_list,
                     // &custom_list_fn,
                 );
@@ -227,6 +238,7 @@ fn generate_read_code(
     id_type_str: &str,
     use_custom: bool,
     custom_fn: &Option<Ident>,
+    auth: &str,
 ) -> proc_macro2::TokenStream {
     let get_fn = format_ident!("get_{}", entity.to_string().to_lowercase());
     let get_handler = format_ident!("get_{}_handler", entity.to_string().to_lowercase());
@@ -240,7 +252,8 @@ fn generate_read_code(
             #[crate::route_permission(
                 path = #full_path,
                 method = "get",
-                permission = #full_permission
+                permission = #full_permission,
+                auth = #auth
             )]
             pub async fn #get_handler(
                 db: web::Data<DatabaseConnection>,
@@ -277,7 +290,8 @@ fn generate_read_code(
             #[crate::route_permission(
                 path = #full_path,
                 method = "get",
-                permission = #full_permission
+                permission = #full_permission,
+                auth = #auth
             )]
             pub async fn #get_handler(
                 db: web::Data<DatabaseConnection>,
@@ -300,6 +314,7 @@ fn generate_create_code(
     route_prefix: &LitStr,
     permission_prefix: &LitStr,
     create_request_type: &Option<Ident>,
+    auth: &str,
 ) -> proc_macro2::TokenStream {
     let create_fn = format_ident!("create_{}", entity.to_string().to_lowercase());
     let create_handler = format_ident!("create_{}_handler", entity.to_string().to_lowercase());
@@ -343,7 +358,8 @@ fn generate_create_code(
         #[crate::route_permission(
             path = #full_path,
             method = "post",
-            permission = #full_permission
+            permission = #full_permission,
+            auth = #auth
         )]
         pub async fn #create_handler(
             db: web::Data<DatabaseConnection>,
@@ -372,6 +388,7 @@ fn generate_delete_code(
     call_expr: &proc_macro2::TokenStream,
     openapi_gen: &OpenApiGenerator,
     id_type_str: &str,
+    auth: &str,
 ) -> proc_macro2::TokenStream {
     let delete_fn = format_ident!("delete_{}", entity.to_string().to_lowercase());
     let delete_handler = format_ident!("delete_{}_handler", entity.to_string().to_lowercase());
@@ -403,7 +420,8 @@ fn generate_delete_code(
         #[crate::route_permission(
             path = #full_path,
             method = "delete",
-            permission = #full_permission
+            permission = #full_permission,
+            auth = #auth
         )]
         pub async fn #delete_handler(
             db: web::Data<DatabaseConnection>,
@@ -420,6 +438,7 @@ fn generate_list_code(
     route_prefix: &LitStr,
     permission_prefix: &LitStr,
     openapi_gen: &OpenApiGenerator,
+    auth: &str,
 ) -> proc_macro2::TokenStream {
     let get_fn = format_ident!("get_{}_all", entity.to_string().to_lowercase());
     let get_handler = format_ident!("get_{}_all_handler", entity.to_string().to_lowercase());
@@ -477,7 +496,8 @@ fn generate_list_code(
         #[crate::route_permission(
             path = #full_path,
             method = "get",
-            permission = #full_permission
+            permission = #full_permission,
+            auth = #auth
         )]
         pub async fn #get_handler(
             db: web::Data<DatabaseConnection>,
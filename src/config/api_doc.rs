@@ -1,5 +1,4 @@
 use crate::handlers::__path_get_users_handler;
-use crate::handlers::auth;
 use crate::handlers::category::categories_routes::{
     __path_delete_categories_handler, __path_get_categories_all, __path_get_categories_handler,
 };
@@ -7,14 +6,19 @@ use crate::handlers::links::external_links_routes::{
     __path_delete_external_links_handler, __path_get_external_links_all,
     __path_get_external_links_handler,
 };
+use crate::handlers::post_revision::{
+    __path_diff_revisions_handler, __path_get_revision_handler, __path_list_revisions_handler,
+    __path_restore_revision_handler,
+};
 use crate::handlers::posts::{
     __path_create_post_handler, __path_delete_post_handler, __path_get_posts_all_handler,
     __path_get_posts_handler, __path_get_prev_next_handler, __path_get_timeline_handler,
-    __path_update_post_handler,
+    __path_search_posts_handler, __path_update_post_handler,
 };
 use crate::handlers::tags::tags_routes::{
     __path_delete_tags_handler, __path_get_tags_all, __path_get_tags_handler,
 };
+use crate::handlers::{auth, oauth};
 use std::fs::File;
 use std::io::Write;
 use utoipa::OpenApi;
@@ -35,14 +39,28 @@ use utoipa::OpenApi;
     paths(
         auth::register,
         auth::login,
+        auth::verify_two_factor,
+        auth::verify_email,
+        auth::forgot_password,
+        auth::reset_password,
+        auth::refresh,
+        auth::logout,
+        auth::jwks,
+        oauth::oauth_authorize,
+        oauth::oauth_callback,
         get_users_handler,
         get_posts_all_handler,
+        search_posts_handler,
         get_timeline_handler,
         get_posts_handler,
         get_prev_next_handler,
         create_post_handler,
         update_post_handler,
         delete_post_handler,
+        list_revisions_handler,
+        diff_revisions_handler,
+        get_revision_handler,
+        restore_revision_handler,
         get_external_links_handler,
         get_categories_handler,
         get_tags_handler,
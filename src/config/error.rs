@@ -1,4 +1,7 @@
-use actix_web::{HttpResponse, ResponseError, http::StatusCode};
+use actix_web::{
+    HttpResponse, ResponseError,
+    http::{StatusCode, header},
+};
 use serde::Serialize;
 use std::fmt;
 use utoipa::ToSchema;
@@ -23,7 +26,7 @@ pub enum AppError {
     NotFound(String),
     AlreadyExists(String),
     Conflict(String),
-    RateLimited(String),
+    RateLimited(String, u64), // 消息, 需要等待的秒数（Retry-After）
 
     // 文件操作错误 (4xx)
     FileTooLarge(String),
@@ -71,7 +74,7 @@ impl AppError {
             AppError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::AlreadyExists(_) | AppError::Conflict(_) => StatusCode::CONFLICT,
-            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::RateLimited(..) => StatusCode::TOO_MANY_REQUESTS,
             AppError::FileTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
             AppError::UnsupportedFileType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             AppError::DatabaseError(_)
@@ -125,7 +128,7 @@ impl fmt::Display for AppError {
             AppError::NotFound(msg) => write!(f, "未找到: {}", msg),
             AppError::AlreadyExists(msg) => write!(f, "已存在: {}", msg),
             AppError::Conflict(msg) => write!(f, "冲突: {}", msg),
-            AppError::RateLimited(msg) => write!(f, "请求过于频繁: {}", msg),
+            AppError::RateLimited(msg, _) => write!(f, "请求过于频繁: {}", msg),
             AppError::FileTooLarge(msg) => write!(f, "文件太大: {}", msg),
             AppError::UnsupportedFileType(msg) => write!(f, "不支持的文件类型: {}", msg),
             AppError::UploadFailed(msg) => write!(f, "上传失败: {}", msg),
@@ -151,7 +154,11 @@ impl fmt::Display for AppError {
 // 实现 ResponseError trait 用于 Actix-web 错误处理
 impl ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(self.status_code()).json(self.to_response())
+        let mut response = HttpResponse::build(self.status_code());
+        if let AppError::RateLimited(_, retry_after) = self {
+            response.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+        response.json(self.to_response())
     }
 }
 
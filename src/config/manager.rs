@@ -3,7 +3,17 @@ use serde::{Deserialize, Serialize};
 
 use std::{env, sync::LazyLock};
 
-use crate::{email::SmtpSettings, upload::QiNiuSettings};
+use crate::{
+    account::AccountSettings,
+    email::SmtpSettings,
+    login_history::LoginHistorySettings,
+    oauth::{OAuthProviderSettings, OAuthSettings},
+    posts::PostSettings,
+    sms::SmsSettings,
+    two_factor::TwoFactorSettings,
+    upload::QiNiuSettings,
+    utils::{crypto_pwd::PasswordSettings, rate_limit::RateLimitSettings},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerSettings {
@@ -12,7 +22,6 @@ pub struct ServerSettings {
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecuritySettings {
-    pub salt: String,
     pub token_expiration: u64,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,9 +35,18 @@ pub struct AppConfig {
     pub jwt: JwtSettings,
     pub qi_niu: QiNiuSettings,
     pub smtp: SmtpSettings,
+    pub sms: SmsSettings,
+    pub two_factor: TwoFactorSettings,
+    pub rate_limit: RateLimitSettings,
+    pub account: AccountSettings,
+    pub login_history: LoginHistorySettings,
+    pub post: PostSettings,
+    pub oauth: OAuthSettings,
     pub security: SecuritySettings,
+    pub password: PasswordSettings,
     pub upload: UploadSettings,
     pub server: ServerSettings,
+    pub permission_sync: PermissionSyncSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,10 +63,42 @@ pub struct DatabaseSettings {
     pub set_schema_search_path: String,
 }
 
+/// 路由权限码同步配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionSyncSettings {
+    /// 启动时是否把路由注册表中的权限码同步到 permissions 表
+    pub sync_on_startup: bool,
+    /// 新增的权限码自动授予该角色（角色代码），为空则不自动授予
+    pub auto_grant_role: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JwtSettings {
+    /// 签名算法：HS256 / EdDSA / RS256
+    pub algorithm: String,
+    /// HS256 使用的 base64 密钥
     pub secret: String,
+    /// EdDSA / RS256 私钥 PEM 路径
+    pub private_key_path: Option<String>,
+    /// 当前签名密钥的 kid
+    pub key_id: Option<String>,
+    /// 可用于验签的公钥列表，格式为 `kid=公钥PEM路径`
+    pub public_keys: Vec<String>,
+    /// 访问令牌有效期（秒）
     pub expiry: i64,
+    /// 刷新令牌有效期（秒）
+    pub refresh_expiry: i64,
+    /// 允许从哪些位置读取访问令牌，按顺序尝试
+    pub token_sources: Vec<TokenSource>,
+}
+
+/// 访问令牌的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenSource {
+    /// `Authorization: Bearer <jwt>` 请求头
+    Bearer,
+    /// `access_token` Cookie
+    Cookie,
 }
 
 // 其他设置结构...
@@ -97,11 +147,38 @@ impl Default for AppConfig {
                     .unwrap_or_else(|_| "public".to_string()),
             },
             jwt: JwtSettings {
+                algorithm: env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string()),
                 secret: env::var("JWT_SECRET").unwrap_or_else(|_| "your_secret_key".to_string()),
+                private_key_path: env::var("JWT_PRIVATE_KEY_PATH").ok(),
+                key_id: env::var("JWT_KEY_ID").ok(),
+                public_keys: env::var("JWT_PUBLIC_KEYS")
+                    .map(|keys| {
+                        keys.split(',')
+                            .map(str::trim)
+                            .filter(|key| !key.is_empty())
+                            .map(String::from)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
                 expiry: env::var("JWT_EXPIRATION")
-                    .unwrap_or_else(|_| "86400".to_string())
+                    .unwrap_or_else(|_| "900".to_string())
+                    .parse()
+                    .unwrap(),
+                refresh_expiry: env::var("JWT_REFRESH_EXPIRATION")
+                    .unwrap_or_else(|_| "2592000".to_string())
                     .parse()
                     .unwrap(),
+                token_sources: env::var("JWT_TOKEN_SOURCES")
+                    .unwrap_or_else(|_| "bearer,cookie".to_string())
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|source| !source.is_empty())
+                    .map(|source| match source.to_ascii_lowercase().as_str() {
+                        "bearer" => TokenSource::Bearer,
+                        "cookie" => TokenSource::Cookie,
+                        other => panic!("JWT_TOKEN_SOURCES 不支持的令牌来源: {other}"),
+                    })
+                    .collect(),
             },
             qi_niu: QiNiuSettings {
                 access_key: env::var("QINIU_ACCESS_KEY")
@@ -132,13 +209,119 @@ impl Default for AppConfig {
                     .parse()
                     .unwrap(),
             },
+            sms: SmsSettings {
+                provider: env::var("SMS_PROVIDER").unwrap_or_else(|_| "log".to_string()),
+                file_path: env::var("SMS_FILE_PATH")
+                    .unwrap_or_else(|_| "./logs/sms.log".to_string()),
+                code_validity_period: env::var("SMS_CODE_VALIDITY_PERIOD")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()
+                    .unwrap(),
+            },
+            rate_limit: RateLimitSettings {
+                enabled: env::var("RATE_LIMIT_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap(),
+                trust_proxy: env::var("RATE_LIMIT_TRUST_PROXY")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .unwrap(),
+                login: env::var("RATE_LIMIT_LOGIN")
+                    .unwrap_or_else(|_| "10/60".to_string())
+                    .parse()
+                    .unwrap(),
+                verification_code: env::var("RATE_LIMIT_VERIFICATION_CODE")
+                    .unwrap_or_else(|_| "5/300".to_string())
+                    .parse()
+                    .unwrap(),
+                verification_code_target: env::var("RATE_LIMIT_VERIFICATION_CODE_TARGET")
+                    .unwrap_or_else(|_| "1/60".to_string())
+                    .parse()
+                    .unwrap(),
+                lockout_threshold: env::var("LOGIN_LOCKOUT_THRESHOLD")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap(),
+                lockout_base: env::var("LOGIN_LOCKOUT_BASE")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .unwrap(),
+                lockout_max: env::var("LOGIN_LOCKOUT_MAX")
+                    .unwrap_or_else(|_| "3600".to_string())
+                    .parse()
+                    .unwrap(),
+            },
+            account: AccountSettings {
+                link_base_url: env::var("ACCOUNT_LINK_BASE_URL")
+                    .unwrap_or_else(|_| "http://localhost:3000".to_string()),
+                email_verification_ttl: env::var("EMAIL_VERIFICATION_TTL")
+                    .unwrap_or_else(|_| "86400".to_string())
+                    .parse()
+                    .unwrap(),
+                password_reset_ttl: env::var("PASSWORD_RESET_TTL")
+                    .unwrap_or_else(|_| "1800".to_string())
+                    .parse()
+                    .unwrap(),
+            },
+            login_history: LoginHistorySettings {
+                notify_new_device: env::var("LOGIN_NOTIFY_NEW_DEVICE")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .unwrap(),
+            },
+            post: PostSettings {
+                scheduler_interval: env::var("POST_SCHEDULER_INTERVAL")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .unwrap(),
+            },
+            two_factor: TwoFactorSettings {
+                issuer: env::var("TWO_FACTOR_ISSUER").unwrap_or_else(|_| "Blog".to_string()),
+                challenge_ttl: env::var("TWO_FACTOR_CHALLENGE_TTL")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()
+                    .unwrap(),
+            },
             security: SecuritySettings {
-                salt: env::var("SECURITY_SALT").unwrap_or_else(|_| "your_salt".to_string()),
                 token_expiration: env::var("SECURITY_TOKEN_EXPIRATION")
                     .unwrap_or_else(|_| "86400".to_string())
                     .parse()
                     .unwrap(),
             },
+            password: PasswordSettings {
+                min_length: env::var("PASSWORD_MIN_LENGTH")
+                    .unwrap_or_else(|_| "8".to_string())
+                    .parse()
+                    .unwrap(),
+                max_length: env::var("PASSWORD_MAX_LENGTH")
+                    .unwrap_or_else(|_| "100".to_string())
+                    .parse()
+                    .unwrap(),
+                min_char_classes: env::var("PASSWORD_MIN_CHAR_CLASSES")
+                    .unwrap_or_else(|_| "2".to_string())
+                    .parse()
+                    .unwrap(),
+                reject_username: env::var("PASSWORD_REJECT_USERNAME")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap(),
+                blocklist_file: env::var("PASSWORD_BLOCKLIST_FILE")
+                    .ok()
+                    .filter(|path| !path.is_empty()),
+                argon2_memory_kib: env::var("ARGON2_MEMORY_KIB")
+                    .unwrap_or_else(|_| "19456".to_string())
+                    .parse()
+                    .unwrap(),
+                argon2_iterations: env::var("ARGON2_ITERATIONS")
+                    .unwrap_or_else(|_| "2".to_string())
+                    .parse()
+                    .unwrap(),
+                argon2_parallelism: env::var("ARGON2_PARALLELISM")
+                    .unwrap_or_else(|_| "1".to_string())
+                    .parse()
+                    .unwrap(),
+            },
             upload: UploadSettings {
                 max_size: env::var("UPLOAD_MAX_SIZE")
                     .unwrap_or_else(|_| "10485760".to_string())
@@ -154,12 +337,62 @@ impl Default for AppConfig {
                     .unwrap(),
                 // debug: std::env::var("CRUD_MACRO_DEBUG").is_ok(),
             },
+            oauth: OAuthSettings {
+                providers: env::var("OAUTH_PROVIDERS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(oauth_provider_from_env)
+                    .collect(),
+            },
+            permission_sync: PermissionSyncSettings {
+                sync_on_startup: env::var("PERMISSION_SYNC_ON_STARTUP")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap(),
+                auto_grant_role: Some(
+                    env::var("PERMISSION_AUTO_GRANT_ROLE")
+                        .unwrap_or_else(|_| "SUPER_ADMIN".to_string()),
+                )
+                .filter(|role| !role.is_empty()),
+            },
         }
         //     enabled: std::env::var("CRUD_MACRO_DEBUG").is_ok(),
         //     colorize: true,
         // }
     }
 }
+/// 读取单个第三方登录服务商的配置，环境变量前缀为 `OAUTH_<NAME>_`
+fn oauth_provider_from_env(name: &str) -> OAuthProviderSettings {
+    let prefix = format!("OAUTH_{}_", name.to_uppercase());
+    let var = |key: &str| env::var(format!("{prefix}{key}")).ok();
+    let kind = var("KIND").unwrap_or_else(|| {
+        if name == "github" {
+            "github".to_string()
+        } else {
+            "oidc".to_string()
+        }
+    });
+    let default_scopes = if kind == "github" {
+        "read:user user:email"
+    } else {
+        "openid profile email"
+    };
+    OAuthProviderSettings {
+        name: name.to_string(),
+        scopes: var("SCOPES").unwrap_or_else(|| default_scopes.to_string()),
+        kind,
+        client_id: var("CLIENT_ID").unwrap_or_default(),
+        client_secret: var("CLIENT_SECRET").unwrap_or_default(),
+        redirect_uri: var("REDIRECT_URI").unwrap_or_default(),
+        issuer: var("ISSUER"),
+        authorize_url: var("AUTHORIZE_URL"),
+        token_url: var("TOKEN_URL"),
+        userinfo_url: var("USERINFO_URL"),
+    }
+}
+
 pub static CONFIG: LazyLock<AppConfig> = LazyLock::new(AppConfig::default);
 
 impl AppConfig {
@@ -0,0 +1,61 @@
+use crate::dto::user::RE_PHONE;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// 邮箱验证请求（前端从链接中取出令牌提交）
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, message = "令牌不能为空"))]
+    pub token: String,
+}
+
+/// 找回密码请求
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "邮箱格式错误"))]
+    pub email: String,
+}
+
+/// 重置密码请求
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1, message = "令牌不能为空"))]
+    pub token: String,
+    /// 新密码，按配置的密码策略校验
+    pub new_password: String,
+}
+
+/// 修改个人资料，只更新提交的字段
+#[derive(Debug, Default, Validate, Deserialize, ToSchema)]
+pub struct UpdateProfileRequest {
+    #[validate(length(min = 5, max = 100, message = "用户名长度必须在5到100之间"))]
+    pub user_name: Option<String>,
+    /// 头像 URL
+    #[validate(length(max = 255, message = "头像地址过长"))]
+    pub image: Option<String>,
+    /// 新邮箱，修改后需要重新验证
+    #[validate(email(message = "邮箱格式错误"))]
+    pub email: Option<String>,
+    /// 新手机号，需同时提交发送到新手机号的短信验证码
+    #[validate(regex(path = "*RE_PHONE", message = "手机号格式错误"))]
+    pub phone: Option<String>,
+    #[validate(length(min = 6, max = 6, message = "验证码格式错误"))]
+    pub phone_code: Option<String>,
+}
+
+/// 修改密码请求
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "当前密码不能为空"))]
+    pub current_password: String,
+    /// 新密码，按配置的密码策略校验
+    pub new_password: String,
+}
+
+/// 注销账号请求，需再次确认密码
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    #[validate(length(min = 1, message = "密码不能为空"))]
+    pub password: String,
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+fn default_expires_in_days() -> i64 {
+    30
+}
+
+/// 创建个人访问令牌请求体
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateApiTokenRequest {
+    #[validate(length(min = 1, max = 64, message = "令牌名称长度必须在1-64个字符之间"))]
+    #[schema(example = "ci-publish")]
+    pub name: String,
+    /// 授权范围（权限码），只能选择自己当前拥有的权限
+    #[validate(length(min = 1, message = "至少需要选择一个授权范围"))]
+    #[schema(example = json!(["post:create", "post:update:own"]))]
+    pub scopes: Vec<String>,
+    /// 有效天数，默认30天
+    #[validate(range(min = 1, max = 365, message = "有效天数必须在1-365之间"))]
+    #[serde(default = "default_expires_in_days")]
+    pub expires_in_days: i64,
+}
+
+/// 个人访问令牌信息（不含明文）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiTokenResponse {
+    pub id: i32,
+    pub name: String,
+    /// 令牌前缀，用于识别是哪一个令牌
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub expires_at: DateTime<Utc>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// 新建令牌的响应，明文令牌只在创建时返回一次
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiTokenResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiTokenResponse,
+}
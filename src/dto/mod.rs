@@ -2,8 +2,15 @@ pub mod user;
 pub use user::RegisterResponse;
 pub mod common;
 pub use common::{PaginatedResp, Pagination, PaginationQuery};
+pub mod account;
+pub mod api_token;
 pub mod category;
 pub mod image;
 pub mod link;
+pub mod oauth;
+pub mod post_revision;
 pub mod posts;
+pub mod rbac;
+pub mod session;
 pub mod tag;
+pub mod two_factor;
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 发起第三方授权的查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OAuthAuthorizeQuery {
+    /// 为 true 时把第三方账号绑定到当前登录用户，而不是登录
+    #[serde(default)]
+    pub link: bool,
+}
+
+/// 第三方授权地址
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthAuthorizeResponse {
+    /// 前端跳转到该地址完成授权
+    pub authorize_url: String,
+    pub state: String,
+}
+
+/// 授权回调的查询参数（由前端回调页原样转发）
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OAuthCallbackQuery {
+    pub code: String,
+    pub state: String,
+}
+
+/// 第三方账号绑定信息
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthBindingResponse {
+    pub provider: String,
+    pub email: Option<String>,
+    pub display_name: Option<String>,
+    pub avatar: Option<String>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub created_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,67 @@
+use crate::models::post_revisions;
+use crate::utils::text_diff::DiffLine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// 修订记录列表项
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PostRevisionSummary {
+    pub id: i32,
+    pub title: String,
+    /// 用这次修改覆盖该版本的用户
+    pub editor: Option<String>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// 修订记录详情
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PostRevisionResponse {
+    pub id: i32,
+    pub title: String,
+    pub summary: Option<String>,
+    pub content: String,
+    pub markdowncontent: String,
+    pub category_id: Option<i32>,
+    pub tag_ids: Vec<i32>,
+    pub editor: Option<String>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl PostRevisionResponse {
+    pub fn from_model(revision: post_revisions::Model, editor: Option<String>) -> Self {
+        Self {
+            tag_ids: serde_json::from_value(revision.tag_ids).unwrap_or_default(),
+            id: revision.id,
+            title: revision.title,
+            summary: revision.summary,
+            content: revision.content,
+            markdowncontent: revision.markdowncontent,
+            category_id: revision.category_id,
+            editor,
+            created_at: revision.created_at.map(|t| t.to_utc()),
+        }
+    }
+}
+
+/// 比较两个修订版本
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct RevisionDiffQuery {
+    /// 旧版本的修订ID
+    pub from: i32,
+    /// 新版本的修订ID，不传时与文章当前内容比较
+    pub to: Option<i32>,
+}
+
+/// 两个版本之间的逐行差异
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PostRevisionDiff {
+    pub from: i32,
+    /// 为空表示文章当前内容
+    pub to: Option<i32>,
+    pub title: Vec<DiffLine>,
+    pub markdowncontent: Vec<DiffLine>,
+}
@@ -1,9 +1,13 @@
 // src/models/responses.rs
-use crate::utils::fmt_beijing;
+use crate::models::posts::PostStatus;
+use crate::utils::markdown::TocEntry;
+use crate::utils::slug;
+use crate::utils::{fmt_beijing, fmt_beijing_opt};
 use sea_orm::FromQueryResult;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
-use validator::Validate;
+use std::borrow::Cow;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct CategoryResponse {
@@ -21,22 +25,35 @@ pub struct TagResponse {
 pub struct PostListResponse {
     pub id: i32,
     pub uuid: String,
+    pub slug: String,
     pub title: String,
     pub cover: String,
+    /// 作者用户名
     pub author: String,
+    /// 作者头像（users.image）
+    pub author_avatar: Option<String>,
+    /// Markdown 原文
     pub content: String,
-    #[schema(value_type = String, format = DateTime)]
-    #[serde(serialize_with = "fmt_beijing")]
-    pub publish_time: chrono::DateTime<chrono::Utc>,
+    /// 服务端渲染并清洗后的 HTML
+    pub html: String,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    #[serde(serialize_with = "fmt_beijing_opt")]
+    pub publish_time: Option<chrono::DateTime<chrono::Utc>>,
     #[schema(value_type = String, format = DateTime)]
     #[serde(serialize_with = "fmt_beijing")]
     pub update_time: chrono::DateTime<chrono::Utc>,
     pub views: i32,
     pub is_top: bool,
+    pub status: PostStatus,
     pub is_publish: bool,
     pub is_hide: bool,
     pub description: String,
+    /// 字数，中日韩字符按字计，其他语言按词计
     pub size: i32,
+    /// 预计阅读分钟数
+    pub reading_minutes: i32,
+    /// 根据标题生成的目录
+    pub toc: Vec<TocEntry>,
     pub category: Option<CategoryResponse>,
     pub tags: Vec<TagResponse>,
 }
@@ -46,17 +63,22 @@ pub struct PostListResponse {
 pub struct PostResponse {
     pub id: i32,
     pub uuid: String,
+    pub slug: String,
     pub title: String,
     pub cover: String,
+    /// 作者用户名
     pub author: String,
-    #[schema(value_type = String, format = DateTime)]
-    #[serde(serialize_with = "fmt_beijing")]
-    pub publish_time: chrono::DateTime<chrono::Utc>,
+    /// 作者头像（users.image）
+    pub author_avatar: Option<String>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    #[serde(serialize_with = "fmt_beijing_opt")]
+    pub publish_time: Option<chrono::DateTime<chrono::Utc>>,
     #[schema(value_type = String, format = DateTime)]
     #[serde(serialize_with = "fmt_beijing")]
     pub update_time: chrono::DateTime<chrono::Utc>,
     pub views: i32,
     pub is_top: bool,
+    pub status: PostStatus,
     pub is_publish: bool,
     pub is_hide: bool,
     pub description: String,
@@ -65,17 +87,27 @@ pub struct PostResponse {
     pub tags: Vec<TagResponse>,
 }
 
+fn validate_slug(value: &str) -> Result<(), ValidationError> {
+    slug::check(value)
+        .map_err(|msg| ValidationError::new("invalid_slug").with_message(Cow::Borrowed(msg)))
+}
+
 /// 用于创建文章的请求体
 #[derive(Debug, Deserialize, ToSchema, Validate)]
 pub struct CreatePostRequest {
     #[validate(length(min = 1, max = 255, message = "标题长度必须在1-255个字符之间"))]
     pub title: String,
 
+    /// 固定链接中使用的 slug，不传时由标题生成（中文转为拼音）
+    #[validate(custom(function = "validate_slug"))]
+    pub slug: Option<String>,
+
     #[validate(length(max = 500, message = "摘要长度不能超过500个字符"))]
     pub summary: Option<String>,
 
-    #[validate(length(min = 1, message = "内容不能为空"))]
-    pub content: String,
+    /// 已废弃：HTML 由服务端根据 `markdowncontent` 渲染，传入的值会被忽略
+    #[serde(default)]
+    pub content: Option<String>,
 
     #[validate(length(min = 1, message = "Markdown内容不能为空"))]
     pub markdowncontent: String,
@@ -87,7 +119,11 @@ pub struct CreatePostRequest {
     #[validate(length(min = 1, message = "至少需要选择一个标签"))]
     pub tag_ids: Vec<i32>,
 
-    pub status: i16, // 0 草稿 1 发布 2 下线
+    pub status: PostStatus,
+
+    /// 定时发布的时间，状态为 `scheduled` 时必填且须晚于当前时间
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
 
     pub featured: bool,
 }
@@ -98,9 +134,14 @@ pub struct UpdatePostRequest {
     #[validate(length(min = 1, max = 255, message = "标题长度必须在1-255个字符之间"))]
     pub title: Option<String>,
 
+    /// 修改 slug，旧的 slug 会 301 重定向到新的；修改标题不会自动改变 slug
+    #[validate(custom(function = "validate_slug"))]
+    pub slug: Option<String>,
+
     #[validate(length(max = 500, message = "摘要长度不能超过500个字符"))]
     pub summary: Option<String>,
 
+    /// 已废弃：HTML 由服务端根据 `markdowncontent` 渲染，传入的值会被忽略
     pub content: Option<String>,
 
     pub markdowncontent: Option<String>,
@@ -111,7 +152,11 @@ pub struct UpdatePostRequest {
 
     pub tag_ids: Option<Vec<i32>>,
 
-    pub status: Option<i16>, // 0 草稿 1 发布 2 下线
+    pub status: Option<PostStatus>,
+
+    /// 定时发布的时间，切换为或保持 `scheduled` 状态时使用
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
 
     pub featured: Option<bool>,
 }
@@ -127,6 +172,7 @@ pub struct TimelineResponse {
 pub struct PrevNextResponse {
     pub title: String,
     pub uuid: String,
+    pub slug: String,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -136,3 +182,52 @@ pub struct PrevNextResult {
     #[serde(rename = "nextArticle")]
     pub next_article: Option<PrevNextResponse>,
 }
+
+/// 文章搜索参数
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct PostSearchQuery {
+    /// 搜索关键词，多个关键词以空格分隔，需全部命中
+    #[validate(length(min = 1, max = 100, message = "搜索关键词长度必须在1-100个字符之间"))]
+    pub q: String,
+    /// 页码
+    #[validate(range(min = 1, message = "页码必须大于1"))]
+    #[serde(default = "default_page")]
+    pub page: u64,
+    /// 每页数量
+    #[validate(range(min = 1, max = 50, message = "每页数量必须在1-50之间"))]
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+    /// 分类ID
+    pub category: Option<i32>,
+    /// 标签ID
+    pub tag: Option<i32>,
+    /// 文章状态，匿名访问时只能搜到已发布的文章
+    #[param(value_type = Option<String>)]
+    pub status: Option<PostStatus>,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_limit() -> u64 {
+    10
+}
+
+/// 搜索命中的高亮片段，关键词以 `<mark>` 包裹，其余内容已做 HTML 转义
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchHighlight {
+    pub title: String,
+    pub summary: Option<String>,
+    /// 正文中第一处命中附近的片段，正文未命中时为空
+    pub content: Option<String>,
+}
+
+/// 文章搜索结果，按相关度排序
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PostSearchResult {
+    #[serde(flatten)]
+    pub post: PostResponse,
+    pub highlight: SearchHighlight,
+}
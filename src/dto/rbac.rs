@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// 创建角色请求体
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateRoleRequest {
+    #[validate(length(min = 1, max = 50, message = "角色代码长度必须在1-50个字符之间"))]
+    #[schema(example = "REVIEWER")]
+    pub code: String,
+    #[validate(length(min = 1, max = 100, message = "角色名称长度必须在1-100个字符之间"))]
+    #[schema(example = "审核员")]
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// 更新角色请求体（角色代码不可修改）
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateRoleRequest {
+    #[validate(length(min = 1, max = 100, message = "角色名称长度必须在1-100个字符之间"))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// 创建权限请求体
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreatePermissionRequest {
+    #[validate(length(min = 1, max = 100, message = "权限代码长度必须在1-100个字符之间"))]
+    #[schema(example = "post:publish")]
+    pub code: String,
+    #[validate(length(min = 1, max = 100, message = "权限名称长度必须在1-100个字符之间"))]
+    #[schema(example = "发布文章")]
+    pub name: String,
+    pub description: Option<String>,
+    #[schema(example = "content")]
+    pub category: Option<String>,
+}
+
+/// 更新权限请求体（权限代码不可修改）
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdatePermissionRequest {
+    #[validate(length(min = 1, max = 100, message = "权限名称长度必须在1-100个字符之间"))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+}
+
+/// 为角色挂载权限
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct AttachPermissionsRequest {
+    #[validate(length(min = 1, message = "权限ID列表不能为空"))]
+    pub permission_ids: Vec<i32>,
+}
+
+/// 为用户分配角色
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignRoleRequest {
+    pub role_id: i32,
+    /// 是否设为主要角色，设置后该用户其他角色的主要标记会被清除
+    #[serde(default)]
+    pub is_primary: bool,
+}
+
+/// 为用户授予（或显式拒绝）权限
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GrantUserPermissionRequest {
+    pub permission_id: i32,
+    /// true 表示授予，false 表示拒绝
+    #[serde(default = "default_granted")]
+    pub granted: bool,
+    /// 过期时间，为空表示永久有效
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+fn default_granted() -> bool {
+    true
+}
+
+/// 角色详情（含权限码）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoleResponse {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_system: bool,
+    pub permissions: Vec<String>,
+}
+
+/// 权限详情
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PermissionResponse {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+}
+
+/// 用户的角色
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserRoleResponse {
+    pub role_id: i32,
+    pub code: String,
+    pub name: String,
+    pub is_primary: bool,
+}
+
+/// 用户级权限覆盖
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserPermissionResponse {
+    pub permission_id: i32,
+    pub code: String,
+    pub granted: bool,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// 路由注册表中声明的权限码
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegistryPermissionResponse {
+    pub code: String,
+    /// 使用该权限码的路由，如 `GET /api/v1/users`
+    pub routes: Vec<String>,
+    /// 权限表中是否缺少该权限码
+    pub missing: bool,
+}
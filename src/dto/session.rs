@@ -0,0 +1,63 @@
+use crate::models::{login_history, user_sessions};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// 登录会话
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionResponse {
+    pub id: String,
+    pub login_method: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// 是否为发起请求的当前会话
+    pub current: bool,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[schema(value_type = String, format = DateTime)]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl SessionResponse {
+    pub fn from_model(session: user_sessions::Model, current: bool) -> Self {
+        Self {
+            id: session.id,
+            login_method: session.login_method,
+            ip: session.ip,
+            user_agent: session.user_agent,
+            current,
+            created_at: session.created_at.map(|t| t.to_utc()),
+            last_used_at: session.last_used_at.map(|t| t.to_utc()),
+            expires_at: session.expires_at.to_utc(),
+        }
+    }
+}
+
+/// 登录记录
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoginHistoryResponse {
+    pub id: i32,
+    pub login_method: String,
+    pub success: bool,
+    pub failure_reason: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<login_history::Model> for LoginHistoryResponse {
+    fn from(record: login_history::Model) -> Self {
+        Self {
+            id: record.id,
+            login_method: record.login_method,
+            success: record.success,
+            failure_reason: record.failure_reason,
+            ip: record.ip,
+            user_agent: record.user_agent,
+            created_at: record.created_at.map(|t| t.to_utc()),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// 两步验证状态
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorStatusResponse {
+    pub enabled: bool,
+    /// 剩余可用的恢复码数量
+    pub recovery_codes_remaining: u64,
+}
+
+/// 生成的 TOTP 密钥，用认证器扫描 `otpauth_uri` 或手动输入 `secret`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorSetupResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// 需要验证码确认的操作（启用、关闭、重新生成恢复码）
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct TwoFactorCodeRequest {
+    /// 认证器上的 6 位验证码，关闭时也可以使用恢复码
+    #[validate(length(min = 6, max = 32, message = "验证码格式错误"))]
+    pub code: String,
+}
+
+/// 恢复码，明文只返回这一次
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// 密码正确但需要两步验证时的登录响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorChallengeResponse {
+    pub two_factor_required: bool,
+    /// 提交到 `/api/v1/auth/2fa/verify` 的挑战令牌
+    pub challenge_token: String,
+    /// 挑战令牌有效期（秒）
+    pub expires_in: u64,
+}
+
+/// 登录第二步
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct TwoFactorLoginRequest {
+    #[validate(length(min = 1, message = "挑战令牌不能为空"))]
+    pub challenge_token: String,
+    /// 认证器上的 6 位验证码或恢复码
+    #[validate(length(min = 6, max = 32, message = "验证码格式错误"))]
+    pub code: String,
+}
@@ -1,3 +1,5 @@
+use crate::models::users::UserStatus;
+use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -10,7 +12,7 @@ pub struct PasswordLogin {
     #[validate(length(min = 1, max = 100))]
     pub account: String,
 
-    #[validate(length(min = 6, max = 100))]
+    #[validate(length(min = 1, max = 100))]
     pub password: String,
 }
 
@@ -73,10 +75,30 @@ impl LoginRequest {
             LoginRequest::OAuth(v) => v.validate(),
         }
     }
+
+    /// 登录方式，与 `login_type` 一致
+    pub fn method(&self) -> &'static str {
+        match self {
+            LoginRequest::Password(_) => "password",
+            LoginRequest::Email(_) => "email",
+            LoginRequest::Phone(_) => "phone",
+            LoginRequest::OAuth(_) => "oauth",
+        }
+    }
+
+    /// 提交的账号标识，用于登录记录
+    pub fn account(&self) -> &str {
+        match self {
+            LoginRequest::Password(v) => &v.account,
+            LoginRequest::Email(v) => &v.email,
+            LoginRequest::Phone(v) => &v.phone,
+            LoginRequest::OAuth(v) => &v.provider,
+        }
+    }
 }
 // 手机号正则
 lazy_static! {
-    static ref RE_PHONE: Regex = Regex::new(r"^1[3-9]\d{9}$").unwrap();
+    pub static ref RE_PHONE: Regex = Regex::new(r"^1[3-9]\d{9}$").unwrap();
 }
 // #[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
 // pub struct LoginRequest {
@@ -93,8 +115,11 @@ lazy_static! {
 pub struct RegisterResponse {
     #[validate(length(min = 5, max = 100, message = "用户名长度必须在5到100之间"))]
     pub user_name: String,
-    #[validate(length(min = 6, max = 100, message = "密码长度必须在6到100之间"))]
+    /// 密码，按配置的密码策略校验
     pub pass_word: String,
+    /// 邮箱（可选），填写后会发送验证链接
+    #[validate(email(message = "邮箱格式错误"))]
+    pub email: Option<String>,
 }
 #[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct ValidationErrorItem {
@@ -148,11 +173,29 @@ impl fmt::Display for ValidationErrorMsg<'_> {
     }
 }
 
-#[derive(Deserialize, Debug, Default, Clone, Serialize, Validate)]
+/// 管理员修改用户资料，只更新提交的字段；角色与权限通过 `/api/v1/admin/users/{uuid}/roles` 等接口管理
+#[derive(Deserialize, Debug, Default, Clone, Serialize, Validate, ToSchema)]
 pub struct UpdateUserRequest {
     #[validate(length(min = 5, max = 100, message = "用户名长度必须在5到100之间"))]
-    pub user_name: String,
-    #[serde(rename = "image")]
+    pub user_name: Option<String>,
+    #[validate(length(max = 255, message = "头像地址过长"))]
     pub image: Option<String>,
-    pub permissions: Option<Vec<String>>,
+    #[validate(email(message = "邮箱格式错误"))]
+    pub email: Option<String>,
+    #[validate(regex(path = "*RE_PHONE", message = "手机号格式错误"))]
+    pub phone: Option<String>,
+    /// 直接标记邮箱的验证状态
+    pub email_verified: Option<bool>,
+}
+
+/// 管理员设置账号状态；注销请使用删除接口
+#[derive(Deserialize, Debug, Clone, Serialize, Validate, ToSchema)]
+pub struct UpdateUserStatusRequest {
+    pub status: UserStatus,
+    /// 停用或封禁原因
+    #[validate(length(max = 255, message = "原因不能超过255个字符"))]
+    pub reason: Option<String>,
+    /// 封禁截止时间，只对 `banned` 有效，为空表示永久封禁
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub banned_until: Option<DateTime<Utc>>,
 }
@@ -0,0 +1,171 @@
+use crate::dto::PaginationQuery;
+use crate::dto::account::{ChangePasswordRequest, DeleteAccountRequest, UpdateProfileRequest};
+use crate::handlers::validate_body;
+use crate::middleware::CurrentUser;
+use crate::services::{
+    AccountService, EmailService, LoginHistoryService, SessionService, SmsVerificationManager,
+};
+use crate::utils::auth_cookie::clear_auth_cookies;
+use crate::{ApiResponse, HttpResult};
+use actix_web::{HttpResponse, web};
+use route_macros::route_permission;
+use sea_orm::DatabaseConnection;
+use serde_json::json;
+
+/// 获取个人资料
+#[route_permission(path = "/api/v1/users/me", method = "get", permission = "user:profile")]
+pub async fn get_profile_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+) -> HttpResult {
+    let profile = AccountService::profile(&db_pool, user.user_id).await?;
+    Ok(ApiResponse::success(profile, "获取个人资料成功").to_http_response())
+}
+
+/// 修改个人资料
+#[route_permission(
+    path = "/api/v1/users/me",
+    method = "patch",
+    permission = "user:profile"
+)]
+pub async fn update_profile_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    email_service: web::Data<EmailService>,
+    sms_manager: web::Data<SmsVerificationManager>,
+    user: CurrentUser,
+    body: web::Json<UpdateProfileRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let profile = AccountService::update_profile(
+        &db_pool,
+        &email_service,
+        &sms_manager,
+        &user,
+        body.into_inner(),
+    )
+    .await?;
+    Ok(ApiResponse::success(profile, "个人资料已更新").to_http_response())
+}
+
+/// 修改密码，成功后需要重新登录
+#[route_permission(
+    path = "/api/v1/users/me/password",
+    method = "post",
+    permission = "user:profile"
+)]
+pub async fn change_password_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    body: web::Json<ChangePasswordRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    AccountService::change_password(&db_pool, &user, &body).await?;
+    Ok(signed_out("密码已修改，请重新登录"))
+}
+
+/// 注销账号
+#[route_permission(
+    path = "/api/v1/users/me",
+    method = "delete",
+    permission = "user:profile"
+)]
+pub async fn delete_account_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    body: web::Json<DeleteAccountRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    AccountService::delete_account(&db_pool, &user, &body.password).await?;
+    Ok(signed_out("账号已注销"))
+}
+
+/// 重新发送邮箱验证链接
+#[route_permission(
+    path = "/api/v1/users/me/email-verification",
+    method = "post",
+    permission = "user:profile"
+)]
+pub async fn resend_email_verification_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    email_service: web::Data<EmailService>,
+    user: CurrentUser,
+) -> HttpResult {
+    AccountService::resend_email_verification(&db_pool, &email_service, &user).await?;
+    Ok(ApiResponse::success((), "验证邮件已发送，请查收").to_http_response())
+}
+
+/// 我的登录会话（登录设备）
+#[route_permission(
+    path = "/api/v1/users/me/sessions",
+    method = "get",
+    permission = "user:profile"
+)]
+pub async fn list_sessions_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+) -> HttpResult {
+    let sessions = SessionService::list(&db_pool, &user).await?;
+    Ok(ApiResponse::success(sessions, "获取登录会话成功").to_http_response())
+}
+
+/// 退出当前会话以外的全部会话
+#[route_permission(
+    path = "/api/v1/users/me/sessions",
+    method = "delete",
+    permission = "user:profile"
+)]
+pub async fn revoke_other_sessions_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+) -> HttpResult {
+    let revoked = SessionService::revoke_others(&db_pool, &user).await?;
+    Ok(
+        ApiResponse::success(json!({ "revoked": revoked }), "其他设备已退出登录")
+            .to_http_response(),
+    )
+}
+
+/// 退出指定会话
+#[route_permission(
+    path = "/api/v1/users/me/sessions/{id}",
+    method = "delete",
+    permission = "user:profile"
+)]
+pub async fn revoke_session_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    path: web::Path<String>,
+) -> HttpResult {
+    SessionService::revoke(&db_pool, &user, &path).await?;
+    Ok(ApiResponse::success((), "该设备已退出登录").to_http_response())
+}
+
+/// 我的登录记录
+#[route_permission(
+    path = "/api/v1/users/me/login-history",
+    method = "get",
+    permission = "user:profile"
+)]
+pub async fn login_history_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    query: web::Query<PaginationQuery>,
+) -> HttpResult {
+    validate_body(&*query)?;
+    let history =
+        LoginHistoryService::list(&db_pool, user.user_id, query.page, query.limit).await?;
+    Ok(ApiResponse::success(history, "获取登录记录成功").to_http_response())
+}
+
+/// 会话已失效时同时清除登录 Cookie
+fn signed_out(message: &str) -> HttpResponse {
+    let mut response = HttpResponse::Ok();
+    for cookie in clear_auth_cookies() {
+        response.cookie(cookie);
+    }
+    response.json(json!({
+        "code": 200,
+        "message": message,
+        "data": null
+    }))
+}
@@ -0,0 +1,53 @@
+use crate::dto::api_token::CreateApiTokenRequest;
+use crate::handlers::validate_body;
+use crate::middleware::CurrentUser;
+use crate::services::api_token::ApiTokenService;
+use crate::{ApiResponse, HttpResult};
+use actix_web::web;
+use route_macros::route_permission;
+use sea_orm::DatabaseConnection;
+
+/// 获取自己的个人访问令牌
+#[route_permission(
+    path = "/api/v1/users/me/tokens",
+    method = "get",
+    permission = "user:token"
+)]
+pub async fn list_api_tokens_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+) -> HttpResult {
+    let tokens = ApiTokenService::list(&db_pool, &user).await?;
+    Ok(ApiResponse::success(tokens, "获取令牌列表成功").to_http_response())
+}
+
+/// 创建个人访问令牌，明文令牌只返回这一次
+#[route_permission(
+    path = "/api/v1/users/me/tokens",
+    method = "post",
+    permission = "user:token"
+)]
+pub async fn create_api_token_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    body: web::Json<CreateApiTokenRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let token = ApiTokenService::create(&db_pool, &user, body.into_inner()).await?;
+    Ok(ApiResponse::success(token, "令牌创建成功，请立即保存").to_http_response())
+}
+
+/// 撤销个人访问令牌
+#[route_permission(
+    path = "/api/v1/users/me/tokens/{id}",
+    method = "delete",
+    permission = "user:token"
+)]
+pub async fn revoke_api_token_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+) -> HttpResult {
+    ApiTokenService::revoke(&db_pool, &user, path.into_inner()).await?;
+    Ok(ApiResponse::success((), "令牌已撤销").to_http_response())
+}
@@ -1,9 +1,19 @@
+use crate::EmailVerificationManager;
 use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::dto::account::{ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest};
+use crate::dto::two_factor::TwoFactorLoginRequest;
 use crate::dto::user::{LoginRequest, ValidationErrorJson};
+use crate::handlers::validate_body;
+use crate::services::{
+    AccountService, EmailService, LoginContext, LoginHistoryService, OAuthService,
+    SmsVerificationManager, TwoFactorChallengeManager,
+};
+use crate::utils::jwt_keys::jwt_keys;
+use crate::utils::rate_limit;
 use crate::{ApiResponse, HttpResult, SseNotifier};
 use crate::{AuthService, RegisterResponse};
-use crate::{EmailVerificationManager, RouteInfo};
-use actix_web::web;
+use actix_web::{HttpRequest, HttpResponse, web};
 use route_macros::route_permission;
 use sea_orm::DatabaseConnection;
 
@@ -20,13 +30,14 @@ use sea_orm::DatabaseConnection;
         (status = 422,description = "校验失败", body = ApiResponse<ValidationErrorJson> )
     ),
 )]
-#[route_permission(path = "/api/register", method = "post", permission = "auth:register")]
+#[route_permission(path = "/api/v1/auth/register", method = "post", public = true)]
 pub async fn register(
     db_pool: web::Data<DatabaseConnection>,
     user_data: web::Json<RegisterResponse>,
     notifier: web::Data<SseNotifier>,
+    email_service: web::Data<EmailService>,
 ) -> HttpResult {
-    match AuthService::register(db_pool, user_data, notifier).await {
+    match AuthService::register(db_pool, user_data, notifier, email_service).await {
         Ok(user) => Ok(ApiResponse::success(user, "添加用户成功").to_http_response()),
         Err(e) => Ok(ApiResponse::from(e).to_http_response()),
     }
@@ -41,25 +52,215 @@ pub async fn register(
     description = "登录接口",
     request_body( content = LoginRequest),
     responses(
-        (status = 200, description = "登录成功", body = RegisterResponse),
+        (status = 200, description = "登录成功；已启用两步验证时返回挑战令牌", body = RegisterResponse),
         (status = 422,description = "校验失败", body = ApiResponse<ValidationErrorJson> )
         ),
 
 )]
-#[route_permission(path = "/api/login", method = "post", permission = "auth:login")]
+#[route_permission(path = "/api/v1/auth/login", method = "post", public = true)]
 pub async fn login(
+    req: HttpRequest,
     db_pool: web::Data<DatabaseConnection>,
     login: web::Json<LoginRequest>,
     email_manager: web::Data<EmailVerificationManager>, // 添加这行
+    sms_manager: web::Data<SmsVerificationManager>,
+    oauth_service: web::Data<OAuthService>,
+    two_factor_challenges: web::Data<TwoFactorChallengeManager>,
 ) -> HttpResult {
     if let Err(e) = login.validate() {
         let msg = ValidationErrorJson::from_validation_errors(&e);
         return Ok(ApiResponse::from(AppError::ValidationError(msg)).to_http_response());
     }
-    match login.0 {
-        LoginRequest::Password(p) => AuthService::login_by_pwd(db_pool, p).await,
-        LoginRequest::Email(e) => AuthService::login_by_email(db_pool, e, email_manager).await,
-        LoginRequest::Phone(p) => AuthService::login_by_phone(db_pool, p),
-        LoginRequest::OAuth(o) => AuthService::login_by_oauth(db_pool, o),
+    let ctx = LoginContext::new(&req, login.method());
+    let account = login.account().to_string();
+    let db = db_pool.clone();
+    let result = match login.0 {
+        LoginRequest::Password(p) => {
+            AuthService::login_by_pwd(db_pool, p, two_factor_challenges, &ctx).await
+        }
+        LoginRequest::Email(e) => {
+            AuthService::login_by_email(db_pool, e, email_manager, two_factor_challenges, &ctx)
+                .await
+        }
+        LoginRequest::Phone(p) => {
+            AuthService::login_by_phone(db_pool, p, sms_manager, two_factor_challenges, &ctx).await
+        }
+        LoginRequest::OAuth(o) => {
+            AuthService::login_by_oauth(db_pool, o, oauth_service, two_factor_challenges, &ctx)
+                .await
+        }
+    };
+    if let Err(e) = &result {
+        LoginHistoryService::record_failure(&db, &ctx, Some(&account), e).await;
     }
+    result
+}
+
+/// 两步验证登录
+///
+/// 登录返回 `two_factor_required` 时，凭挑战令牌与验证码换取登录会话
+#[utoipa::path(
+    post,
+    summary = "两步验证登录",
+    path = "/api/v1/auth/2fa/verify",
+    tag="鉴权模块",
+    description = "提交挑战令牌与认证器验证码（或恢复码）完成登录",
+    request_body( content = TwoFactorLoginRequest),
+    responses(
+        (status = 200, description = "登录成功", body = RegisterResponse),
+        (status = 401, description = "挑战令牌无效或验证码错误", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(path = "/api/v1/auth/2fa/verify", method = "post", public = true)]
+pub async fn verify_two_factor(
+    req: HttpRequest,
+    db_pool: web::Data<DatabaseConnection>,
+    body: web::Json<TwoFactorLoginRequest>,
+    two_factor_challenges: web::Data<TwoFactorChallengeManager>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let ctx = LoginContext::new(&req, "two_factor");
+    let result =
+        AuthService::login_by_two_factor(&db_pool, body.into_inner(), &two_factor_challenges, &ctx)
+            .await;
+    if let Err(e) = &result {
+        LoginHistoryService::record_failure(&db_pool, &ctx, None, e).await;
+    }
+    result
+}
+
+/// 验证邮箱
+///
+/// 前端从邮件链接中取出令牌后提交，验证成功后发送欢迎邮件
+#[utoipa::path(
+    post,
+    summary = "验证邮箱",
+    path = "/api/v1/auth/verify-email",
+    tag="鉴权模块",
+    description = "提交邮件链接中的令牌完成邮箱验证，令牌只能使用一次",
+    request_body( content = VerifyEmailRequest),
+    responses(
+        (status = 200, description = "验证成功"),
+        (status = 400, description = "链接无效或已过期", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(path = "/api/v1/auth/verify-email", method = "post", public = true)]
+pub async fn verify_email(
+    db_pool: web::Data<DatabaseConnection>,
+    email_service: web::Data<EmailService>,
+    body: web::Json<VerifyEmailRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    AccountService::verify_email(&db_pool, &email_service, &body.token).await?;
+    Ok(ApiResponse::success((), "邮箱验证成功").to_http_response())
+}
+
+/// 找回密码
+///
+/// 向已验证的邮箱发送密码重置链接；邮箱不存在时同样返回成功
+#[utoipa::path(
+    post,
+    summary = "找回密码",
+    path = "/api/v1/auth/forgot-password",
+    tag="鉴权模块",
+    description = "发送密码重置链接，之前未使用的重置链接随之作废",
+    request_body( content = ForgotPasswordRequest),
+    responses(
+        (status = 200, description = "请求已受理"),
+        (status = 429, description = "请求过于频繁", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(path = "/api/v1/auth/forgot-password", method = "post", public = true)]
+pub async fn forgot_password(
+    db_pool: web::Data<DatabaseConnection>,
+    email_service: web::Data<EmailService>,
+    body: web::Json<ForgotPasswordRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    // 与发送验证码共用同一邮箱的频率限制
+    rate_limit::check(
+        CONFIG.rate_limit.verification_code_target,
+        &format!("email:{}", body.email.to_lowercase()),
+    )?;
+    AccountService::forgot_password(&db_pool, &email_service, &body.email).await?;
+    Ok(ApiResponse::success((), "如果该邮箱已注册并验证，重置链接将发送到邮箱").to_http_response())
+}
+
+/// 重置密码
+///
+/// 凭邮件链接中的令牌设置新密码，成功后该用户的全部会话失效
+#[utoipa::path(
+    post,
+    summary = "重置密码",
+    path = "/api/v1/auth/reset-password",
+    tag="鉴权模块",
+    description = "提交重置令牌与新密码，令牌只能使用一次",
+    request_body( content = ResetPasswordRequest),
+    responses(
+        (status = 200, description = "重置成功"),
+        (status = 400, description = "链接无效或已过期", body = ApiResponse<ValidationErrorJson>),
+        (status = 422, description = "校验失败", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(path = "/api/v1/auth/reset-password", method = "post", public = true)]
+pub async fn reset_password(
+    db_pool: web::Data<DatabaseConnection>,
+    body: web::Json<ResetPasswordRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    AccountService::reset_password(&db_pool, &body.token, &body.new_password).await?;
+    Ok(ApiResponse::success((), "密码已重置，请重新登录").to_http_response())
+}
+
+/// 刷新令牌
+///
+/// 使用 `refresh_token` Cookie 换取新的访问令牌，刷新令牌同时轮换
+#[utoipa::path(
+    post,
+    summary = "刷新令牌",
+    path = "/api/v1/auth/refresh",
+    tag="鉴权模块",
+    description = "使用刷新令牌换取新的访问令牌",
+    responses(
+        (status = 200, description = "刷新成功"),
+        (status = 401, description = "刷新令牌无效或已过期", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(path = "/api/v1/auth/refresh", method = "post", public = true)]
+pub async fn refresh(db_pool: web::Data<DatabaseConnection>, req: HttpRequest) -> HttpResult {
+    AuthService::refresh(&db_pool, &req).await
+}
+
+/// 退出登录
+#[utoipa::path(
+    post,
+    summary = "退出登录",
+    path = "/api/v1/auth/logout",
+    tag="鉴权模块",
+    description = "清除登录 Cookie 并作废当前会话的令牌",
+    responses(
+        (status = 200, description = "已退出登录"),
+    ),
+)]
+#[route_permission(path = "/api/v1/auth/logout", method = "post", auth = "optional")]
+pub async fn logout(db_pool: web::Data<DatabaseConnection>, req: HttpRequest) -> HttpResult {
+    AuthService::logout(&db_pool, &req).await
+}
+
+/// 公钥集合（JWKS）
+#[utoipa::path(
+    get,
+    summary = "JWT 公钥集合",
+    path = "/.well-known/jwks.json",
+    tag="鉴权模块",
+    description = "返回 EdDSA / RS256 验签公钥，供其他服务校验访问令牌；HS256 时为空集合",
+    responses(
+        (status = 200, description = "JWKS 文档"),
+    ),
+)]
+#[route_permission(path = "/.well-known/jwks.json", method = "get", public = true)]
+pub async fn jwks() -> HttpResult {
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(jwt_keys()?.jwks()))
 }
@@ -1,8 +1,11 @@
 use crate::ApiResponse;
 use crate::config::AppError;
+use crate::config::manager::CONFIG;
 use crate::dto::user::ValidationErrorJson;
 use crate::services::{EmailService, EmailVerificationManager};
+use crate::utils::rate_limit;
 use actix_web::{HttpResponse, web};
+use route_macros::route_permission;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use validator::Validate;
@@ -34,9 +37,15 @@ pub struct SendVerificationCodeResponse {
     responses(
         (status = 200, description = "验证码发送成功", body = SendVerificationCodeResponse),
         (status = 400, description = "请求参数错误", body = ApiResponse<ValidationErrorJson>),
+        (status = 429, description = "发送过于频繁，见 Retry-After 响应头"),
         (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
     )
 )]
+#[route_permission(
+    path = "/api/v1/email/send-verification-code",
+    method = "get",
+    public = true
+)]
 pub async fn send_verification_code(
     request: web::Json<SendVerificationCodeRequest>,
     email_service: web::Data<EmailService>,
@@ -49,6 +58,12 @@ pub async fn send_verification_code(
         ));
     }
 
+    // 同一邮箱的发送频率限制，防止被用来轰炸邮箱
+    rate_limit::check(
+        CONFIG.rate_limit.verification_code_target,
+        &format!("email:{}", request.email.to_lowercase()),
+    )?;
+
     // 生成并发送验证码
     match email_verification_manager
         .generate_and_send_code(&email_service, &request.email)
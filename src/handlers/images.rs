@@ -1,10 +1,16 @@
 use actix_multipart::Multipart;
 use actix_web::web;
+use route_macros::route_permission;
 use sea_orm::DatabaseConnection;
 
 use crate::{HttpResult, images::ImageService};
 
 /// 处理图片上传
+#[route_permission(
+    path = "/api/v1/images/upload",
+    method = "post",
+    permission = "images:upload"
+)]
 pub async fn upload_image_handler(
     db_pool: web::Data<DatabaseConnection>,
     payload: Multipart,
@@ -14,6 +20,7 @@ pub async fn upload_image_handler(
 }
 
 /// 获取图片列表
+#[route_permission(path = "/api/v1/images", method = "get", public = true)]
 pub async fn get_images_handler(
     db_pool: web::Data<DatabaseConnection>,
 
@@ -24,6 +31,7 @@ pub async fn get_images_handler(
 }
 
 /// 根据ID获取图片
+#[route_permission(path = "/api/v1/images/{id}", method = "get", public = true)]
 pub async fn get_image_by_id_handler(
     db_pool: web::Data<DatabaseConnection>,
 
@@ -35,6 +43,11 @@ pub async fn get_image_by_id_handler(
 }
 
 /// 删除图片
+#[route_permission(
+    path = "/api/v1/images/{id}",
+    method = "delete",
+    permission = "images:delete"
+)]
 pub async fn delete_image_handler(
     db_pool: web::Data<DatabaseConnection>,
     path: web::Path<i32>,
@@ -5,7 +5,7 @@ use crate::dto::user::ValidationErrorJson;
 use crate::dto::{PaginatedResp, Pagination};
 
 use crate::models::external_links;
-use crate::{ApiResponse, EmptyResponse, HttpResult, RouteInfo, utils::db_err_map};
+use crate::{ApiResponse, EmptyResponse, HttpResult, utils::db_err_map};
 use actix_web::{HttpResponse, web};
 use route_macros::crud_entity;
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, ModelTrait, PaginatorTrait};
@@ -15,6 +15,7 @@ crud_entity!({
     permission_prefix: "links",
     id_type:"id",
     operations: ["create","list","delete","read"],
+    public_operations: ["list","read"],
     create_request_type: CreateLinkRequest,
     openapi_summary:"链接管理",
     openapi_read: {
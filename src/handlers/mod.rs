@@ -6,11 +6,31 @@ pub mod tags;
 pub mod users;
 pub use users::users_routes::*;
 pub mod email;
+pub mod post_revision;
 pub mod posts;
 pub use posts::get_posts_all_handler;
 pub mod room_messages;
 pub mod rooms;
 pub mod upload;
 pub use email::send_verification_code;
+pub mod account;
+pub mod api_tokens;
 pub mod images;
 pub mod links;
+pub mod oauth;
+pub mod rbac;
+pub mod sms;
+pub mod two_factor;
+
+use crate::config::AppError;
+use crate::dto::user::ValidationErrorJson;
+use validator::Validate;
+
+/// 校验请求体，失败时转换为统一的校验错误
+pub(crate) fn validate_body<T: Validate>(body: &T) -> Result<(), AppError> {
+    body.validate().map_err(|validation_errors| {
+        AppError::ValidationError(ValidationErrorJson::from_validation_errors(
+            &validation_errors,
+        ))
+    })
+}
@@ -0,0 +1,186 @@
+use crate::config::AppError;
+use crate::dto::oauth::{OAuthAuthorizeQuery, OAuthAuthorizeResponse, OAuthCallbackQuery};
+use crate::middleware::{CurrentUser, OptionalUser};
+use crate::services::oauth::STATE_VALIDITY_PERIOD;
+use crate::services::{LoginContext, LoginHistoryService, OAuthService, TwoFactorChallengeManager};
+use crate::utils::auth_cookie::{
+    clear_oauth_state_cookie, oauth_state_cookie, oauth_state_matches,
+};
+use crate::{ApiResponse, AuthService, HttpResult};
+use actix_web::{HttpRequest, ResponseError, web};
+use route_macros::route_permission;
+use sea_orm::DatabaseConnection;
+
+/// 发起第三方授权
+#[utoipa::path(
+    get,
+    summary = "发起第三方授权",
+    path = "/api/v1/auth/oauth/{provider}/authorize",
+    tag="鉴权模块",
+    description = "返回服务商授权地址并把 state 写入 Cookie，回调时须由同一浏览器携带；`link=true` 时需要登录，授权完成后绑定到当前用户",
+    params(
+        ("provider" = String, Path, description = "服务商名称，如 github"),
+        ("link" = Option<bool>, Query, description = "是否绑定到当前用户")
+    ),
+    responses(
+        (status = 200, description = "授权地址", body = OAuthAuthorizeResponse),
+        (status = 404, description = "不支持的服务商"),
+    ),
+)]
+#[route_permission(
+    path = "/api/v1/auth/oauth/{provider}/authorize",
+    method = "get",
+    auth = "optional"
+)]
+pub async fn oauth_authorize(
+    oauth: web::Data<OAuthService>,
+    path: web::Path<String>,
+    query: web::Query<OAuthAuthorizeQuery>,
+    user: OptionalUser,
+) -> HttpResult {
+    let link_user_id = match (query.link, user.0) {
+        (false, _) => None,
+        (true, Some(user)) => Some(user.user_id),
+        (true, None) => {
+            return Err(AppError::Unauthorized(
+                "请先登录再绑定第三方账号".to_string(),
+            ));
+        }
+    };
+    let (authorize_url, state) = oauth.authorize_url(&path, link_user_id).await?;
+    let mut response = ApiResponse::success(
+        OAuthAuthorizeResponse {
+            authorize_url,
+            state: state.clone(),
+        },
+        "获取授权地址成功",
+    )
+    .to_http_response();
+    response
+        .add_cookie(&oauth_state_cookie(state, STATE_VALIDITY_PERIOD as i64))
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    Ok(response)
+}
+
+/// 第三方授权回调
+#[utoipa::path(
+    get,
+    summary = "第三方授权回调",
+    path = "/api/v1/auth/oauth/{provider}/callback",
+    tag="鉴权模块",
+    description = "用授权码完成登录（已绑定时直接登录，否则创建新用户）或绑定",
+    params(
+        ("provider" = String, Path, description = "服务商名称，如 github"),
+        ("code" = String, Query, description = "授权码"),
+        ("state" = String, Query, description = "发起授权时返回的 state")
+    ),
+    responses(
+        (status = 200, description = "登录或绑定成功"),
+        (status = 400, description = "授权请求无效、已过期或不是由当前浏览器发起"),
+        (status = 409, description = "第三方账号已绑定其他用户"),
+    ),
+)]
+#[route_permission(
+    path = "/api/v1/auth/oauth/{provider}/callback",
+    method = "get",
+    public = true
+)]
+pub async fn oauth_callback(
+    req: HttpRequest,
+    db_pool: web::Data<DatabaseConnection>,
+    oauth: web::Data<OAuthService>,
+    path: web::Path<String>,
+    query: web::Query<OAuthCallbackQuery>,
+    two_factor_challenges: web::Data<TwoFactorChallengeManager>,
+) -> HttpResult {
+    // state 必须与发起授权时写入的 Cookie 一致，防止把他人的授权回调注入当前浏览器
+    if !oauth_state_matches(&req, &query.state) {
+        return Err(AppError::BadRequest(
+            "授权请求不是由当前浏览器发起".to_string(),
+        ));
+    }
+    let provider = path.into_inner();
+    let result = handle_callback(
+        &req,
+        &db_pool,
+        &oauth,
+        &provider,
+        &query,
+        &two_factor_challenges,
+    )
+    .await;
+    // 无论成功与否，state 都已失效，清除 Cookie
+    let mut response = result.unwrap_or_else(|e| e.error_response());
+    response
+        .add_cookie(&clear_oauth_state_cookie())
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    Ok(response)
+}
+
+async fn handle_callback(
+    req: &HttpRequest,
+    db_pool: &DatabaseConnection,
+    oauth: &OAuthService,
+    provider: &str,
+    query: &OAuthCallbackQuery,
+    two_factor_challenges: &TwoFactorChallengeManager,
+) -> HttpResult {
+    let callback = oauth.callback(provider, &query.code, &query.state).await?;
+    match callback.link_user_id {
+        Some(user_id) => {
+            let binding =
+                OAuthService::link(db_pool, user_id, provider, &callback.identity).await?;
+            Ok(ApiResponse::success(binding, "绑定成功").to_http_response())
+        }
+        None => {
+            let ctx = LoginContext::new(req, "oauth");
+            let result = match OAuthService::login_user(db_pool, provider, &callback.identity).await
+            {
+                Ok(user) => {
+                    AuthService::login_or_challenge(
+                        db_pool,
+                        user,
+                        "第三方登录成功",
+                        two_factor_challenges,
+                        &ctx,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &result {
+                LoginHistoryService::record_failure(db_pool, &ctx, Some(provider), e).await;
+            }
+            result
+        }
+    }
+}
+
+/// 获取自己的第三方账号绑定
+#[route_permission(
+    path = "/api/v1/users/me/oauth",
+    method = "get",
+    permission = "user:profile"
+)]
+pub async fn list_oauth_bindings_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+) -> HttpResult {
+    let bindings = OAuthService::list_bindings(&db_pool, user.user_id).await?;
+    Ok(ApiResponse::success(bindings, "获取绑定列表成功").to_http_response())
+}
+
+/// 解除第三方账号绑定
+#[route_permission(
+    path = "/api/v1/users/me/oauth/{provider}",
+    method = "delete",
+    permission = "user:profile"
+)]
+pub async fn unlink_oauth_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    path: web::Path<String>,
+) -> HttpResult {
+    OAuthService::unlink(&db_pool, user.user_id, &path).await?;
+    Ok(ApiResponse::success((), "已解除绑定").to_http_response())
+}
@@ -0,0 +1,141 @@
+use crate::dto::PaginatedResp;
+use crate::dto::PaginationQuery;
+use crate::dto::post_revision::{
+    PostRevisionDiff, PostRevisionResponse, PostRevisionSummary, RevisionDiffQuery,
+};
+use crate::dto::posts::PostResponse;
+use crate::dto::user::ValidationErrorJson;
+use crate::handlers::validate_body;
+use crate::middleware::CurrentUser;
+use crate::services::post_revision::PostRevisionService;
+use crate::{ApiResponse, HttpResult};
+use actix_web::web;
+use route_macros::route_permission;
+use sea_orm::DatabaseConnection;
+
+/// 文章修订记录列表
+#[utoipa::path(
+    summary = "获取文章修订记录",
+    tag="文章",
+    description = "分页列出文章每次更新前保存的版本，仅作者可查看",
+    get,
+    path = "/api/v1/posts/{uuid}/revisions",
+    params(
+        ("uuid" = String, Path, description = "文章UUID或slug"),
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "成功获取修订记录", body = PaginatedResp<PostRevisionSummary>),
+        (status = 404, description = "文章不存在", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(
+    path = "/api/v1/posts/{uuid}/revisions",
+    method = "get",
+    permission = "post:update:own"
+)]
+pub async fn list_revisions_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    path: web::Path<String>,
+    query: web::Query<PaginationQuery>,
+) -> HttpResult {
+    validate_body(&*query)?;
+    let revisions =
+        PostRevisionService::list(&db_pool, user.user_id, &path, query.page, query.limit).await?;
+    Ok(ApiResponse::success(revisions, "成功").to_http_response())
+}
+
+/// 比较两个修订版本
+#[utoipa::path(
+    summary = "比较文章修订版本",
+    tag="文章",
+    description = "逐行比较两个修订版本的标题与Markdown内容，不传 to 时与文章当前内容比较",
+    get,
+    path = "/api/v1/posts/{uuid}/revisions/diff",
+    params(
+        ("uuid" = String, Path, description = "文章UUID或slug"),
+        RevisionDiffQuery
+    ),
+    responses(
+        (status = 200, description = "成功获取差异", body = ApiResponse<PostRevisionDiff>),
+        (status = 404, description = "文章或修订记录不存在", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(
+    path = "/api/v1/posts/{uuid}/revisions/diff",
+    method = "get",
+    permission = "post:update:own"
+)]
+pub async fn diff_revisions_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    path: web::Path<String>,
+    query: web::Query<RevisionDiffQuery>,
+) -> HttpResult {
+    let diff =
+        PostRevisionService::diff(&db_pool, user.user_id, &path, query.from, query.to).await?;
+    Ok(ApiResponse::success(diff, "成功").to_http_response())
+}
+
+/// 查看修订版本
+#[utoipa::path(
+    summary = "获取文章修订版本",
+    tag="文章",
+    description = "查看某个修订版本的完整内容",
+    get,
+    path = "/api/v1/posts/{uuid}/revisions/{id}",
+    params(
+        ("uuid" = String, Path, description = "文章UUID或slug"),
+        ("id" = i32, Path, description = "修订ID")
+    ),
+    responses(
+        (status = 200, description = "成功获取修订版本", body = ApiResponse<PostRevisionResponse>),
+        (status = 404, description = "文章或修订记录不存在", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(
+    path = "/api/v1/posts/{uuid}/revisions/{id}",
+    method = "get",
+    permission = "post:update:own"
+)]
+pub async fn get_revision_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    path: web::Path<(String, i32)>,
+) -> HttpResult {
+    let (uuid, revision_id) = path.into_inner();
+    let revision = PostRevisionService::get(&db_pool, user.user_id, &uuid, revision_id).await?;
+    Ok(ApiResponse::success(revision, "成功").to_http_response())
+}
+
+/// 恢复修订版本
+#[utoipa::path(
+    summary = "恢复文章修订版本",
+    tag="文章",
+    description = "将文章内容、分类与标签恢复为某个修订版本，恢复前的内容同样会保存为修订记录",
+    post,
+    path = "/api/v1/posts/{uuid}/revisions/{id}/restore",
+    params(
+        ("uuid" = String, Path, description = "文章UUID或slug"),
+        ("id" = i32, Path, description = "修订ID")
+    ),
+    responses(
+        (status = 200, description = "文章已恢复", body = ApiResponse<PostResponse>),
+        (status = 404, description = "文章或修订记录不存在", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(
+    path = "/api/v1/posts/{uuid}/revisions/{id}/restore",
+    method = "post",
+    permission = "post:update:own"
+)]
+pub async fn restore_revision_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    path: web::Path<(String, i32)>,
+) -> HttpResult {
+    let (uuid, revision_id) = path.into_inner();
+    let post = PostRevisionService::restore(&db_pool, user.user_id, &uuid, revision_id).await?;
+    Ok(ApiResponse::success(post, "文章已恢复到该版本").to_http_response())
+}
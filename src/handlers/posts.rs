@@ -10,10 +10,17 @@ use crate::dto::posts::UpdatePostRequest;
 use crate::dto::posts::{
     CategoryResponse, CreatePostRequest, PostListResponse, PostResponse, TagResponse,
 };
+use crate::dto::posts::{PostSearchQuery, PostSearchResult};
 use crate::dto::user::ValidationErrorJson;
+use crate::handlers::validate_body;
+use crate::middleware::{CurrentUser, OptionalUser};
+use crate::models::posts::PostStatus;
 use crate::models::{categories, post_tags, posts, tags};
+use crate::services::post_search::PostSearchService;
+use crate::services::posts::PostService;
 use crate::{ApiResponse, HttpResult};
-use actix_web::web;
+use actix_web::{HttpResponse, http::header, web};
+use route_macros::route_permission;
 use sea_orm::RelationTrait;
 use sea_orm::{
     ColumnTrait,
@@ -34,7 +41,7 @@ use validator::Validate;
 
 /// 获取所有文章列表
 ///
-/// 返回分页的文章列表，支持按分类和标签过滤
+/// 返回分页的文章列表，支持按分类和标签过滤；匿名访问只返回已发布的文章，登录用户还能看到自己的草稿
 #[utoipa::path(
     summary = "获取文章列表",
     tag="文章",
@@ -52,8 +59,10 @@ use validator::Validate;
         (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
     ),
 )]
+#[route_permission(path = "/api/v1/posts", method = "get", auth = "optional")]
 pub async fn get_posts_all_handler(
     db_pool: web::Data<DatabaseConnection>,
+    user: OptionalUser,
     query: web::Query<PaginationQuery>,
 ) -> HttpResult {
     let PaginationQuery {
@@ -63,7 +72,10 @@ pub async fn get_posts_all_handler(
         tag,
     } = query.into_inner();
     // 1. 构建基础查询（文章 + 分类）
-    let mut query_builder = posts::Entity::find().find_also_related(categories::Entity);
+    let viewer_id = user.0.map(|claims| claims.user_id);
+    let mut query_builder = posts::Entity::find()
+        .filter(posts::Entity::visible_to(viewer_id))
+        .find_also_related(categories::Entity);
 
     // 新增：如果传入了分类ID，则添加过滤条件
     if let Some(cat_id) = category {
@@ -106,72 +118,11 @@ pub async fn get_posts_all_handler(
         }
     };
 
-    // 2. 收集文章ID用于批量查询标签
-    let post_ids: Vec<i32> = posts_with_categories
-        .iter()
-        .map(|(post, _)| post.id)
-        .collect();
-
-    // 3. 批量查询所有文章的标签（避免N+1查询）
-    let post_tags_map = if post_ids.is_empty() {
-        std::collections::HashMap::new()
-    } else {
-        // 通过中间表查询标签
-        let tag_relations = post_tags::Entity::find()
-            .filter(post_tags::Column::PostId.is_in(post_ids.clone()))
-            .find_also_related(tags::Entity)
-            .all(db_pool.as_ref())
-            .await
-            .unwrap_or_default();
-
-        let mut map = std::collections::HashMap::new();
-        for (post_tag, tag_option) in tag_relations {
-            if let Some(tag) = tag_option {
-                map.entry(post_tag.post_id)
-                    .or_insert_with(Vec::new)
-                    .push(TagResponse {
-                        id: tag.id,
-                        name: tag.name,
-                    });
-            }
-        }
-        map
+    // 2. 批量查询标签与作者，构建响应数据
+    let data = match PostService::list_responses(db_pool.as_ref(), posts_with_categories).await {
+        Ok(data) => data,
+        Err(e) => return Ok(ApiResponse::from(e).to_http_response()),
     };
-    // 4. 构建最终的响应数据
-    let data: Vec<PostResponse> = posts_with_categories
-        .into_iter()
-        .map(|(post, category_option)| {
-            // 处理分类信息
-            let category = category_option.map(|category| CategoryResponse {
-                id: category.id,
-                name: category.name,
-            });
-
-            // 获取标签信息
-            let tags = post_tags_map.get(&post.id).cloned().unwrap_or_default();
-
-            // 构建作者信息（这里需要根据您的用户表结构调整）
-            let author = "朝阳".to_string(); // 您需要从用户表查询真实作者名
-            // 构建响应对象
-            PostResponse {
-                id: post.id,
-                uuid: post.uuid,
-                title: post.title,
-                cover: post.cover_image.unwrap_or_default(),
-                author,
-                publish_time: post.published_at,
-                update_time: post.updated_at,
-                views: post.view_count,
-                is_top: post.featured,
-                is_publish: post.status == 1,
-                is_hide: post.status == 2,
-                description: post.summary.unwrap_or_default(),
-                size: post.size, // 可以根据内容长度计算
-                category,
-                tags,
-            }
-        })
-        .collect();
 
     let resp: PaginatedResp<PostResponse> = PaginatedResp {
         data,
@@ -180,6 +131,34 @@ pub async fn get_posts_all_handler(
     Ok(ApiResponse::success(resp, "成功").to_http_response())
 }
 
+/// 全文搜索文章
+///
+/// 按相关度返回匹配的文章及标题、摘要、正文的高亮片段，可见范围与文章列表相同
+#[utoipa::path(
+    summary = "搜索文章",
+    tag="文章",
+    description = "按关键词全文搜索文章，多个关键词以空格分隔且需全部命中，支持按分类、标签、状态过滤",
+    get,
+    path = "/api/v1/posts/search",
+    params(PostSearchQuery),
+    responses(
+        (status = 200, description = "成功获取搜索结果", body = PaginatedResp<PostSearchResult>),
+        (status = 400, description = "请求参数验证失败", body = ApiResponse<ValidationErrorJson>),
+        (status = 500, description = "搜索服务错误", body = ApiResponse<ValidationErrorJson>)
+    ),
+)]
+#[route_permission(path = "/api/v1/posts/search", method = "get", auth = "optional")]
+pub async fn search_posts_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: OptionalUser,
+    query: web::Query<PostSearchQuery>,
+) -> HttpResult {
+    validate_body(&*query)?;
+    let viewer_id = user.0.map(|claims| claims.user_id);
+    let resp = PostSearchService::search(&db_pool, viewer_id, query.into_inner()).await?;
+    Ok(ApiResponse::success(resp, "成功").to_http_response())
+}
+
 /// 为时间轴处理函数添加OpenAPI文档
 #[utoipa::path(
     summary = "获取文章时间轴",
@@ -192,13 +171,20 @@ pub async fn get_posts_all_handler(
         (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
     ),
 )]
-pub async fn get_timeline_handler(db_pool: web::Data<DatabaseConnection>) -> HttpResult {
+#[route_permission(path = "/api/v1/posts/uploadTime", method = "get", auth = "optional")]
+pub async fn get_timeline_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: OptionalUser,
+) -> HttpResult {
     #[derive(Debug, FromQueryResult)]
     struct TimelineCount {
         date: chrono::NaiveDate,
         count: i64,
     }
     let timeline_data = posts::Entity::find()
+        .filter(posts::Entity::visible_to(
+            user.0.map(|claims| claims.user_id),
+        ))
         .select_only()
         .column_as(
             Expr::col(posts::Column::CreatedAt).cast_as(sea_orm::sea_query::Alias::new("date")),
@@ -209,8 +195,7 @@ pub async fn get_timeline_handler(db_pool: web::Data<DatabaseConnection>) -> Htt
         .order_by_desc(Expr::col(posts::Column::CreatedAt).cast_as(Alias::new("date")))
         .into_model::<TimelineCount>()
         .all(db_pool.as_ref())
-        .await
-        .unwrap_or_default();
+        .await?;
 
     let resp = timeline_data
         .into_iter()
@@ -224,26 +209,31 @@ pub async fn get_timeline_handler(db_pool: web::Data<DatabaseConnection>) -> Htt
 #[utoipa::path(
     summary = "获取文章详情",
     tag="文章",
-    description = "根据UUID获取文章详情",
+    description = "根据UUID或slug获取文章详情，使用文章曾用的slug访问时301重定向到当前slug",
     get,
     path = "/api/v1/posts/{uuid}",
     params(
-        ("uuid" = String, Path, description = "文章UUID")
+        ("uuid" = String, Path, description = "文章UUID或slug")
     ),
     responses(
         (status = 200, description = "成功获取文章详情", body = ApiResponse<PostListResponse>),
+        (status = 301, description = "slug已变更，Location为文章当前地址"),
         (status = 404, description = "文章不存在", body = ApiResponse<ValidationErrorJson>),
         (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
     ),
 )]
+#[route_permission(path = "/api/v1/posts/{uuid}", method = "get", auth = "optional")]
 pub async fn get_posts_handler(
     db_pool: web::Data<DatabaseConnection>,
+    user: OptionalUser,
     page: web::Path<String>,
 ) -> HttpResult {
-    let uuid = page.into_inner();
+    let key = page.into_inner();
+    let viewer_id = user.0.map(|claims| claims.user_id);
 
-    // 1. 查询文章和分类（通过 UUID）
-    let post_with_category = posts::Entity::find_by_uuid(&uuid)
+    // 1. 查询文章和分类（通过 UUID 或 slug），未发布的文章只有作者能看到
+    let post_with_category = posts::Entity::find_by_key(&key)
+        .filter(posts::Entity::visible_to(viewer_id))
         .find_also_related(categories::Entity)
         .one(db_pool.as_ref())
         .await
@@ -256,6 +246,14 @@ pub async fn get_posts_handler(
     let (post, category_option) = match post_with_category {
         Some(data) => data,
         None => {
+            // 旧链接重定向到文章当前的 slug
+            if let Some(slug) =
+                PostService::find_moved_slug(db_pool.as_ref(), &key, viewer_id).await?
+            {
+                return Ok(HttpResponse::MovedPermanently()
+                    .insert_header((header::LOCATION, format!("/api/v1/posts/{slug}")))
+                    .finish());
+            }
             return Err(AppError::NotFound(String::from("文章不存在")));
         }
     };
@@ -283,23 +281,30 @@ pub async fn get_posts_handler(
         name: category.name,
     });
 
-    let author = "朝阳".to_string(); // 这里需要从用户表查询真实作者名
+    let (author, author_avatar) =
+        PostService::find_author(db_pool.as_ref(), post.author_id).await?;
 
     let response = PostListResponse {
         id: post.id,
         uuid: post.uuid,
+        slug: post.slug,
         title: post.title,
         content: post.markdowncontent,
+        html: post.content,
         cover: post.cover_image.unwrap_or_default(),
         author,
+        author_avatar,
         publish_time: post.published_at,
         update_time: post.updated_at,
         views: post.view_count,
         is_top: post.featured,
-        is_publish: post.status == 1,
-        is_hide: post.status == 2,
+        status: post.status,
+        is_publish: post.status == PostStatus::Published,
+        is_hide: post.status == PostStatus::Offline,
         description: post.summary.unwrap_or_default(),
         size: post.size,
+        reading_minutes: post.reading_minutes,
+        toc: serde_json::from_value(post.toc).unwrap_or_default(),
         category,
         tags,
     };
@@ -311,11 +316,11 @@ pub async fn get_posts_handler(
 #[utoipa::path(
     summary = "获取上一篇和下一篇文章",
     tag="文章",
-    description = "根据当前文章的UUID获取上一篇和下一篇文章的信息",
+    description = "根据当前文章的UUID或slug获取上一篇和下一篇文章的信息",
     get,
     path = "/api/v1/posts/{uuid}/prevnext",
     params(
-        ("uuid" = String, Path, description = "当前文章UUID")
+        ("uuid" = String, Path, description = "当前文章UUID或slug")
     ),
     responses(
         (status = 200, description = "成功获取上一篇和下一篇文章", body = ApiResponse<PrevNextResult>),
@@ -323,21 +328,30 @@ pub async fn get_posts_handler(
         (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
     ),
 )]
+#[route_permission(
+    path = "/api/v1/posts/prevNext/{uuid}",
+    method = "get",
+    auth = "optional"
+)]
 pub async fn get_prev_next_handler(
     db_pool: web::Data<DatabaseConnection>,
+    user: OptionalUser,
     page: web::Path<String>,
 ) -> HttpResult {
     // 将转换函数提取出来，避免重复
     fn to_response(post: posts::Model) -> PrevNextResponse {
         PrevNextResponse {
             uuid: post.uuid,
+            slug: post.slug,
             title: post.title,
         }
     }
 
     let uuid = page.into_inner();
+    let visible = posts::Entity::visible_to(user.0.map(|claims| claims.user_id));
     // 查询当前文章，使用更简洁的错误处理
-    let post = posts::Entity::find_by_uuid(&uuid)
+    let post = posts::Entity::find_by_key(&uuid)
+        .filter(visible.clone())
         .one(db_pool.as_ref())
         .await?
         .ok_or_else(|| AppError::NotFound("文章不存在".to_string()))?;
@@ -346,11 +360,13 @@ pub async fn get_prev_next_handler(
     let (prev, next) = try_join!(
         // 查询上一篇（创建时间更早的）
         posts::Entity::find()
+            .filter(visible.clone())
             .filter(posts::Column::CreatedAt.lt(post.created_at))
             .order_by(posts::Column::CreatedAt, Order::Desc)
             .one(db_pool.as_ref()),
         // 查询下一篇（创建时间更晚的）
         posts::Entity::find()
+            .filter(visible)
             .filter(posts::Column::CreatedAt.gt(post.created_at))
             .order_by(posts::Column::CreatedAt, Order::Asc)
             .one(db_pool.as_ref())
@@ -384,13 +400,13 @@ pub async fn get_prev_next_handler(
         (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
     ),
 )]
+#[route_permission(path = "/api/v1/posts", method = "post", permission = "post:create")]
 pub async fn create_post_handler(
     db_pool: web::Data<DatabaseConnection>,
-    post_data: web::Json<CreatePostRequest>, // 从认证中间件获取用户ID
-                                             // user_id: web::ReqData<i32>,
+    user: CurrentUser,
+    post_data: web::Json<CreatePostRequest>,
 ) -> HttpResult {
     log::info!("create_post_handler: {post_data:?}");
-    let user_id = 1;
     // 验证输入
     if let Err(validation_errors) = post_data.validate() {
         let msg = ValidationErrorJson::from_validation_errors(&validation_errors);
@@ -398,13 +414,7 @@ pub async fn create_post_handler(
     }
 
     // 调用服务层创建文章
-    match crate::services::posts::PostService::create_post(
-        db_pool.as_ref(),
-        user_id,
-        post_data.into_inner(),
-    )
-    .await
-    {
+    match PostService::create_post(db_pool.as_ref(), user.user_id, post_data.into_inner()).await {
         Ok(post) => Ok(ApiResponse::success(post, "文章创建成功").to_http_response()),
         Err(e) => Ok(ApiResponse::from(e).to_http_response()),
     }
@@ -414,11 +424,11 @@ pub async fn create_post_handler(
 #[utoipa::path(
     summary = "更新文章",
     tag="文章",
-    description = "根据UUID更新现有文章",
+    description = "根据UUID或slug更新现有文章",
     put,
     path = "/api/v1/posts/{uuid}",
     params(
-        ("uuid" = String, Path, description = "文章UUID")
+        ("uuid" = String, Path, description = "文章UUID或slug")
     ),
     request_body = UpdatePostRequest,
     responses(
@@ -428,13 +438,17 @@ pub async fn create_post_handler(
         (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
     ),
 )]
+#[route_permission(
+    path = "/api/v1/posts/{uuid}",
+    method = "put",
+    permission = "post:update:own"
+)]
 pub async fn update_post_handler(
     db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
     path: web::Path<String>,
-    post_data: web::Json<UpdatePostRequest>, // 从认证中间件获取用户ID
-                                             // user_id: web::ReqData<i32>,
+    post_data: web::Json<UpdatePostRequest>,
 ) -> HttpResult {
-    let user_id = 1;
     // 验证输入
     if let Err(validation_errors) = post_data.validate() {
         let msg = ValidationErrorJson::from_validation_errors(&validation_errors);
@@ -444,9 +458,9 @@ pub async fn update_post_handler(
     let uuid = path.into_inner();
 
     // 调用服务层更新文章
-    match crate::services::posts::PostService::update_post(
+    match PostService::update_post(
         db_pool.as_ref(),
-        user_id,
+        user.user_id,
         &uuid,
         post_data.into_inner(),
     )
@@ -461,11 +475,11 @@ pub async fn update_post_handler(
 #[utoipa::path(
     summary = "删除文章",
     tag="文章",
-    description = "根据UUID删除文章",
+    description = "根据UUID或slug删除文章",
     delete,
     path = "/api/v1/posts/{uuid}",
     params(
-        ("uuid" = String, Path, description = "文章UUID")
+        ("uuid" = String, Path, description = "文章UUID或slug")
     ),
     responses(
         (status = 200, description = "文章删除成功", body = ApiResponse<EmptyResponse>),
@@ -473,16 +487,20 @@ pub async fn update_post_handler(
         (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
     ),
 )]
+#[route_permission(
+    path = "/api/v1/posts/{uuid}",
+    method = "delete",
+    permission = "post:delete:own"
+)]
 pub async fn delete_post_handler(
     db_pool: web::Data<DatabaseConnection>,
-    path: web::Path<String>, // 从认证中间件获取用户ID
-                             // user_id: web::ReqData<i32>,
+    user: CurrentUser,
+    path: web::Path<String>,
 ) -> HttpResult {
-    let user_id = 1;
     let uuid = path.into_inner();
 
     // 调用服务层删除文章
-    match crate::services::posts::PostService::delete_post(db_pool.as_ref(), user_id, &uuid).await {
+    match PostService::delete_post(db_pool.as_ref(), user.user_id, &uuid).await {
         Ok(()) => Ok(
             ApiResponse::<EmptyResponse>::success(EmptyResponse, "文章删除成功").to_http_response(),
         ),
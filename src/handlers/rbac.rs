@@ -0,0 +1,290 @@
+use crate::dto::rbac::{
+    AssignRoleRequest, AttachPermissionsRequest, CreatePermissionRequest, CreateRoleRequest,
+    GrantUserPermissionRequest, UpdatePermissionRequest, UpdateRoleRequest,
+};
+use crate::handlers::validate_body;
+use crate::services::AuthService;
+use crate::services::rbac::RbacService;
+use crate::utils::perm_cache::load_perm_cache;
+use crate::{ApiResponse, HttpResult};
+use actix_web::web;
+use route_macros::route_permission;
+use sea_orm::DatabaseConnection;
+
+// ------------------------------ 角色 ------------------------------
+
+/// 获取所有角色及其权限码
+#[route_permission(
+    path = "/api/v1/admin/roles",
+    method = "get",
+    permission = "system:rbac"
+)]
+pub async fn list_roles_handler(db_pool: web::Data<DatabaseConnection>) -> HttpResult {
+    let roles = RbacService::list_roles(&db_pool).await?;
+    Ok(ApiResponse::success(roles, "获取角色列表成功").to_http_response())
+}
+
+/// 创建角色
+#[route_permission(
+    path = "/api/v1/admin/roles",
+    method = "post",
+    permission = "system:rbac"
+)]
+pub async fn create_role_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    body: web::Json<CreateRoleRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let role = RbacService::create_role(&db_pool, body.into_inner()).await?;
+    Ok(ApiResponse::success(role, "角色创建成功").to_http_response())
+}
+
+/// 更新角色
+#[route_permission(
+    path = "/api/v1/admin/roles/{id}",
+    method = "put",
+    permission = "system:rbac"
+)]
+pub async fn update_role_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<i32>,
+    body: web::Json<UpdateRoleRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let role = RbacService::update_role(&db_pool, path.into_inner(), body.into_inner()).await?;
+    Ok(ApiResponse::success(role, "角色更新成功").to_http_response())
+}
+
+/// 删除角色
+#[route_permission(
+    path = "/api/v1/admin/roles/{id}",
+    method = "delete",
+    permission = "system:rbac"
+)]
+pub async fn delete_role_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<i32>,
+) -> HttpResult {
+    RbacService::delete_role(&db_pool, path.into_inner()).await?;
+    Ok(ApiResponse::success((), "角色删除成功").to_http_response())
+}
+
+/// 为角色挂载权限
+#[route_permission(
+    path = "/api/v1/admin/roles/{id}/permissions",
+    method = "post",
+    permission = "system:rbac"
+)]
+pub async fn attach_role_permissions_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<i32>,
+    body: web::Json<AttachPermissionsRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    RbacService::attach_permissions(
+        &db_pool,
+        path.into_inner(),
+        body.into_inner().permission_ids,
+    )
+    .await?;
+    Ok(ApiResponse::success((), "权限挂载成功").to_http_response())
+}
+
+/// 从角色上移除权限
+#[route_permission(
+    path = "/api/v1/admin/roles/{id}/permissions/{permission_id}",
+    method = "delete",
+    permission = "system:rbac"
+)]
+pub async fn detach_role_permission_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResult {
+    let (role_id, permission_id) = path.into_inner();
+    RbacService::detach_permission(&db_pool, role_id, permission_id).await?;
+    Ok(ApiResponse::success((), "权限移除成功").to_http_response())
+}
+
+// ------------------------------ 权限 ------------------------------
+
+/// 获取所有权限
+#[route_permission(
+    path = "/api/v1/admin/permissions",
+    method = "get",
+    permission = "system:rbac"
+)]
+pub async fn list_permissions_handler(db_pool: web::Data<DatabaseConnection>) -> HttpResult {
+    let perms = RbacService::list_permissions(&db_pool).await?;
+    Ok(ApiResponse::success(perms, "获取权限列表成功").to_http_response())
+}
+
+/// 列出路由中声明的权限码，并标记权限表中缺失的
+#[route_permission(
+    path = "/api/v1/admin/permissions/registry",
+    method = "get",
+    permission = "system:rbac"
+)]
+pub async fn registry_permissions_handler(db_pool: web::Data<DatabaseConnection>) -> HttpResult {
+    let codes = RbacService::registry_permissions(&db_pool).await?;
+    Ok(ApiResponse::success(codes, "获取路由权限码成功").to_http_response())
+}
+
+/// 创建权限
+#[route_permission(
+    path = "/api/v1/admin/permissions",
+    method = "post",
+    permission = "system:rbac"
+)]
+pub async fn create_permission_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    body: web::Json<CreatePermissionRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let perm = RbacService::create_permission(&db_pool, body.into_inner()).await?;
+    Ok(ApiResponse::success(perm, "权限创建成功").to_http_response())
+}
+
+/// 更新权限
+#[route_permission(
+    path = "/api/v1/admin/permissions/{id}",
+    method = "put",
+    permission = "system:rbac"
+)]
+pub async fn update_permission_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<i32>,
+    body: web::Json<UpdatePermissionRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let perm =
+        RbacService::update_permission(&db_pool, path.into_inner(), body.into_inner()).await?;
+    Ok(ApiResponse::success(perm, "权限更新成功").to_http_response())
+}
+
+/// 删除权限
+#[route_permission(
+    path = "/api/v1/admin/permissions/{id}",
+    method = "delete",
+    permission = "system:rbac"
+)]
+pub async fn delete_permission_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<i32>,
+) -> HttpResult {
+    RbacService::delete_permission(&db_pool, path.into_inner()).await?;
+    Ok(ApiResponse::success((), "权限删除成功").to_http_response())
+}
+
+/// 手动重新加载权限缓存（直接修改数据库后使用）
+#[route_permission(
+    path = "/api/v1/admin/permissions/reload",
+    method = "post",
+    permission = "system:rbac"
+)]
+pub async fn reload_permissions_handler(db_pool: web::Data<DatabaseConnection>) -> HttpResult {
+    load_perm_cache(&db_pool).await?;
+    Ok(ApiResponse::success((), "权限缓存已重新加载").to_http_response())
+}
+
+// ------------------------------ 用户角色与权限 ------------------------------
+
+/// 获取用户的角色
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}/roles",
+    method = "get",
+    permission = "system:rbac"
+)]
+pub async fn list_user_roles_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<String>,
+) -> HttpResult {
+    let roles = RbacService::list_user_roles(&db_pool, &path).await?;
+    Ok(ApiResponse::success(roles, "获取用户角色成功").to_http_response())
+}
+
+/// 为用户分配角色
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}/roles",
+    method = "post",
+    permission = "system:rbac"
+)]
+pub async fn assign_user_role_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<String>,
+    body: web::Json<AssignRoleRequest>,
+) -> HttpResult {
+    RbacService::assign_role(&db_pool, &path, body.into_inner()).await?;
+    Ok(ApiResponse::success((), "角色分配成功").to_http_response())
+}
+
+/// 移除用户的角色
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}/roles/{role_id}",
+    method = "delete",
+    permission = "system:rbac"
+)]
+pub async fn remove_user_role_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<(String, i32)>,
+) -> HttpResult {
+    let (user_uuid, role_id) = path.into_inner();
+    RbacService::remove_role(&db_pool, &user_uuid, role_id).await?;
+    Ok(ApiResponse::success((), "角色移除成功").to_http_response())
+}
+
+/// 获取用户的特殊权限
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}/permissions",
+    method = "get",
+    permission = "system:rbac"
+)]
+pub async fn list_user_permissions_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<String>,
+) -> HttpResult {
+    let perms = RbacService::list_user_permissions(&db_pool, &path).await?;
+    Ok(ApiResponse::success(perms, "获取用户权限成功").to_http_response())
+}
+
+/// 授予或拒绝用户权限
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}/permissions",
+    method = "post",
+    permission = "system:rbac"
+)]
+pub async fn grant_user_permission_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<String>,
+    body: web::Json<GrantUserPermissionRequest>,
+) -> HttpResult {
+    RbacService::grant_user_permission(&db_pool, &path, body.into_inner()).await?;
+    Ok(ApiResponse::success((), "用户权限设置成功").to_http_response())
+}
+
+/// 撤销用户的特殊权限
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}/permissions/{permission_id}",
+    method = "delete",
+    permission = "system:rbac"
+)]
+pub async fn revoke_user_permission_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<(String, i32)>,
+) -> HttpResult {
+    let (user_uuid, permission_id) = path.into_inner();
+    RbacService::revoke_user_permission(&db_pool, &user_uuid, permission_id).await?;
+    Ok(ApiResponse::success((), "用户权限已撤销").to_http_response())
+}
+
+/// 强制用户下线，作废其全部刷新令牌与已签发的访问令牌
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}/sessions",
+    method = "delete",
+    permission = "user:session:revoke"
+)]
+pub async fn revoke_user_sessions_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<String>,
+) -> HttpResult {
+    AuthService::revoke_sessions(&db_pool, &path).await
+}
@@ -1,9 +1,15 @@
 use crate::{HttpResult, config::AppError, models::room_messages};
 use actix_web::web;
+use route_macros::route_permission;
 use sea_orm::{EntityTrait, prelude::*};
 
 use crate::ApiResponse;
 
+#[route_permission(
+    path = "/api/v1/rooms/{room_id}/messages",
+    method = "get",
+    public = true
+)]
 pub async fn get_room_messages_handler(
     db: web::Data<DatabaseConnection>,
     room_id: web::Path<String>,
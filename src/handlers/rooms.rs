@@ -1,6 +1,7 @@
 use crate::{EmptyResponse, HttpResult, config::AppError};
 use actix_web::{HttpResponse, Result, web};
 use chrono::Utc;
+use route_macros::route_permission;
 use sea_orm::{ActiveValue::Set, EntityTrait, prelude::*};
 use serde::{Deserialize, Serialize};
 
@@ -22,6 +23,7 @@ pub struct RoomResponse {
     pub user_count: i32,
 }
 
+#[route_permission(path = "/api/v1/rooms", method = "post", public = true)]
 pub async fn create_room_handler(
     db: web::Data<DatabaseConnection>,
     room_data: web::Json<CreateRoomRequest>,
@@ -54,6 +56,7 @@ pub async fn create_room_handler(
     Ok(ApiResponse::success(room, "创建房间成功").to_http_response())
 }
 
+#[route_permission(path = "/api/v1/rooms/{room_id}", method = "get", public = true)]
 pub async fn get_room_handler(
     db_pool: web::Data<DatabaseConnection>,
     room_name: web::Path<String>,
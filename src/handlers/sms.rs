@@ -0,0 +1,67 @@
+use crate::ApiResponse;
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::dto::user::{RE_PHONE, ValidationErrorJson};
+use crate::handlers::email::SendVerificationCodeResponse;
+use crate::services::{SmsService, SmsVerificationManager};
+use crate::utils::rate_limit;
+use actix_web::{HttpResponse, web};
+use route_macros::route_permission;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// 发送短信验证码请求
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct SendSmsCodeRequest {
+    /// 手机号
+    #[validate(regex(path = "*RE_PHONE", message = "手机号格式错误"))]
+    #[schema(example = "13800138000")]
+    pub phone: String,
+}
+
+/// 发送短信验证码
+#[utoipa::path(
+    post,
+    path = "/api/v1/sms/send-verification-code",
+    tag = "短信",
+    summary = "发送短信验证码",
+    request_body = SendSmsCodeRequest,
+    responses(
+        (status = 200, description = "验证码发送成功", body = SendVerificationCodeResponse),
+        (status = 400, description = "请求参数错误", body = ApiResponse<ValidationErrorJson>),
+        (status = 429, description = "发送过于频繁，见 Retry-After 响应头"),
+        (status = 500, description = "服务器内部错误", body = ApiResponse<ValidationErrorJson>)
+    )
+)]
+#[route_permission(
+    path = "/api/v1/sms/send-verification-code",
+    method = "post",
+    public = true
+)]
+pub async fn send_sms_code(
+    request: web::Json<SendSmsCodeRequest>,
+    sms_service: web::Data<SmsService>,
+    sms_verification_manager: web::Data<SmsVerificationManager>,
+) -> Result<HttpResponse, AppError> {
+    if let Err(errors) = request.validate() {
+        return Err(AppError::ValidationError(
+            ValidationErrorJson::from_validation_errors(&errors),
+        ));
+    }
+
+    // 同一手机号的发送频率限制，防止短信轰炸
+    rate_limit::check(
+        CONFIG.rate_limit.verification_code_target,
+        &format!("phone:{}", request.phone),
+    )?;
+
+    sms_verification_manager
+        .generate_and_send_code(&sms_service, &request.phone)
+        .await
+        .map_err(|e| AppError::ExternalServiceError(format!("发送验证码失败: {e}")))?;
+    Ok(HttpResponse::Ok().json(SendVerificationCodeResponse {
+        success: true,
+        message: "验证码已发送，请查收短信".to_string(),
+    }))
+}
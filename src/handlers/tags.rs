@@ -4,11 +4,13 @@ use crate::dto::posts::{CategoryResponse, PostResponse};
 use crate::dto::tag::{CreateTagRequest, TagCloudItem};
 use crate::dto::user::ValidationErrorJson;
 use crate::dto::{PaginatedResp, Pagination};
+use crate::models::posts::PostStatus;
 use crate::models::tags::PostWithCategory;
-use crate::models::{categories, post_tags, posts, tags};
-use crate::{ApiResponse, EmptyResponse, HttpResult, RouteInfo, utils::db_err_map};
+use crate::models::{categories, post_tags, posts, tags, users};
+use crate::services::posts::UNKNOWN_AUTHOR;
+use crate::{ApiResponse, EmptyResponse, HttpResult, utils::db_err_map};
 use actix_web::{HttpResponse, web};
-use route_macros::crud_entity;
+use route_macros::{crud_entity, route_permission};
 use sea_orm::prelude::Expr;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, PaginatorTrait,
@@ -21,11 +23,13 @@ crud_entity!({
     permission_prefix: "tags",
     id_type:"id",
     operations: ["create","list","delete","read"],
+    public_operations: ["list","read"],
     create_request_type: CreateTagRequest,
     openapi_summary: "标签",
 
 });
 
+#[route_permission(path = "/api/v1/tags/count", method = "get", public = true)]
 pub async fn get_tags_with_count_handler(db_pool: web::Data<DatabaseConnection>) -> HttpResult {
     let tag_counts = tags::Entity::find()
         .column_as(tags::Column::Id, "id")
@@ -52,6 +56,7 @@ pub async fn get_tags_with_count_handler(db_pool: web::Data<DatabaseConnection>)
 }
 
 /// 通过tag获取文章列表
+#[route_permission(path = "/api/v1/tags/{id}/posts", method = "get", public = true)]
 pub async fn get_posts_by_tag_handler(
     db_pool: web::Data<DatabaseConnection>,
     path: web::Path<i32>, // 标签ID
@@ -78,6 +83,7 @@ pub async fn get_posts_by_tag_handler(
         .select_only()
         .column(posts::Column::Id)
         .column(posts::Column::Uuid)
+        .column(posts::Column::Slug)
         .column(posts::Column::Title)
         .column(posts::Column::CoverImage)
         .column(posts::Column::PublishedAt)
@@ -89,6 +95,8 @@ pub async fn get_posts_by_tag_handler(
         .column(posts::Column::Size)
         .column_as(categories::Column::Id, "category_id")
         .column_as(categories::Column::Name, "category_name")
+        .column_as(users::Column::UserName, "author_name")
+        .column_as(users::Column::Image, "author_avatar")
         .join(
             sea_orm::JoinType::InnerJoin,
             posts::Relation::PostTags.def(),
@@ -98,7 +106,8 @@ pub async fn get_posts_by_tag_handler(
             sea_orm::JoinType::LeftJoin,
             posts::Relation::Categories.def(),
         )
-        // .filter(posts::Column::Status.eq(1))
+        .join(sea_orm::JoinType::LeftJoin, posts::Relation::Users.def())
+        .filter(posts::Column::Status.eq(PostStatus::Published))
         .order_by_desc(posts::Column::PublishedAt)
         .into_model::<PostWithCategory>()
         .paginate(db_pool.as_ref(), limit);
@@ -112,15 +121,20 @@ pub async fn get_posts_by_tag_handler(
         .map(|post| PostResponse {
             id: post.id,
             uuid: post.uuid,
+            slug: post.slug,
             title: post.title,
             cover: post.cover_image.unwrap_or_default(),
-            author: "朝阳".to_string(),
+            author: post
+                .author_name
+                .unwrap_or_else(|| UNKNOWN_AUTHOR.to_string()),
+            author_avatar: post.author_avatar,
             publish_time: post.published_at,
             update_time: post.updated_at,
             views: post.view_count,
             is_top: post.featured,
-            is_publish: post.status == 1,
-            is_hide: post.status == 2,
+            status: post.status,
+            is_publish: post.status == PostStatus::Published,
+            is_hide: post.status == PostStatus::Offline,
             description: post.summary.unwrap_or_default(),
             size: post.size,
             // category,
@@ -0,0 +1,84 @@
+use crate::dto::two_factor::TwoFactorCodeRequest;
+use crate::handlers::validate_body;
+use crate::middleware::CurrentUser;
+use crate::services::TwoFactorService;
+use crate::{ApiResponse, HttpResult};
+use actix_web::web;
+use route_macros::route_permission;
+use sea_orm::DatabaseConnection;
+
+/// 查询两步验证状态
+#[route_permission(
+    path = "/api/v1/users/me/2fa",
+    method = "get",
+    permission = "user:profile"
+)]
+pub async fn two_factor_status_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+) -> HttpResult {
+    let status = TwoFactorService::status(&db_pool, user.user_id).await?;
+    Ok(ApiResponse::success(status, "获取两步验证状态成功").to_http_response())
+}
+
+/// 生成两步验证密钥与 otpauth 地址
+#[route_permission(
+    path = "/api/v1/users/me/2fa/setup",
+    method = "post",
+    permission = "user:profile"
+)]
+pub async fn setup_two_factor_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+) -> HttpResult {
+    let setup = TwoFactorService::setup(&db_pool, &user).await?;
+    Ok(ApiResponse::success(setup, "请在认证器中添加后提交验证码").to_http_response())
+}
+
+/// 确认启用两步验证，返回恢复码
+#[route_permission(
+    path = "/api/v1/users/me/2fa/enable",
+    method = "post",
+    permission = "user:profile"
+)]
+pub async fn enable_two_factor_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    body: web::Json<TwoFactorCodeRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let codes = TwoFactorService::enable(&db_pool, &user, &body.code).await?;
+    Ok(ApiResponse::success(codes, "两步验证已启用，请妥善保存恢复码").to_http_response())
+}
+
+/// 关闭两步验证
+#[route_permission(
+    path = "/api/v1/users/me/2fa/disable",
+    method = "post",
+    permission = "user:profile"
+)]
+pub async fn disable_two_factor_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    body: web::Json<TwoFactorCodeRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    TwoFactorService::disable(&db_pool, &user, &body.code).await?;
+    Ok(ApiResponse::success((), "两步验证已关闭").to_http_response())
+}
+
+/// 重新生成恢复码
+#[route_permission(
+    path = "/api/v1/users/me/2fa/recovery-codes",
+    method = "post",
+    permission = "user:profile"
+)]
+pub async fn regenerate_recovery_codes_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    user: CurrentUser,
+    body: web::Json<TwoFactorCodeRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let codes = TwoFactorService::regenerate_recovery_codes(&db_pool, &user, &body.code).await?;
+    Ok(ApiResponse::success(codes, "恢复码已重新生成，旧恢复码已作废").to_http_response())
+}
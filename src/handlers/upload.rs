@@ -1,8 +1,10 @@
 use actix_multipart::Multipart;
+use route_macros::route_permission;
 
 use crate::{HttpResult, upload::UploadManager};
 
 /// 处理文件上传
+#[route_permission(path = "/api/v1/upload", method = "post", permission = "upload:create")]
 pub async fn upload_file_handler(payload: Multipart) -> HttpResult {
     let upload_manager = UploadManager::default();
     let result = upload_manager.handle_upload(payload).await?;
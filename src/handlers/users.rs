@@ -1,10 +1,14 @@
 use crate::config::AppError;
 use crate::dto::PaginationQuery;
+use crate::dto::user::{UpdateUserRequest, UpdateUserStatusRequest};
 use crate::dto::{PaginatedResp, Pagination};
+use crate::handlers::validate_body;
+use crate::middleware::CurrentUser;
 use crate::models::users;
-use crate::{ApiResponse, EmptyResponse, HttpResult, RouteInfo};
+use crate::services::UserService;
+use crate::{ApiResponse, EmptyResponse, HttpResult};
 use actix_web::{HttpResponse, web};
-use route_macros::crud_entity;
+use route_macros::{crud_entity, route_permission};
 use sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait};
 
 crud_entity!({
@@ -16,6 +20,55 @@ crud_entity!({
     operations: ["list","read"],
 });
 
+/// 管理员修改用户资料
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}",
+    method = "patch",
+    permission = "user:update"
+)]
+pub async fn admin_update_user_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    path: web::Path<String>,
+    body: web::Json<UpdateUserRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let user = UserService::update(&db_pool, &path, body.into_inner()).await?;
+    Ok(ApiResponse::success(user, "用户资料已更新").to_http_response())
+}
+
+/// 管理员停用、封禁或恢复用户
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}/status",
+    method = "put",
+    permission = "user:update"
+)]
+pub async fn admin_update_user_status_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    operator: CurrentUser,
+    path: web::Path<String>,
+    body: web::Json<UpdateUserStatusRequest>,
+) -> HttpResult {
+    validate_body(&*body)?;
+    let user =
+        UserService::set_status(&db_pool, operator.user_id, &path, body.into_inner()).await?;
+    Ok(ApiResponse::success(user, "账号状态已更新").to_http_response())
+}
+
+/// 管理员注销用户（软删除，保留其发布的内容）
+#[route_permission(
+    path = "/api/v1/admin/users/{uuid}",
+    method = "delete",
+    permission = "user:delete"
+)]
+pub async fn admin_delete_user_handler(
+    db_pool: web::Data<DatabaseConnection>,
+    operator: CurrentUser,
+    path: web::Path<String>,
+) -> HttpResult {
+    UserService::delete(&db_pool, operator.user_id, &path).await?;
+    Ok(ApiResponse::success((), "用户已删除").to_http_response())
+}
+
 // / 获取用户列表
 // #[utoipa::path(
 //     get,
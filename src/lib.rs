@@ -24,12 +24,24 @@ use std::sync::RwLock;
 // 重新导出宏
 pub use route_macros::route_permission;
 
+/// 路由鉴权方式，由 `#[route_permission]` 的 `public` / `auth` 参数声明
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteAuth {
+    /// 公开访问，不校验令牌
+    Public,
+    /// 可选登录：携带有效令牌时识别用户，否则按匿名访问
+    Optional,
+    /// 必须登录，并校验权限码
+    Required,
+}
+
 // 定义路由信息结构体（必须与宏crate中的完全一致）
 #[derive(Debug, Clone)]
 pub struct RouteInfo {
     pub path: &'static str,
     pub method: &'static str,
     pub permission: &'static str,
+    pub auth: RouteAuth,
     // pub handler: fn(), // 简化表示
 }
 // 收集所有被宏标记的路由
@@ -70,8 +82,89 @@ pub fn get_all_routes() -> Result<Vec<RouteInfo>, AppError> {
         .map_err(|e| AppError::InternalServerError(format!("读取路由注册表失败: {e}")))?;
     Ok(registry.values().cloned().collect())
 }
+
+/// 根据请求方法和实际路径，在路由注册表中查找匹配的路由。
+///
+/// 路由模式支持 `{param}`（匹配单个路径段）以及 `{param:.*}`（匹配剩余全部路径）。
+/// 当多个模式同时匹配时，优先选择字面量路径段更多的那个，
+/// 例如 `/api/v1/tags/count` 优先于 `/api/v1/tags/{id}`。
+///
+/// # Errors
+///
+/// 如果路由注册表的读写锁被中毒，返回 `AppError::InternalServerError`。
+pub fn match_route(method: &str, path: &str) -> Result<Option<RouteInfo>, AppError> {
+    let registry = ROUTE_REGISTRY
+        .read()
+        .map_err(|e| AppError::InternalServerError(format!("读取路由注册表失败: {e}")))?;
+    let method = method.to_lowercase();
+    let matched = registry
+        .values()
+        .filter(|route| route.method.eq_ignore_ascii_case(&method))
+        .filter_map(|route| route_match_score(route.path, path).map(|score| (score, route)))
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, route)| route.clone());
+    Ok(matched)
+}
+
+/// 计算路由模式与实际路径的匹配度，不匹配时返回 `None`，
+/// 匹配时返回字面量路径段的数量（越大越精确）。
+fn route_match_score(pattern: &str, path: &str) -> Option<usize> {
+    let pattern_segments: Vec<&str> = pattern.trim_matches('/').split('/').collect();
+    let path_segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let mut score = 0;
+
+    for (idx, segment) in pattern_segments.iter().enumerate() {
+        if let Some(param) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            // `{uuid:.*}` 这类尾部通配，匹配剩余所有路径段
+            if param.ends_with(":.*") || param.ends_with(":.+") {
+                return (idx < path_segments.len() || param.ends_with(":.*")).then_some(score);
+            }
+            match path_segments.get(idx) {
+                Some(value) if !value.is_empty() => {}
+                _ => return None,
+            }
+        } else if path_segments.get(idx) == Some(segment) {
+            score += 1;
+        } else {
+            return None;
+        }
+    }
+
+    (pattern_segments.len() == path_segments.len()).then_some(score)
+}
 use route_macros::flush_crud_logs;
 
 use crate::config::AppError;
 
 flush_crud_logs!();
+
+#[cfg(test)]
+mod tests {
+    use super::route_match_score;
+
+    #[test]
+    fn route_pattern_matching() {
+        assert_eq!(route_match_score("/api/v1/posts", "/api/v1/posts"), Some(3));
+        assert_eq!(
+            route_match_score("/api/v1/posts/{uuid}", "/api/v1/posts/abc"),
+            Some(3)
+        );
+        assert_eq!(
+            route_match_score("/api/v1/posts/{uuid}", "/api/v1/posts"),
+            None
+        );
+        assert_eq!(
+            route_match_score("/api/v1/posts/{uuid}", "/api/v1/posts/a/b"),
+            None
+        );
+        assert_eq!(
+            route_match_score("/api/v1/users/{uuid:.*}", "/api/v1/users/a/b"),
+            Some(3)
+        );
+        assert_eq!(
+            route_match_score("/api/v1/tags/count", "/api/v1/tags/count"),
+            Some(4)
+        );
+        assert_eq!(route_match_score("/api/v1/tags", "/api/v1/links"), None);
+    }
+}
@@ -13,14 +13,23 @@ use web_server::{
     config::{init_logger, manager::CONFIG, write_to_file},
     config_routes, create_db_pool, init_route_registry,
     middleware::auth::Auth,
-    services::{EmailService, EmailVerificationManager},
-    utils::{perm_cache::load_perm_cache, websocket::ChatServer},
+    services::{
+        EmailService, EmailVerificationManager, OAuthService, SmsService, SmsVerificationManager,
+        TwoFactorChallengeManager, permission_sync::PermissionSyncService,
+        post_search::PostSearchService, posts::PostService,
+    },
+    utils::{
+        jwt_keys::jwt_keys, perm_cache::load_perm_cache, token_revocation::load_blocked_users,
+        websocket::ChatServer,
+    },
 };
 
 #[actix_web::main]
 async fn main() -> Result<()> {
     init_logger(); // 初始化日志
     log::info!("app config: {:#?}", CONFIG.jwt);
+    // 校验 JWT 密钥配置，配置错误时直接退出
+    jwt_keys().map_err(|e| anyhow::anyhow!("Invalid JWT key configuration: {e}"))?;
     // 初始化路由注册表 - 这行很重要！
     let _ = init_route_registry();
 
@@ -33,9 +42,46 @@ async fn main() -> Result<()> {
     let db = create_db_pool()
         .await
         .context("Failed to connect to database")?;
+
+    if let Err(e) = PostSearchService::ensure_index(&db).await {
+        log::error!("{e}");
+    }
+    // 子命令：升级已有文章（回填 slug、重新渲染）并重建搜索索引后退出，
+    // 例如 `cargo run -- reindex-posts`，见 src/sql/upgrade_posts.sql
+    if std::env::args().nth(1).as_deref() == Some("reindex-posts") {
+        let count = PostService::upgrade_existing(&db)
+            .await
+            .map_err(|e| anyhow::anyhow!("升级已有文章失败: {e}"))?;
+        log::info!("已为 {count} 篇文章生成 slug，并重新渲染全部文章");
+        let count = PostSearchService::reindex_all(&db)
+            .await
+            .map_err(|e| anyhow::anyhow!("重建搜索索引失败: {e}"))?;
+        log::info!("已为 {count} 篇文章重建搜索索引");
+        return Ok(());
+    }
+    // 子命令：仅同步路由权限码后退出，例如 `cargo run -- sync-permissions`
+    if std::env::args().nth(1).as_deref() == Some("sync-permissions") {
+        let report =
+            PermissionSyncService::sync(&db, CONFIG.permission_sync.auto_grant_role.as_deref())
+                .await
+                .map_err(|e| anyhow::anyhow!("权限同步失败: {e}"))?;
+        PermissionSyncService::log_report(&report);
+        return Ok(());
+    }
+    if CONFIG.permission_sync.sync_on_startup {
+        match PermissionSyncService::sync(&db, CONFIG.permission_sync.auto_grant_role.as_deref())
+            .await
+        {
+            Ok(report) => PermissionSyncService::log_report(&report),
+            Err(e) => log::error!("启动时权限同步失败: {e}"),
+        }
+    }
     // 将db添加到应用数据中
     let db_pool = web::Data::new(db);
     load_perm_cache(&db_pool.clone()).await.unwrap();
+    load_blocked_users(&db_pool).await.unwrap();
+    // 启动定时发布文章任务
+    PostService::start_scheduler(db_pool.get_ref().clone());
     // 添加sse
     let notifier = web::Data::new(SseNotifier::new());
     let chat_server = web::Data::new(Mutex::new(ChatServer::new()));
@@ -49,6 +95,19 @@ async fn main() -> Result<()> {
     // // 启动邮件验证码清理任务
     email_verification_manager.start_cleanup_task();
 
+    // 初始化短信服务与短信验证码管理器
+    let sms_service = web::Data::new(SmsService::default());
+    let sms_verification_manager = web::Data::new(SmsVerificationManager::default());
+    sms_verification_manager.start_cleanup_task();
+
+    // 初始化第三方登录服务
+    let oauth_service = web::Data::new(OAuthService::default());
+    oauth_service.start_cleanup_task();
+
+    // 初始化两步验证登录挑战管理器
+    let two_factor_challenges = web::Data::new(TwoFactorChallengeManager::default());
+    two_factor_challenges.start_cleanup_task();
+
     write_to_file(); // api_doc生成文件
     log::info!(
         "Server running on {}:{}",
@@ -99,6 +158,10 @@ async fn main() -> Result<()> {
             .app_data(chat_server.clone()) // 共享聊天服务器状态
             .app_data(email_service.clone()) // 添加邮件服务
             .app_data(email_verification_manager.clone()) // 添加邮件验证码管理器
+            .app_data(sms_service.clone())
+            .app_data(sms_verification_manager.clone())
+            .app_data(oauth_service.clone())
+            .app_data(two_factor_challenges.clone())
             .configure(config_routes)
             .wrap(actix_web::middleware::Logger::default())
             .wrap(cors)
@@ -1,12 +1,21 @@
-use crate::{get_all_routes, utils::perm_cache::ROLE_PERMS};
+use crate::services::api_token::{API_TOKEN_PREFIX, ApiTokenService};
+use crate::utils::{
+    auth_cookie::access_token,
+    jwt::{TokenClaims, decode_jwt},
+    perm_cache::has_permission,
+    token_revocation::is_revoked,
+};
+use crate::{RouteAuth, match_route};
 use actix_web::{
-    Error,
+    Error, HttpMessage,
     dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    web,
 };
 use sea_orm::DatabaseConnection;
 use std::{
     future::{Future, Ready, ready},
     pin::Pin,
+    rc::Rc,
 };
 
 use crate::config::AppError;
@@ -16,7 +25,7 @@ pub struct Auth;
 
 impl<S, B> Transform<S, ServiceRequest> for Auth
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -27,17 +36,19 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(AuthMiddleware { service }))
+        ready(Ok(AuthMiddleware {
+            service: Rc::new(service),
+        }))
     }
 }
 
 pub struct AuthMiddleware<S> {
-    service: S,
+    service: Rc<S>,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddleware<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -47,79 +58,229 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let path = req.path().to_string(); // 克隆 path
-        // 完全匹配的路径
-        let exact_paths = [
-            "/api/v1/auth/login",
-            "/api/v1/auth/register",
-            "/api/v1/sse",
-            "/api/v1/ws",
-        ];
-        // 前缀匹配的路径
-
-        let prefix_paths = [
-            "/api/v1/tags",
-            "/api/v1/posts",
-            "/api/v1/categories",
-            "/api/v1/rooms",
-            "/api/v1/upload",
-            "/api/v1/email",
-            "/api/v1/version",
-            "/api/v1/links",
-            "/api/v1/images",
-        ];
-        let is_public = exact_paths.contains(&path.as_str())
-            || prefix_paths.iter().any(|&prefix| path.starts_with(prefix));
-
-        if is_public {
-            // 公开路径，直接调用服务
-            let fut = self.service.call(req);
-            Box::pin(async move {
-                let res = fut.await?;
-                Ok(res)
-            })
-        } else {
-            // 需要认证的路径，先检查cookie
-            let cookie_result = req.cookie("access_token").ok_or_else(|| {
-                log::error!("access_token not found, path: {}", path);
-                AppError::Unauthorized("access_token not found".to_string())
-            });
-            match cookie_result {
-                Ok(cookie) => {
-                    let token = cookie.value();
-
-                    // 验证令牌
-                    match crate::utils::jwt::decode_jwt(token) {
+        let service = Rc::clone(&self.service);
+        Box::pin(async move {
+            let path = req.path().to_string(); // 克隆 path
+            // 按 方法 + 路径 在路由注册表中查找路由声明，未注册的路由默认需要登录
+            let route = match_route(req.method().as_str(), &path)?;
+            let auth = route
+                .as_ref()
+                .map_or(RouteAuth::Required, |route| route.auth);
+
+            match auth {
+                // 公开路径，直接调用服务
+                RouteAuth::Public => service.call(req).await,
+                // 可选登录：令牌缺失或无效时按匿名访问
+                RouteAuth::Optional => {
+                    match authenticate(&req).await {
                         Ok(claims) => {
-                            log::info!("s: {:?}", claims);
-                            // 令牌有效，调用服务
-                            let fut = self.service.call(req);
-                            Box::pin(async move {
-                                let routes = get_all_routes()?;
-                                log::info!("Registered {} routes:", routes.len());
-                                for route in routes {
-                                    log::info!(
-                                        "  {} {} -> {}",
-                                        route.method,
-                                        route.path,
-                                        route.permission
-                                    );
-                                }
-                                let role_perms = ROLE_PERMS.read().await;
-                                if let Some(perms) = role_perms.get(&claims.role_id) {
-                                    log::info!("perms: {:?}", perms);
-                                }
-                                let res = fut.await?;
-                                Ok(res)
-                            })
+                            req.extensions_mut().insert(claims);
                         }
-                        Err(_) => Box::pin(async move {
-                            Err(AppError::Unauthorized("无效的令牌".to_string()).into())
-                        }),
+                        Err(e) => log::debug!("optional auth ignored token, path: {path}, {e}"),
                     }
+                    service.call(req).await
+                }
+                RouteAuth::Required => {
+                    let claims = authenticate(&req).await.inspect_err(|e| {
+                        log::error!("authentication failed, path: {path}, {e}");
+                    })?;
+
+                    // 校验路由声明的权限码
+                    if let Some(route) = route
+                        && !route.permission.is_empty()
+                    {
+                        // 个人访问令牌的权限为令牌范围与用户权限的交集
+                        let allowed = claims.scope_allows(route.permission)
+                            && has_permission(claims.user_id, &claims.role_ids, route.permission)
+                                .await;
+                        if !allowed {
+                            log::warn!(
+                                "permission denied: user={} {} {} requires {}",
+                                claims.user_name,
+                                route.method,
+                                route.path,
+                                route.permission
+                            );
+                            return Err(AppError::Forbidden(format!(
+                                "缺少权限: {}",
+                                route.permission
+                            ))
+                            .into());
+                        }
+                    }
+
+                    // 令牌有效且权限满足，把载荷交给后续处理器并调用服务
+                    req.extensions_mut().insert(claims);
+                    service.call(req).await
                 }
-                Err(e) => Box::pin(async move { Err(Error::from(e)) }),
             }
+        })
+    }
+}
+
+/// 从请求中读取并校验访问令牌（Bearer 请求头或 Cookie，见 `JWT_TOKEN_SOURCES`），
+/// `pat_` 开头的是个人访问令牌，其余按 JWT 校验
+async fn authenticate(req: &ServiceRequest) -> Result<TokenClaims, AppError> {
+    let token = access_token(req.request())
+        .ok_or_else(|| AppError::Unauthorized("access_token not found".to_string()))?;
+    if token.starts_with(API_TOKEN_PREFIX) {
+        let db = req
+            .app_data::<web::Data<DbPool>>()
+            .ok_or_else(|| AppError::InternalServerError("数据库连接未配置".to_string()))?;
+        return ApiTokenService::authenticate(db, &token).await;
+    }
+    let claims =
+        decode_jwt(&token).map_err(|_| AppError::Unauthorized("无效的令牌".to_string()))?;
+    if is_revoked(&claims).await {
+        return Err(AppError::Unauthorized("令牌已被撤销".to_string()));
+    }
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ROUTE_REGISTRY;
+    use crate::RouteInfo;
+    use crate::middleware::current_user::OptionalUser;
+    use crate::utils::jwt_keys::jwt_keys;
+    use crate::utils::perm_cache::{CACHE_TEST_LOCK, ROLE_PERMS};
+    use actix_web::http::StatusCode;
+    use actix_web::{App, HttpResponse, test};
+    use chrono::Utc;
+    use jsonwebtoken::{Header, encode};
+    use std::collections::HashSet;
+
+    const ROLE_ID: i32 = 8700;
+
+    /// 在全局路由注册表中登记测试路由，路径均以 `/__auth_test` 开头，不与业务路由冲突
+    fn register_routes() {
+        let routes = [
+            ("/__auth_test/public", "", RouteAuth::Public),
+            ("/__auth_test/optional", "", RouteAuth::Optional),
+            ("/__auth_test/required", "", RouteAuth::Required),
+            ("/__auth_test/read", "test:read", RouteAuth::Required),
+            ("/__auth_test/write", "test:write", RouteAuth::Required),
+        ];
+        let mut registry = ROUTE_REGISTRY.write().unwrap();
+        for (path, permission, auth) in routes {
+            registry.insert(
+                format!("GET:{path}"),
+                RouteInfo {
+                    path,
+                    method: "GET",
+                    permission,
+                    auth,
+                },
+            );
         }
     }
+
+    fn token(user_id: i32) -> String {
+        let now = Utc::now().timestamp();
+        let claims = TokenClaims {
+            user_uuid: format!("uuid-{user_id}"),
+            user_name: format!("user{user_id}"),
+            user_id,
+            jti: uuid::Uuid::new_v4().to_string(),
+            iat: now,
+            iat_ms: now * 1000,
+            exp: now + 600,
+            role_ids: vec![ROLE_ID],
+            scopes: None,
+            sid: None,
+        };
+        let keys = jwt_keys().unwrap();
+        let mut header = Header::new(keys.algorithm);
+        header.kid = keys.kid.clone();
+        encode(&header, &claims, &keys.encoding).unwrap()
+    }
+
+    /// 返回当前识别到的用户名，匿名访问时返回 `anonymous`
+    async fn whoami(user: OptionalUser) -> HttpResponse {
+        let name = user.0.map_or("anonymous".to_string(), |c| c.user_name);
+        HttpResponse::Ok().body(name)
+    }
+
+    /// 发起 GET 请求，返回状态码与响应体；中间件返回的错误按 `AppError` 的响应渲染
+    async fn get(path: &str, token: Option<&str>) -> (StatusCode, String) {
+        register_routes();
+        let app = test::init_service(
+            App::new()
+                .wrap(Auth)
+                .default_service(web::route().to(whoami)),
+        )
+        .await;
+        let mut req = test::TestRequest::get().uri(path);
+        if let Some(token) = token {
+            req = req.insert_header(("Authorization", format!("Bearer {token}")));
+        }
+        match test::try_call_service(&app, req.to_request()).await {
+            Ok(resp) => {
+                let status = resp.status();
+                let body = test::read_body(resp).await;
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+            Err(e) => (e.error_response().status(), e.to_string()),
+        }
+    }
+
+    #[actix_web::test]
+    async fn public_route_without_token() {
+        let (status, body) = get("/__auth_test/public", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "anonymous");
+    }
+
+    #[actix_web::test]
+    async fn optional_route_ignores_bad_token() {
+        let (status, body) = get("/__auth_test/optional", Some("not-a-jwt")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "anonymous");
+
+        let (status, body) = get("/__auth_test/optional", Some(&token(8701))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "user8701");
+    }
+
+    #[actix_web::test]
+    async fn required_route_without_token() {
+        let (status, _) = get("/__auth_test/required", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = get("/__auth_test/required", Some("not-a-jwt")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = get("/__auth_test/required", Some(&token(8702))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "user8702");
+    }
+
+    #[actix_web::test]
+    async fn missing_permission_is_forbidden() {
+        let _guard = CACHE_TEST_LOCK.lock().await;
+        ROLE_PERMS
+            .write()
+            .await
+            .insert(ROLE_ID, HashSet::from(["test:read".to_string()]));
+        let token = token(8703);
+
+        let (status, body) = get("/__auth_test/read", Some(&token)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "user8703");
+        let (status, body) = get("/__auth_test/write", Some(&token)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.contains("test:write"), "{body}");
+
+        ROLE_PERMS.write().await.remove(&ROLE_ID);
+    }
+
+    #[actix_web::test]
+    async fn unregistered_route_requires_login() {
+        let (status, _) = get("/__auth_test/unregistered", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = get("/__auth_test/unregistered", Some(&token(8704))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "user8704");
+    }
 }
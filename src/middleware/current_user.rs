@@ -0,0 +1,89 @@
+use crate::config::AppError;
+use crate::utils::jwt::TokenClaims;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, dev::Payload};
+use std::future::{Ready, ready};
+use std::ops::Deref;
+
+/// 当前登录用户，由认证中间件放入请求扩展的 `TokenClaims` 构造。
+///
+/// 只能用于需要登录的路由；未登录时返回 401。
+#[derive(Debug, Clone)]
+pub struct CurrentUser(pub TokenClaims);
+
+impl Deref for CurrentUser {
+    type Target = TokenClaims;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromRequest for CurrentUser {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<TokenClaims>()
+                .cloned()
+                .map(CurrentUser)
+                .ok_or_else(|| AppError::Unauthorized("请先登录".to_string())),
+        )
+    }
+}
+
+/// 可选的登录用户，用于公开或 `auth = "optional"` 的路由，匿名访问时为 `None`
+#[derive(Debug, Clone)]
+pub struct OptionalUser(pub Option<TokenClaims>);
+
+impl FromRequest for OptionalUser {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(OptionalUser(
+            req.extensions().get::<TokenClaims>().cloned(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn claims() -> TokenClaims {
+        TokenClaims {
+            user_uuid: "uuid".to_string(),
+            user_name: "tester".to_string(),
+            user_id: 7,
+            jti: "jti".to_string(),
+            iat: 0,
+            iat_ms: 0,
+            exp: 0,
+            role_ids: vec![1],
+            scopes: None,
+            sid: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn extract_from_request_extensions() {
+        let req = TestRequest::default().to_http_request();
+        assert!(CurrentUser::extract(&req).await.is_err());
+        assert!(OptionalUser::extract(&req).await.unwrap().0.is_none());
+
+        req.extensions_mut().insert(claims());
+        assert_eq!(CurrentUser::extract(&req).await.unwrap().user_id, 7);
+        assert_eq!(
+            OptionalUser::extract(&req)
+                .await
+                .unwrap()
+                .0
+                .unwrap()
+                .user_name,
+            "tester"
+        );
+    }
+}
@@ -90,6 +90,11 @@ impl From<AppError> for ApiResponse<ValidationErrorJson> {
                 message: msg,
                 data: None,
             },
+            AppError::RateLimited(msg, _) => ApiResponse {
+                code: 429,
+                message: msg,
+                data: None,
+            },
             AppError::InternalServerError(msg) => ApiResponse {
                 code: 500,
                 message: msg,
@@ -1,3 +1,7 @@
 pub mod helpers;
 pub use helpers::*;
 pub mod auth;
+pub mod current_user;
+pub use current_user::{CurrentUser, OptionalUser};
+pub mod rate_limit;
+pub use rate_limit::RateLimit;
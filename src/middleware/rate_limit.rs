@@ -0,0 +1,75 @@
+use crate::utils::rate_limit::{RateLimitPolicy, check, client_ip};
+use actix_web::{
+    Error,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+};
+use std::{
+    future::{Future, Ready, ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+/// 按客户端 IP 限流的路由中间件，不同 `scope` 分别计数：
+///
+/// ```ignore
+/// .route("/login", web::post().to(login).wrap(RateLimit::per_ip("login", CONFIG.rate_limit.login)))
+/// ```
+pub struct RateLimit {
+    scope: &'static str,
+    policy: RateLimitPolicy,
+}
+
+impl RateLimit {
+    pub fn per_ip(scope: &'static str, policy: RateLimitPolicy) -> Self {
+        Self { scope, policy }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            scope: self.scope,
+            policy: self.policy,
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    scope: &'static str,
+    policy: RateLimitPolicy,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let key = format!("{}:ip:{}", self.scope, client_ip(req.request()));
+        let limited = check(self.policy, &key);
+        let service = Rc::clone(&self.service);
+        Box::pin(async move {
+            limited.inspect_err(|_| log::warn!("rate limited: {key}"))?;
+            service.call(req).await
+        })
+    }
+}
@@ -2,17 +2,27 @@
 
 pub mod prelude;
 
+pub mod api_tokens;
 pub mod categories;
 pub mod external_links;
 pub mod images;
+pub mod login_history;
 pub mod permissions;
+pub mod post_revisions;
+pub mod post_slug_history;
 pub mod post_tags;
 pub mod posts;
+pub mod refresh_tokens;
 pub mod role_permissions;
 pub mod roles;
 pub mod room_messages;
 pub mod rooms;
 pub mod tags;
+pub mod two_factor_recovery_codes;
+pub mod user_action_tokens;
+pub mod user_oauth_bindings;
 pub mod user_permissions;
 pub mod user_roles;
+pub mod user_sessions;
+pub mod user_two_factor;
 pub mod users;
@@ -1,10 +1,13 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
-use crate::utils::fmt_beijing;
+use crate::utils::markdown::TocEntry;
+use crate::utils::{fmt_beijing, fmt_beijing_opt};
 
+use sea_orm::Condition;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use utoipa::ToSchema;
+use uuid::Uuid;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, ToSchema)]
 #[sea_orm(table_name = "posts")]
@@ -13,6 +16,9 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(unique)]
     pub uuid: String,
+    /// 固定链接中使用的 slug，改名后旧的 slug 保存在 `post_slug_history`
+    #[sea_orm(unique)]
+    pub slug: String,
     pub author_id: i32,
     pub category_id: i32,
     pub title: String,
@@ -22,7 +28,7 @@ pub struct Model {
     pub content: String,
     pub markdowncontent: String,
     pub cover_image: Option<String>,
-    pub status: i16,
+    pub status: PostStatus,
     pub featured: bool,
     pub view_count: i32,
     #[schema(value_type = String, format = DateTime)]
@@ -31,10 +37,64 @@ pub struct Model {
     #[schema(value_type = String, format = DateTime)]
     #[serde(serialize_with = "fmt_beijing")]
     pub updated_at: DateTimeUtc,
-    #[schema(value_type = String, format = DateTime)]
-    #[serde(serialize_with = "fmt_beijing")]
-    pub published_at: DateTimeUtc,
+    /// 发布时间，定时发布时为计划发布时间，草稿为空
+    #[schema(value_type = Option<String>, format = DateTime)]
+    #[serde(serialize_with = "fmt_beijing_opt")]
+    pub published_at: Option<DateTimeUtc>,
+    /// 字数，中日韩字符按字计，其他语言按词计
     pub size: i32,
+    /// 预计阅读分钟数
+    pub reading_minutes: i32,
+    /// 根据标题生成的目录
+    #[sea_orm(column_type = "JsonBinary")]
+    #[schema(value_type = Vec<TocEntry>)]
+    pub toc: JsonValue,
+}
+
+/// 文章状态
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
+#[serde(rename_all = "snake_case")]
+pub enum PostStatus {
+    /// 草稿，仅作者可见
+    #[sea_orm(num_value = 0)]
+    Draft,
+    /// 已发布
+    #[sea_orm(num_value = 1)]
+    Published,
+    /// 已下线，仅作者可见
+    #[sea_orm(num_value = 2)]
+    Offline,
+    /// 定时发布，到 `published_at` 后由后台任务自动发布
+    #[sea_orm(num_value = 3)]
+    Scheduled,
+}
+
+impl PostStatus {
+    /// 中文名称，用于提示信息
+    pub fn label(self) -> &'static str {
+        match self {
+            PostStatus::Draft => "草稿",
+            PostStatus::Published => "已发布",
+            PostStatus::Offline => "已下线",
+            PostStatus::Scheduled => "定时发布",
+        }
+    }
+
+    /// 是否允许从当前状态切换到 `next`，保持原状态总是允许的
+    pub fn can_transition_to(self, next: PostStatus) -> bool {
+        use PostStatus::*;
+        self == next
+            || matches!(
+                (self, next),
+                (Draft, Published | Scheduled)
+                    | (Scheduled, Draft | Published)
+                    | (Published, Offline)
+                    | (Offline, Published | Draft)
+            )
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -47,6 +107,8 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     Categories,
+    #[sea_orm(has_many = "super::post_slug_history::Entity")]
+    PostSlugHistory,
     #[sea_orm(has_many = "super::post_tags::Entity")]
     PostTags,
     #[sea_orm(
@@ -65,6 +127,12 @@ impl Related<super::categories::Entity> for Entity {
     }
 }
 
+impl Related<super::post_slug_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostSlugHistory.def()
+    }
+}
+
 impl Related<super::post_tags::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PostTags.def()
@@ -90,6 +158,26 @@ impl Entity {
     pub fn find_by_uuid(uuid: &str) -> Select<Entity> {
         Self::find().filter(Column::Uuid.eq(uuid))
     }
+
+    /// 按 UUID 或 slug 查询，UUID 形式的参数按 UUID 查找
+    pub fn find_by_key(key: &str) -> Select<Entity> {
+        if Uuid::parse_str(key).is_ok() {
+            Self::find_by_uuid(key)
+        } else {
+            Self::find().filter(Column::Slug.eq(key))
+        }
+    }
+
+    /// 访问者可见的文章：已发布的文章，以及登录用户自己的全部文章
+    pub fn visible_to(viewer_id: Option<i32>) -> Condition {
+        let published = Condition::all().add(Column::Status.eq(PostStatus::Published));
+        match viewer_id {
+            Some(user_id) => Condition::any()
+                .add(published)
+                .add(Column::AuthorId.eq(user_id)),
+            None => published,
+        }
+    }
 }
 impl From<Model> for JsonValue {
     fn from(model: Model) -> JsonValue {
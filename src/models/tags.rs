@@ -43,16 +43,19 @@ impl ActiveModelBehavior for ActiveModel {}
 pub struct PostWithCategory {
     pub id: i32,
     pub uuid: String,
+    pub slug: String,
     pub title: String,
     pub cover_image: Option<String>,
-    pub published_at: DateTimeUtc,
+    pub published_at: Option<DateTimeUtc>,
     pub updated_at: DateTimeUtc,
     pub view_count: i32,
     pub featured: bool,
-    pub status: i16,
+    pub status: super::posts::PostStatus,
     pub summary: Option<String>,
     pub size: i32,
     pub category_id: Option<i32>,
     pub category_name: Option<String>,
+    pub author_name: Option<String>,
+    pub author_avatar: Option<String>,
 }
 impl_entity_unique_check!(Entity, Model);
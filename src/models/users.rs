@@ -16,10 +16,17 @@ pub struct Model {
     pub pass_word: String, // 密码
     #[sea_orm(unique)]
     pub email: Option<String>, // 邮箱
+    pub email_verified: bool,  // 邮箱是否已验证
     pub image: Option<String>, // 头像
     #[sea_orm(unique)]
     pub phone: Option<String>, // 手机号
-    pub binding: Option<String>, // authentication绑定
+    pub binding: Option<String>, // 已废弃，第三方绑定见 user_oauth_bindings
+    pub status: UserStatus,    // 账号状态
+    pub status_reason: Option<String>, // 停用或封禁原因
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub banned_until: Option<DateTimeUtc>, // 封禁截止时间，为空表示永久封禁
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub deleted_at: Option<DateTimeUtc>, // 注销时间
     #[schema(value_type = String, format = DateTime)]
     #[sea_orm(default_value_t = DateTimeUtc::default())]
     #[serde(serialize_with = "fmt_beijing")]
@@ -30,6 +37,38 @@ pub struct Model {
     pub updated_at: DateTimeUtc,
 }
 
+/// 账号状态
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum UserStatus {
+    /// 正常
+    #[sea_orm(string_value = "active")]
+    Active,
+    /// 已被管理员停用，不能登录
+    #[sea_orm(string_value = "disabled")]
+    Disabled,
+    /// 已封禁，到 `banned_until` 后自动解除
+    #[sea_orm(string_value = "banned")]
+    Banned,
+    /// 已注销（软删除），保留其发布的内容
+    #[sea_orm(string_value = "deleted")]
+    Deleted,
+}
+
+impl Model {
+    /// 当前是否处于不可用状态（停用、封禁期内或已注销）
+    pub fn is_blocked(&self, now: DateTimeUtc) -> bool {
+        match self.status {
+            UserStatus::Active => false,
+            UserStatus::Banned => self.banned_until.is_none_or(|until| until > now),
+            UserStatus::Disabled | UserStatus::Deleted => true,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::posts::Entity")]
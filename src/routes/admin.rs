@@ -0,0 +1,88 @@
+use crate::handlers::rbac::{
+    assign_user_role_handler, attach_role_permissions_handler, create_permission_handler,
+    create_role_handler, delete_permission_handler, delete_role_handler,
+    detach_role_permission_handler, grant_user_permission_handler, list_permissions_handler,
+    list_roles_handler, list_user_permissions_handler, list_user_roles_handler,
+    registry_permissions_handler, reload_permissions_handler, remove_user_role_handler,
+    revoke_user_permission_handler, revoke_user_sessions_handler, update_permission_handler,
+    update_role_handler,
+};
+use crate::handlers::users::{
+    admin_delete_user_handler, admin_update_user_handler, admin_update_user_status_handler,
+};
+use actix_web::web;
+
+pub fn config_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/v1/admin")
+            // 角色
+            .route("/roles", web::get().to(list_roles_handler))
+            .route("/roles", web::post().to(create_role_handler))
+            .route("/roles/{id}", web::put().to(update_role_handler))
+            .route("/roles/{id}", web::delete().to(delete_role_handler))
+            .route(
+                "/roles/{id}/permissions",
+                web::post().to(attach_role_permissions_handler),
+            )
+            .route(
+                "/roles/{id}/permissions/{permission_id}",
+                web::delete().to(detach_role_permission_handler),
+            )
+            // 权限
+            .route("/permissions", web::get().to(list_permissions_handler))
+            .route("/permissions", web::post().to(create_permission_handler))
+            .route(
+                "/permissions/registry",
+                web::get().to(registry_permissions_handler),
+            )
+            .route(
+                "/permissions/reload",
+                web::post().to(reload_permissions_handler),
+            )
+            .route(
+                "/permissions/{id}",
+                web::put().to(update_permission_handler),
+            )
+            .route(
+                "/permissions/{id}",
+                web::delete().to(delete_permission_handler),
+            )
+            // 用户资料与状态
+            .route("/users/{uuid}", web::patch().to(admin_update_user_handler))
+            .route("/users/{uuid}", web::delete().to(admin_delete_user_handler))
+            .route(
+                "/users/{uuid}/status",
+                web::put().to(admin_update_user_status_handler),
+            )
+            // 用户角色与特殊权限
+            .route(
+                "/users/{uuid}/roles",
+                web::get().to(list_user_roles_handler),
+            )
+            .route(
+                "/users/{uuid}/roles",
+                web::post().to(assign_user_role_handler),
+            )
+            .route(
+                "/users/{uuid}/roles/{role_id}",
+                web::delete().to(remove_user_role_handler),
+            )
+            .route(
+                "/users/{uuid}/permissions",
+                web::get().to(list_user_permissions_handler),
+            )
+            .route(
+                "/users/{uuid}/permissions",
+                web::post().to(grant_user_permission_handler),
+            )
+            .route(
+                "/users/{uuid}/permissions/{permission_id}",
+                web::delete().to(revoke_user_permission_handler),
+            )
+            // 会话
+            .route(
+                "/users/{uuid}/sessions",
+                web::delete().to(revoke_user_sessions_handler),
+            ),
+    );
+}
@@ -1,10 +1,48 @@
-use crate::handlers::auth::{login, register};
+use crate::config::manager::CONFIG;
+use crate::handlers::auth::{
+    forgot_password, login, logout, refresh, register, reset_password, verify_email,
+    verify_two_factor,
+};
+use crate::handlers::oauth::{oauth_authorize, oauth_callback};
+use crate::middleware::RateLimit;
 use actix_web::web;
 
 pub fn config_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/v1/auth")
             .route("/register", web::post().to(register))
-            .route("/login", web::post().to(login)),
+            .route(
+                "/login",
+                web::post()
+                    .to(login)
+                    .wrap(RateLimit::per_ip("login", CONFIG.rate_limit.login)),
+            )
+            .route(
+                "/2fa/verify",
+                web::post()
+                    .to(verify_two_factor)
+                    .wrap(RateLimit::per_ip("login", CONFIG.rate_limit.login)),
+            )
+            .route("/verify-email", web::post().to(verify_email))
+            .route(
+                "/forgot-password",
+                web::post().to(forgot_password).wrap(RateLimit::per_ip(
+                    "verification-code",
+                    CONFIG.rate_limit.verification_code,
+                )),
+            )
+            .route(
+                "/reset-password",
+                web::post()
+                    .to(reset_password)
+                    .wrap(RateLimit::per_ip("login", CONFIG.rate_limit.login)),
+            )
+            .route("/refresh", web::post().to(refresh))
+            .route("/logout", web::post().to(logout))
+            .route(
+                "/oauth/{provider}/authorize",
+                web::get().to(oauth_authorize),
+            )
+            .route("/oauth/{provider}/callback", web::get().to(oauth_callback)),
     );
 }
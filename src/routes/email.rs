@@ -1,9 +1,18 @@
+use crate::config::manager::CONFIG;
 use crate::handlers::send_verification_code;
+use crate::middleware::RateLimit;
 use actix_web::web;
 
 pub fn config_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/v1/email").route(
-        "/send-verification-code",
-        web::get().to(send_verification_code),
-    ));
+    cfg.service(
+        web::scope("/v1/email").route(
+            "/send-verification-code",
+            web::get()
+                .to(send_verification_code)
+                .wrap(RateLimit::per_ip(
+                    "verification-code",
+                    CONFIG.rate_limit.verification_code,
+                )),
+        ),
+    );
 }
@@ -1,3 +1,4 @@
+pub mod admin;
 pub mod auth;
 pub mod categories;
 pub mod email;
@@ -5,12 +6,14 @@ pub mod images;
 pub mod links;
 pub mod posts;
 pub mod rooms;
+pub mod sms;
 pub mod tags;
 pub mod upload;
 pub mod users;
 mod version;
 use actix_web::web;
 
+use crate::handlers::auth::jwks;
 use crate::routes::version::get_version;
 
 pub fn config_routes(cfg: &mut web::ServiceConfig) {
@@ -34,9 +37,15 @@ pub fn config_routes(cfg: &mut web::ServiceConfig) {
             .configure(images::config_routes)
             // 邮件路由
             .configure(email::config_routes)
+            // 短信路由
+            .configure(sms::config_routes)
             // 房间路由
             .configure(rooms::config_routes)
+            // 权限管理路由
+            .configure(admin::config_routes)
             // 获取当前版本信息
             .route("/v1/version", web::get().to(get_version)),
-    );
+    )
+    // JWT 公钥集合，按惯例放在根路径下
+    .route("/.well-known/jwks.json", web::get().to(jwks));
 }
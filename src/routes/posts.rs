@@ -1,6 +1,9 @@
+use crate::handlers::post_revision::{
+    diff_revisions_handler, get_revision_handler, list_revisions_handler, restore_revision_handler,
+};
 use crate::handlers::posts::{
     create_post_handler, delete_post_handler, get_posts_all_handler, get_posts_handler,
-    get_prev_next_handler, get_timeline_handler, update_post_handler,
+    get_prev_next_handler, get_timeline_handler, search_posts_handler, update_post_handler,
 };
 use actix_web::web;
 
@@ -9,6 +12,21 @@ pub fn config_routes(cfg: &mut web::ServiceConfig) {
         web::scope("/v1/posts")
             .route("/prevNext/{uuid:.*}", web::get().to(get_prev_next_handler))
             .route("/uploadTime", web::get().to(get_timeline_handler))
+            .route("/search", web::get().to(search_posts_handler))
+            // 修订记录路由须在 `{uuid:.*}` 之前注册
+            .route("/{uuid}/revisions", web::get().to(list_revisions_handler))
+            .route(
+                "/{uuid}/revisions/diff",
+                web::get().to(diff_revisions_handler),
+            )
+            .route(
+                "/{uuid}/revisions/{id}",
+                web::get().to(get_revision_handler),
+            )
+            .route(
+                "/{uuid}/revisions/{id}/restore",
+                web::post().to(restore_revision_handler),
+            )
             .route("", web::get().to(get_posts_all_handler))
             .route("", web::post().to(create_post_handler))
             .route("/{uuid:.*}", web::get().to(get_posts_handler))
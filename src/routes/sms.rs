@@ -0,0 +1,14 @@
+use crate::config::manager::CONFIG;
+use crate::handlers::sms::send_sms_code;
+use crate::middleware::RateLimit;
+use actix_web::web;
+
+pub fn config_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/v1/sms").route(
+        "/send-verification-code",
+        web::post().to(send_sms_code).wrap(RateLimit::per_ip(
+            "verification-code",
+            CONFIG.rate_limit.verification_code,
+        )),
+    ));
+}
@@ -1,3 +1,16 @@
+use crate::handlers::account::{
+    change_password_handler, delete_account_handler, get_profile_handler, list_sessions_handler,
+    login_history_handler, resend_email_verification_handler, revoke_other_sessions_handler,
+    revoke_session_handler, update_profile_handler,
+};
+use crate::handlers::api_tokens::{
+    create_api_token_handler, list_api_tokens_handler, revoke_api_token_handler,
+};
+use crate::handlers::oauth::{list_oauth_bindings_handler, unlink_oauth_handler};
+use crate::handlers::two_factor::{
+    disable_two_factor_handler, enable_two_factor_handler, regenerate_recovery_codes_handler,
+    setup_two_factor_handler, two_factor_status_handler,
+};
 use crate::handlers::{get_users_all_handler, get_users_handler};
 use actix_web::web;
 
@@ -5,7 +18,47 @@ pub fn config_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/v1/users")
             .route("", web::get().to(get_users_all_handler))
-            .route("/{uuid:.*}", web::get().to(get_users_handler)), // .route("/{uuid:.*}", web::put().to(put_demo))
-                                                                    // .route("/{uuid:.*}", web::delete().to(delete_user_uuid)),
+            // 个人资料、访问令牌、两步验证、第三方绑定与邮箱验证，需在 `{uuid:.*}` 之前注册
+            .route("/me", web::get().to(get_profile_handler))
+            .route("/me", web::patch().to(update_profile_handler))
+            .route("/me", web::delete().to(delete_account_handler))
+            .route("/me/password", web::post().to(change_password_handler))
+            .route("/me/sessions", web::get().to(list_sessions_handler))
+            .route(
+                "/me/sessions",
+                web::delete().to(revoke_other_sessions_handler),
+            )
+            .route(
+                "/me/sessions/{id}",
+                web::delete().to(revoke_session_handler),
+            )
+            .route("/me/login-history", web::get().to(login_history_handler))
+            .route("/me/tokens", web::get().to(list_api_tokens_handler))
+            .route("/me/tokens", web::post().to(create_api_token_handler))
+            .route(
+                "/me/tokens/{id}",
+                web::delete().to(revoke_api_token_handler),
+            )
+            .route("/me/2fa", web::get().to(two_factor_status_handler))
+            .route("/me/2fa/setup", web::post().to(setup_two_factor_handler))
+            .route("/me/2fa/enable", web::post().to(enable_two_factor_handler))
+            .route(
+                "/me/2fa/disable",
+                web::post().to(disable_two_factor_handler),
+            )
+            .route(
+                "/me/2fa/recovery-codes",
+                web::post().to(regenerate_recovery_codes_handler),
+            )
+            .route("/me/oauth", web::get().to(list_oauth_bindings_handler))
+            .route(
+                "/me/oauth/{provider}",
+                web::delete().to(unlink_oauth_handler),
+            )
+            .route(
+                "/me/email-verification",
+                web::post().to(resend_email_verification_handler),
+            )
+            .route("/{uuid:.*}", web::get().to(get_users_handler)),
     );
 }
@@ -1,5 +1,6 @@
 use crate::{ApiResponse, HttpResult, config::AppError};
 
+use route_macros::route_permission;
 use serde::{Deserialize, Serialize};
 use std::fs;
 
@@ -21,6 +22,7 @@ struct VersionInfo {
     environment: String,
     language: String,
 }
+#[route_permission(path = "/api/v1/version", method = "get", public = true)]
 pub async fn get_version() -> HttpResult {
     // 读取文件内容
     let version_content = fs::read_to_string(".docker/version.json").map_err(|e| {
@@ -0,0 +1,805 @@
+//! 账号自助管理：个人资料、修改密码、注销，以及邮箱验证与找回密码。
+//!
+//! 链接中的令牌是签名的一次性操作令牌（见 `utils::jwt::ActionClaims`），
+//! `user_action_tokens` 记录其 jti，使用一次后即失效；令牌同时绑定签发时的邮箱，
+//! 邮箱变更后旧链接不再可用。
+
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::dto::account::{ChangePasswordRequest, UpdateProfileRequest};
+use crate::models::users::UserStatus;
+use crate::models::{user_action_tokens, users};
+use crate::services::refresh_token::RefreshTokenService;
+use crate::services::{EmailService, SmsVerificationManager, UserService};
+use crate::utils::crypto_pwd::{hash, validate_password, verify};
+use crate::utils::format_beijing;
+use crate::utils::jwt::{ActionClaims, TokenClaims, decode_action_token, encode_action_token};
+use crate::utils::rate_limit;
+use crate::utils::token_revocation::revoke_user_tokens;
+use chrono::{Duration as ChronoDuration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    Set,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 邮箱验证令牌用途
+const VERIFY_EMAIL: &str = "verify_email";
+/// 密码重置令牌用途
+const RESET_PASSWORD: &str = "reset_password";
+
+/// 账号邮件配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSettings {
+    /// 前端地址，邮件中的链接为 `{link_base_url}/verify-email?token=...` 与 `/reset-password?token=...`
+    pub link_base_url: String,
+    /// 邮箱验证链接有效期（秒）
+    pub email_verification_ttl: i64,
+    /// 密码重置链接有效期（秒）
+    pub password_reset_ttl: i64,
+}
+
+/// 账号服务
+pub struct AccountService;
+
+impl AccountService {
+    /// 向用户当前邮箱发送验证链接
+    pub async fn send_email_verification(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        user: &users::Model,
+    ) -> Result<(), AppError> {
+        let email = user
+            .email
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("尚未设置邮箱".to_string()))?;
+        if user.email_verified {
+            return Err(AppError::Conflict("邮箱已验证".to_string()));
+        }
+        let settings = &CONFIG.account;
+        let token = issue_token(
+            db,
+            user,
+            email,
+            VERIFY_EMAIL,
+            settings.email_verification_ttl,
+        )
+        .await?;
+        let link = format!(
+            "{}/verify-email?token={token}",
+            settings.link_base_url.trim_end_matches('/')
+        );
+        email_service
+            .send_email_verification(email, &link, settings.email_verification_ttl / 60)
+            .map_err(|e| AppError::EmailServiceError(format!("发送验证邮件失败: {e}")))
+    }
+
+    /// 当前登录用户重新发送验证链接
+    pub async fn resend_email_verification(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        claims: &TokenClaims,
+    ) -> Result<(), AppError> {
+        ensure_session(claims, "发送验证邮件")?;
+        let user = Self::profile(db, claims.user_id).await?;
+        Self::send_email_verification(db, email_service, &user).await
+    }
+
+    /// 通过链接中的令牌完成邮箱验证
+    pub async fn verify_email(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        token: &str,
+    ) -> Result<users::Model, AppError> {
+        let user = consume_token(db, token, VERIFY_EMAIL).await?;
+        let mut active = user.into_active_model();
+        active.email_verified = Set(true);
+        active.updated_at = Set(Utc::now());
+        let user = active.update(db).await?;
+
+        if let Some(email) = &user.email
+            && let Err(e) = email_service.send_welcome(email, &user.user_name).await
+        {
+            log::warn!("发送欢迎邮件失败: {e}");
+        }
+        Ok(user)
+    }
+
+    /// 找回密码：邮箱属于已验证的用户时发送重置链接。
+    ///
+    /// 无论邮箱是否存在都返回成功，避免被用来探测注册邮箱
+    pub async fn forgot_password(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        email: &str,
+    ) -> Result<(), AppError> {
+        let Some(user) = users::Entity::find()
+            .filter(users::Column::Email.eq(email))
+            .filter(users::Column::EmailVerified.eq(true))
+            .one(db)
+            .await?
+        else {
+            log::info!("password reset requested for unknown or unverified email");
+            return Ok(());
+        };
+
+        // 新链接签发后，之前未使用的重置链接全部作废
+        user_action_tokens::Entity::update_many()
+            .col_expr(
+                user_action_tokens::Column::UsedAt,
+                Expr::current_timestamp().into(),
+            )
+            .filter(user_action_tokens::Column::UserId.eq(user.id))
+            .filter(user_action_tokens::Column::Purpose.eq(RESET_PASSWORD))
+            .filter(user_action_tokens::Column::UsedAt.is_null())
+            .exec(db)
+            .await?;
+
+        let settings = &CONFIG.account;
+        let token = issue_token(
+            db,
+            &user,
+            email,
+            RESET_PASSWORD,
+            settings.password_reset_ttl,
+        )
+        .await?;
+        let link = format!(
+            "{}/reset-password?token={token}",
+            settings.link_base_url.trim_end_matches('/')
+        );
+        if let Err(e) =
+            email_service.send_password_reset(email, &link, settings.password_reset_ttl / 60)
+        {
+            log::error!("发送密码重置邮件失败: {e}");
+        }
+        Ok(())
+    }
+
+    /// 通过链接中的令牌重置密码，并让该用户的全部会话失效
+    pub async fn reset_password(
+        db: &DatabaseConnection,
+        token: &str,
+        new_password: &str,
+    ) -> Result<(), AppError> {
+        let user = consume_token(db, token, RESET_PASSWORD).await?;
+        validate_password(new_password, &user.user_name)?;
+        let user_id = user.id;
+        let user_name = user.user_name.clone();
+        let mut active = user.into_active_model();
+        active.pass_word = Set(hash(new_password)?);
+        active.updated_at = Set(Utc::now());
+        active.update(db).await?;
+
+        RefreshTokenService::revoke_user(db, user_id).await?;
+        revoke_user_tokens(user_id).await;
+        rate_limit::record_login_success(user_id);
+        log::warn!("password reset for user {user_name}");
+        Ok(())
+    }
+
+    /// 当前用户的资料
+    pub async fn profile(db: &DatabaseConnection, user_id: i32) -> Result<users::Model, AppError> {
+        users::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("用户不存在".to_string()))
+    }
+
+    /// 修改个人资料。
+    ///
+    /// 新邮箱需重新验证（发送验证链接），新手机号需提交发到该手机号的短信验证码
+    pub async fn update_profile(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        sms_manager: &SmsVerificationManager,
+        claims: &TokenClaims,
+        request: UpdateProfileRequest,
+    ) -> Result<users::Model, AppError> {
+        let user = Self::profile(db, claims.user_id).await?;
+        let email_changed = request
+            .email
+            .as_ref()
+            .is_some_and(|email| user.email.as_ref() != Some(email));
+        let phone_changed = request
+            .phone
+            .as_ref()
+            .is_some_and(|phone| user.phone.as_ref() != Some(phone));
+        if email_changed || phone_changed {
+            ensure_session(claims, "修改邮箱或手机号")?;
+        }
+
+        let mut active = user.clone().into_active_model();
+        if let Some(user_name) = request.user_name
+            && user_name != user.user_name
+        {
+            ensure_unique(db, users::Column::UserName, &user_name, "用户名已被使用").await?;
+            active.user_name = Set(user_name);
+        }
+        if let Some(image) = request.image {
+            // 空字符串表示清除头像
+            active.image = Set(Some(image).filter(|image| !image.is_empty()));
+        }
+        if email_changed && let Some(email) = request.email {
+            ensure_unique(db, users::Column::Email, &email, "邮箱已被使用").await?;
+            active.email = Set(Some(email));
+            active.email_verified = Set(false);
+        }
+        if phone_changed && let Some(phone) = request.phone {
+            let code = request
+                .phone_code
+                .ok_or_else(|| AppError::BadRequest("请提交新手机号的短信验证码".to_string()))?;
+            if !sms_manager.verify_code(&phone, &code).await {
+                return Err(AppError::BadRequest("验证码无效或已过期".to_string()));
+            }
+            ensure_unique(db, users::Column::Phone, &phone, "手机号已被使用").await?;
+            active.phone = Set(Some(phone));
+        }
+        active.updated_at = Set(Utc::now());
+        let user = active.update(db).await?;
+
+        if email_changed
+            && let Err(e) = Self::send_email_verification(db, email_service, &user).await
+        {
+            log::warn!("发送邮箱验证邮件失败: {e}");
+        }
+        Ok(user)
+    }
+
+    /// 凭当前密码修改密码，成功后该用户的全部会话失效
+    pub async fn change_password(
+        db: &DatabaseConnection,
+        claims: &TokenClaims,
+        request: &ChangePasswordRequest,
+    ) -> Result<(), AppError> {
+        ensure_session(claims, "修改密码")?;
+        let user = Self::profile(db, claims.user_id).await?;
+        ensure_password(&user, &request.current_password)?;
+        validate_password(&request.new_password, &user.user_name)?;
+        let user_id = user.id;
+        let mut active = user.into_active_model();
+        active.pass_word = Set(hash(&request.new_password)?);
+        active.updated_at = Set(Utc::now());
+        active.update(db).await?;
+
+        RefreshTokenService::revoke_user(db, user_id).await?;
+        revoke_user_tokens(user_id).await;
+        Ok(())
+    }
+
+    /// 注销账号：确认密码后软删除用户，登录会话随之失效
+    pub async fn delete_account(
+        db: &DatabaseConnection,
+        claims: &TokenClaims,
+        password: &str,
+    ) -> Result<(), AppError> {
+        ensure_session(claims, "注销账号")?;
+        let user = Self::profile(db, claims.user_id).await?;
+        ensure_password(&user, password)?;
+        let user = UserService::soft_delete(db, user).await?;
+        log::warn!("account {} deleted by owner", user.user_name);
+        Ok(())
+    }
+
+    /// 停用、封禁期内与已注销的账号不能登录、刷新令牌或使用个人访问令牌
+    pub fn ensure_active(user: &users::Model) -> Result<(), AppError> {
+        if !user.is_blocked(Utc::now()) {
+            return Ok(());
+        }
+        let reason = user
+            .status_reason
+            .as_deref()
+            .map(|reason| format!("，原因：{reason}"))
+            .unwrap_or_default();
+        Err(match (user.status, user.banned_until) {
+            (UserStatus::Deleted, _) => AppError::NotFound("用户不存在".to_string()),
+            (UserStatus::Banned, Some(until)) => {
+                AppError::Forbidden(format!("账号已被封禁至 {}{reason}", format_beijing(until)))
+            }
+            (UserStatus::Banned, None) => AppError::Forbidden(format!("账号已被永久封禁{reason}")),
+            _ => AppError::Forbidden(format!("账号已被停用{reason}")),
+        })
+    }
+
+    /// 成功的邮箱验证码登录同样证明了邮箱归属
+    pub async fn mark_email_verified(
+        db: &DatabaseConnection,
+        user: users::Model,
+    ) -> Result<users::Model, AppError> {
+        if user.email_verified {
+            return Ok(user);
+        }
+        let mut active = user.into_active_model();
+        active.email_verified = Set(true);
+        Ok(active.update(db).await?)
+    }
+}
+
+/// 修改账号安全设置只能在登录会话中进行，个人访问令牌无权操作
+fn ensure_session(claims: &TokenClaims, action: &str) -> Result<(), AppError> {
+    if claims.scopes.is_some() {
+        return Err(AppError::Forbidden(format!("个人访问令牌不能用于{action}")));
+    }
+    Ok(())
+}
+
+fn ensure_password(user: &users::Model, password: &str) -> Result<(), AppError> {
+    match verify(password, &user.pass_word) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(AppError::Unauthorized("密码错误".to_string())),
+        Err(e) => Err(AppError::InternalServerError(format!("校验密码失败: {e}"))),
+    }
+}
+
+/// 唯一列（用户名、邮箱、手机号）的值已被占用时返回 409
+pub(crate) async fn ensure_unique(
+    db: &DatabaseConnection,
+    column: users::Column,
+    value: &str,
+    message: &str,
+) -> Result<(), AppError> {
+    let taken = users::Entity::find()
+        .filter(column.eq(value))
+        .one(db)
+        .await?
+        .is_some();
+    if taken {
+        return Err(AppError::Conflict(message.to_string()));
+    }
+    Ok(())
+}
+
+/// 签发一次性操作令牌并记录 jti
+async fn issue_token(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    email: &str,
+    purpose: &str,
+    ttl: i64,
+) -> Result<String, AppError> {
+    let now = Utc::now();
+    let expires_at = now + ChronoDuration::seconds(ttl);
+    let claims = ActionClaims {
+        sub: user.id,
+        purpose: purpose.to_string(),
+        email: email.to_string(),
+        jti: Uuid::new_v4().to_string(),
+        iat: now.timestamp(),
+        exp: expires_at.timestamp(),
+    };
+    user_action_tokens::ActiveModel {
+        user_id: Set(user.id),
+        purpose: Set(purpose.to_string()),
+        jti: Set(claims.jti.clone()),
+        expires_at: Set(expires_at.into()),
+        used_at: Set(None),
+        created_at: Set(Some(now.into())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    encode_action_token(&claims)
+}
+
+/// 校验并作废一次性操作令牌，返回令牌对应的用户
+async fn consume_token(
+    db: &DatabaseConnection,
+    token: &str,
+    purpose: &str,
+) -> Result<users::Model, AppError> {
+    let invalid = || AppError::BadRequest("链接无效或已过期".to_string());
+    let claims = decode_action_token(token, purpose)?;
+    let now = Utc::now();
+    // 条件更新保证并发请求中只有一个能成功使用令牌
+    let consumed = user_action_tokens::Entity::update_many()
+        .col_expr(
+            user_action_tokens::Column::UsedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(user_action_tokens::Column::Jti.eq(&claims.jti))
+        .filter(user_action_tokens::Column::UserId.eq(claims.sub))
+        .filter(user_action_tokens::Column::Purpose.eq(purpose))
+        .filter(user_action_tokens::Column::UsedAt.is_null())
+        .filter(user_action_tokens::Column::ExpiresAt.gt(now))
+        .exec(db)
+        .await?;
+    if consumed.rows_affected != 1 {
+        return Err(invalid());
+    }
+    users::Entity::find_by_id(claims.sub)
+        .one(db)
+        .await?
+        .filter(|user| user.email.as_deref() == Some(claims.email.as_str()))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::refresh_tokens;
+    use crate::services::test_db::{create_user, create_user_with_id, setup_test_db};
+
+    fn user(status: UserStatus, banned_until: Option<chrono::DateTime<Utc>>) -> users::Model {
+        users::Model {
+            id: 1,
+            uuid: Uuid::new_v4().to_string(),
+            user_name: "tester".to_string(),
+            pass_word: String::new(),
+            email: None,
+            email_verified: false,
+            image: None,
+            phone: None,
+            binding: None,
+            status,
+            status_reason: Some("spam".to_string()),
+            banned_until,
+            deleted_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn status_blocks_login_until_ban_expires() {
+        let now = Utc::now();
+        assert!(AccountService::ensure_active(&user(UserStatus::Active, None)).is_ok());
+        assert!(matches!(
+            AccountService::ensure_active(&user(UserStatus::Disabled, None)),
+            Err(AppError::Forbidden(msg)) if msg.contains("spam")
+        ));
+        assert!(matches!(
+            AccountService::ensure_active(&user(UserStatus::Deleted, None)),
+            Err(AppError::NotFound(_))
+        ));
+        let banned = user(UserStatus::Banned, Some(now + ChronoDuration::hours(1)));
+        assert!(AccountService::ensure_active(&banned).is_err());
+        assert!(AccountService::ensure_active(&user(UserStatus::Banned, None)).is_err());
+        let expired = user(UserStatus::Banned, Some(now - ChronoDuration::seconds(1)));
+        assert!(AccountService::ensure_active(&expired).is_ok());
+    }
+
+    /// 创建邮箱已验证的用户
+    async fn verified_user(db: &DatabaseConnection, name: &str) -> users::Model {
+        let mut active = create_user(db, name).await.into_active_model();
+        active.email_verified = Set(true);
+        active.update(db).await.unwrap()
+    }
+
+    async fn token_for(db: &DatabaseConnection, user: &users::Model, purpose: &str) -> String {
+        issue_token(db, user, user.email.as_deref().unwrap(), purpose, 3600)
+            .await
+            .unwrap()
+    }
+
+    fn is_invalid_link(result: Result<impl std::fmt::Debug, AppError>) -> bool {
+        matches!(result, Err(AppError::BadRequest(msg)) if msg == "链接无效或已过期")
+    }
+
+    #[tokio::test]
+    async fn verify_email_token_is_single_use() {
+        let db = setup_test_db().await;
+        let email_service = EmailService::default();
+        let user = create_user(&db, "verifyonce").await;
+        let token = token_for(&db, &user, VERIFY_EMAIL).await;
+
+        let verified = AccountService::verify_email(&db, &email_service, &token)
+            .await
+            .unwrap();
+        assert!(verified.email_verified);
+        assert!(is_invalid_link(
+            AccountService::verify_email(&db, &email_service, &token).await
+        ));
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_rejected() {
+        let db = setup_test_db().await;
+        let email_service = EmailService::default();
+        let user = create_user(&db, "verifyexpired").await;
+        let token = issue_token(&db, &user, user.email.as_deref().unwrap(), VERIFY_EMAIL, -1)
+            .await
+            .unwrap();
+
+        assert!(is_invalid_link(
+            AccountService::verify_email(&db, &email_service, &token).await
+        ));
+        let user = AccountService::profile(&db, user.id).await.unwrap();
+        assert!(!user.email_verified);
+    }
+
+    #[tokio::test]
+    async fn token_purpose_must_match() {
+        let db = setup_test_db().await;
+        let email_service = EmailService::default();
+        let user = create_user(&db, "purposeuser").await;
+        let token = token_for(&db, &user, VERIFY_EMAIL).await;
+
+        // 邮箱验证令牌不能用于重置密码，且不会因此被消耗
+        assert!(is_invalid_link(
+            AccountService::reset_password(&db, &token, "N3w-passw0rd!").await
+        ));
+        assert!(
+            AccountService::verify_email(&db, &email_service, &token)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn changed_email_invalidates_link() {
+        let db = setup_test_db().await;
+        let email_service = EmailService::default();
+        let user = create_user(&db, "emailchanged").await;
+        let token = token_for(&db, &user, VERIFY_EMAIL).await;
+
+        let mut active = user.into_active_model();
+        active.email = Set(Some("another@example.com".to_string()));
+        let user = active.update(&db).await.unwrap();
+
+        assert!(is_invalid_link(
+            AccountService::verify_email(&db, &email_service, &token).await
+        ));
+        let user = AccountService::profile(&db, user.id).await.unwrap();
+        assert!(!user.email_verified);
+    }
+
+    #[tokio::test]
+    async fn reset_password_revokes_sessions() {
+        let db = setup_test_db().await;
+        let user = verified_user(&db, "resetuser").await;
+        RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+        let token = token_for(&db, &user, RESET_PASSWORD).await;
+
+        AccountService::reset_password(&db, &token, "N3w-passw0rd!")
+            .await
+            .unwrap();
+        let user = AccountService::profile(&db, user.id).await.unwrap();
+        assert!(verify("N3w-passw0rd!", &user.pass_word).unwrap());
+        let active_tokens = refresh_tokens::Entity::find()
+            .filter(refresh_tokens::Column::UserId.eq(user.id))
+            .filter(refresh_tokens::Column::RevokedAt.is_null())
+            .all(&db)
+            .await
+            .unwrap();
+        assert!(active_tokens.is_empty(), "重置密码后刷新令牌应全部作废");
+        assert!(is_invalid_link(
+            AccountService::reset_password(&db, &token, "An0ther-passw0rd!").await
+        ));
+    }
+
+    #[tokio::test]
+    async fn forgot_password_voids_earlier_reset_links() {
+        let db = setup_test_db().await;
+        let email_service = EmailService::default();
+        let user = verified_user(&db, "forgotuser").await;
+        let earlier = token_for(&db, &user, RESET_PASSWORD).await;
+
+        AccountService::forgot_password(&db, &email_service, user.email.as_deref().unwrap())
+            .await
+            .unwrap();
+        assert!(is_invalid_link(
+            AccountService::reset_password(&db, &earlier, "N3w-passw0rd!").await
+        ));
+        // 新签发了一个尚未使用的重置令牌
+        let pending = user_action_tokens::Entity::find()
+            .filter(user_action_tokens::Column::UserId.eq(user.id))
+            .filter(user_action_tokens::Column::Purpose.eq(RESET_PASSWORD))
+            .filter(user_action_tokens::Column::UsedAt.is_null())
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+
+        // 未知邮箱同样返回成功
+        assert!(
+            AccountService::forgot_password(&db, &email_service, "nobody@example.com")
+                .await
+                .is_ok()
+        );
+    }
+
+    fn session_claims(user: &users::Model) -> TokenClaims {
+        let now = Utc::now();
+        TokenClaims {
+            user_uuid: user.uuid.clone(),
+            user_name: user.user_name.clone(),
+            user_id: user.id,
+            jti: Uuid::new_v4().to_string(),
+            iat: now.timestamp(),
+            iat_ms: now.timestamp_millis(),
+            exp: now.timestamp() + 600,
+            role_ids: Vec::new(),
+            scopes: None,
+            sid: Some(Uuid::new_v4().to_string()),
+        }
+    }
+
+    async fn active_refresh_tokens(db: &DatabaseConnection, user_id: i32) -> usize {
+        refresh_tokens::Entity::find()
+            .filter(refresh_tokens::Column::UserId.eq(user_id))
+            .filter(refresh_tokens::Column::RevokedAt.is_null())
+            .all(db)
+            .await
+            .unwrap()
+            .len()
+    }
+
+    #[tokio::test]
+    async fn change_password_requires_current_password() {
+        let db = setup_test_db().await;
+        let user = create_user_with_id(&db, 8501, "changepwd").await;
+        RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+        let claims = session_claims(&user);
+
+        let err = AccountService::change_password(
+            &db,
+            &claims,
+            &ChangePasswordRequest {
+                current_password: "wrong-password".to_string(),
+                new_password: "N3w-passw0rd!".to_string(),
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)));
+        assert_eq!(active_refresh_tokens(&db, user.id).await, 1);
+
+        // 个人访问令牌不能修改密码
+        let mut pat = claims.clone();
+        pat.scopes = Some(vec!["user:profile".to_string()]);
+        let request = ChangePasswordRequest {
+            current_password: "password123".to_string(),
+            new_password: "N3w-passw0rd!".to_string(),
+        };
+        assert!(matches!(
+            AccountService::change_password(&db, &pat, &request).await,
+            Err(AppError::Forbidden(_))
+        ));
+
+        AccountService::change_password(&db, &claims, &request)
+            .await
+            .unwrap();
+        let user = AccountService::profile(&db, user.id).await.unwrap();
+        assert!(verify("N3w-passw0rd!", &user.pass_word).unwrap());
+        assert_eq!(active_refresh_tokens(&db, user.id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn phone_change_requires_valid_sms_code() {
+        let db = setup_test_db().await;
+        let email_service = EmailService::default();
+        let sms_manager = SmsVerificationManager::new(300);
+        let user = create_user(&db, "phoneuser").await;
+        let claims = session_claims(&user);
+        let request = |code: Option<&str>| UpdateProfileRequest {
+            phone: Some("13900139000".to_string()),
+            phone_code: code.map(String::from),
+            ..Default::default()
+        };
+
+        let err = AccountService::update_profile(
+            &db,
+            &email_service,
+            &sms_manager,
+            &claims,
+            request(None),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+
+        sms_manager.insert_code("13900139000", "123456").await;
+        let err = AccountService::update_profile(
+            &db,
+            &email_service,
+            &sms_manager,
+            &claims,
+            request(Some("654321")),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+        assert_eq!(
+            AccountService::profile(&db, user.id).await.unwrap().phone,
+            None
+        );
+
+        let user = AccountService::update_profile(
+            &db,
+            &email_service,
+            &sms_manager,
+            &claims,
+            request(Some("123456")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.phone.as_deref(), Some("13900139000"));
+    }
+
+    #[tokio::test]
+    async fn profile_rejects_taken_email_and_user_name() {
+        let db = setup_test_db().await;
+        let email_service = EmailService::default();
+        let sms_manager = SmsVerificationManager::new(300);
+        let user = verified_user(&db, "profileuser").await;
+        let other = create_user(&db, "otheruser").await;
+        let claims = session_claims(&user);
+
+        let err = AccountService::update_profile(
+            &db,
+            &email_service,
+            &sms_manager,
+            &claims,
+            UpdateProfileRequest {
+                email: other.email.clone(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        let err = AccountService::update_profile(
+            &db,
+            &email_service,
+            &sms_manager,
+            &claims,
+            UpdateProfileRequest {
+                user_name: Some(other.user_name.clone()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        // 新邮箱需要重新验证
+        let user = AccountService::update_profile(
+            &db,
+            &email_service,
+            &sms_manager,
+            &claims,
+            UpdateProfileRequest {
+                email: Some("new@example.com".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.email.as_deref(), Some("new@example.com"));
+        assert!(!user.email_verified);
+    }
+
+    #[tokio::test]
+    async fn delete_account_requires_password() {
+        let db = setup_test_db().await;
+        let user = create_user_with_id(&db, 8502, "deleteme").await;
+        RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+        let claims = session_claims(&user);
+
+        assert!(matches!(
+            AccountService::delete_account(&db, &claims, "wrong-password").await,
+            Err(AppError::Unauthorized(_))
+        ));
+        assert_eq!(
+            AccountService::profile(&db, user.id).await.unwrap().status,
+            UserStatus::Active
+        );
+
+        AccountService::delete_account(&db, &claims, "password123")
+            .await
+            .unwrap();
+        let user = AccountService::profile(&db, user.id).await.unwrap();
+        assert_eq!(user.status, UserStatus::Deleted);
+        assert_eq!(user.email, None);
+        assert_eq!(active_refresh_tokens(&db, user.id).await, 0);
+    }
+}
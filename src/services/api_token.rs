@@ -0,0 +1,354 @@
+use crate::config::AppError;
+use crate::dto::api_token::{ApiTokenResponse, CreateApiTokenRequest, CreatedApiTokenResponse};
+use crate::models::{api_tokens, users};
+use crate::services::AccountService;
+use crate::utils::crypto_pwd::{hash, verify};
+use crate::utils::jwt::TokenClaims;
+use crate::utils::perm_cache::effective_permissions;
+use actix_web::web;
+use base64::engine::{Engine as _, general_purpose};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use rand::{Rng, RngCore, distributions::Alphanumeric};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, Set,
+};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{LazyLock, Mutex};
+
+/// 个人访问令牌的固定前缀，`Authorization: Bearer pat_...` 据此与 JWT 区分
+pub const API_TOKEN_PREFIX: &str = "pat_";
+/// 令牌中公开前缀的长度
+const PUBLIC_PREFIX_LEN: usize = 8;
+/// 两次记录最后使用时间的最小间隔（秒），避免每个请求都写库
+const LAST_USED_INTERVAL: i64 = 60;
+/// 校验通过的令牌缓存时长（秒），期间同一令牌只比对 SHA-256，不再重复 argon2 校验
+const VERIFIED_TTL: i64 = 300;
+
+/// 最近校验通过的令牌
+struct VerifiedToken {
+    /// 令牌的 SHA-256
+    digest: String,
+    /// 缓存过期时间
+    until: DateTime<Utc>,
+}
+
+/// 公开前缀 → 最近校验通过的令牌。只缓存哈希校验结果，
+/// 撤销、过期与用户状态仍然每次按数据库记录检查
+static VERIFIED: LazyLock<Mutex<HashMap<String, VerifiedToken>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 个人访问令牌：`pat_<公开前缀>_<随机密钥>`。
+///
+/// 公开前缀用于查找记录，整个令牌以 argon2 哈希保存；
+/// 使用令牌时的权限为令牌范围与用户当前权限的交集。
+pub struct ApiTokenService;
+
+impl ApiTokenService {
+    /// 创建令牌，明文只在这里返回一次
+    pub async fn create(
+        db: &DatabaseConnection,
+        user: &TokenClaims,
+        req: CreateApiTokenRequest,
+    ) -> Result<CreatedApiTokenResponse, AppError> {
+        ensure_session(user)?;
+        let scopes: BTreeSet<String> = req.scopes.into_iter().collect();
+        let granted = effective_permissions(user.user_id, &user.role_ids).await;
+        let missing: Vec<&str> = scopes
+            .iter()
+            .filter(|scope| !granted.contains(*scope))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::Forbidden(format!(
+                "没有以下权限，不能授予令牌: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let (token, token_prefix) = generate_token();
+        let now = Utc::now();
+        let record = api_tokens::ActiveModel {
+            user_id: Set(user.user_id),
+            name: Set(req.name),
+            token_prefix: Set(token_prefix),
+            token_hash: Set(hash(&token)?),
+            scopes: Set(scopes.into_iter().collect::<Vec<_>>().join(" ")),
+            expires_at: Set((now + ChronoDuration::days(req.expires_in_days)).into()),
+            last_used_at: Set(None),
+            revoked_at: Set(None),
+            created_at: Set(Some(now.into())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(|e| {
+            log::error!("保存个人访问令牌失败: {e}");
+            AppError::DatabaseError("创建令牌失败".to_string())
+        })?;
+
+        Ok(CreatedApiTokenResponse {
+            token,
+            info: to_response(record),
+        })
+    }
+
+    /// 列出用户的全部令牌（含已过期和已撤销的）
+    pub async fn list(
+        db: &DatabaseConnection,
+        user: &TokenClaims,
+    ) -> Result<Vec<ApiTokenResponse>, AppError> {
+        ensure_session(user)?;
+        let tokens = api_tokens::Entity::find()
+            .filter(api_tokens::Column::UserId.eq(user.user_id))
+            .order_by_desc(api_tokens::Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(tokens.into_iter().map(to_response).collect())
+    }
+
+    /// 撤销自己的令牌
+    pub async fn revoke(
+        db: &DatabaseConnection,
+        user: &TokenClaims,
+        token_id: i32,
+    ) -> Result<(), AppError> {
+        ensure_session(user)?;
+        let record = api_tokens::Entity::find_by_id(token_id)
+            .filter(api_tokens::Column::UserId.eq(user.user_id))
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("令牌不存在".to_string()))?;
+        if record.revoked_at.is_none() {
+            let mut record = record.into_active_model();
+            record.revoked_at = Set(Some(Utc::now().into()));
+            record.update(db).await?;
+        }
+        Ok(())
+    }
+
+    /// 校验令牌并构造请求上下文中的 `TokenClaims`，`scopes` 为令牌的授权范围
+    pub async fn authenticate(
+        db: &DatabaseConnection,
+        token: &str,
+    ) -> Result<TokenClaims, AppError> {
+        let invalid = || AppError::Unauthorized("个人访问令牌无效".to_string());
+        let token_prefix = public_prefix(token).ok_or_else(invalid)?;
+        let (record, user) = api_tokens::Entity::find()
+            .filter(api_tokens::Column::TokenPrefix.eq(token_prefix))
+            .find_also_related(users::Entity)
+            .one(db)
+            .await?
+            .ok_or_else(invalid)?;
+        let user = user.ok_or_else(invalid)?;
+        let now = Utc::now();
+        if !verify_cached(token, token_prefix, &record.token_hash, now).await? {
+            return Err(invalid());
+        }
+        AccountService::ensure_active(&user)?;
+        if record.revoked_at.is_some() {
+            return Err(AppError::Unauthorized("个人访问令牌已被撤销".to_string()));
+        }
+        if record.expires_at < now {
+            return Err(AppError::Unauthorized("个人访问令牌已过期".to_string()));
+        }
+
+        let claims = TokenClaims {
+            user_uuid: user.uuid.clone(),
+            user_name: user.user_name.clone(),
+            user_id: user.id,
+            jti: format!("pat:{}", record.id),
+            iat: record.created_at.map_or(0, |t| t.timestamp()),
+            iat_ms: record.created_at.map_or(0, |t| t.timestamp_millis()),
+            exp: record.expires_at.timestamp(),
+            role_ids: TokenClaims::get_role_ids(db, &user).await?,
+            scopes: Some(record.scopes.split_whitespace().map(String::from).collect()),
+            sid: None,
+        };
+
+        let stale = record
+            .last_used_at
+            .is_none_or(|t| (now - t.to_utc()).num_seconds() >= LAST_USED_INTERVAL);
+        if stale {
+            let mut record = record.into_active_model();
+            record.last_used_at = Set(Some(now.into()));
+            if let Err(e) = record.update(db).await {
+                log::warn!("更新令牌最后使用时间失败: {e}");
+            }
+        }
+        Ok(claims)
+    }
+}
+
+/// 校验令牌与库中的 argon2 哈希。最近校验通过的令牌直接比对 SHA-256；
+/// argon2 校验耗时数十毫秒，放到阻塞线程池中执行，避免占住 actix 工作线程
+async fn verify_cached(
+    token: &str,
+    token_prefix: &str,
+    token_hash: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, AppError> {
+    let digest = hex::encode(Sha256::digest(token.as_bytes()));
+    let cached = VERIFIED
+        .lock()
+        .unwrap()
+        .get(token_prefix)
+        .is_some_and(|cached| cached.digest == digest && cached.until > now);
+    if cached {
+        return Ok(true);
+    }
+
+    let (token, token_hash) = (token.to_string(), token_hash.to_string());
+    let valid = web::block(move || verify(&token, &token_hash))
+        .await
+        .map_err(|e| {
+            log::error!("校验个人访问令牌失败: {e}");
+            AppError::InternalServerError("校验令牌失败".to_string())
+        })??;
+    if valid {
+        let mut verified = VERIFIED.lock().unwrap();
+        verified.retain(|_, cached| cached.until > now);
+        verified.insert(
+            token_prefix.to_string(),
+            VerifiedToken {
+                digest,
+                until: now + ChronoDuration::seconds(VERIFIED_TTL),
+            },
+        );
+    }
+    Ok(valid)
+}
+
+/// 令牌管理只能在登录会话中进行，防止泄露的令牌自我续期
+fn ensure_session(user: &TokenClaims) -> Result<(), AppError> {
+    if user.scopes.is_some() {
+        return Err(AppError::Forbidden(
+            "个人访问令牌不能用于管理令牌".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 生成令牌明文，返回 (令牌, 公开前缀)
+fn generate_token() -> (String, String) {
+    let token_prefix: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(PUBLIC_PREFIX_LEN)
+        .map(char::from)
+        .collect();
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let token = format!(
+        "{API_TOKEN_PREFIX}{token_prefix}_{}",
+        general_purpose::URL_SAFE_NO_PAD.encode(secret)
+    );
+    (token, token_prefix)
+}
+
+/// 取出令牌中的公开前缀
+fn public_prefix(token: &str) -> Option<&str> {
+    let (prefix, secret) = token.strip_prefix(API_TOKEN_PREFIX)?.split_once('_')?;
+    (prefix.len() == PUBLIC_PREFIX_LEN && !secret.is_empty()).then_some(prefix)
+}
+
+fn to_response(record: api_tokens::Model) -> ApiTokenResponse {
+    ApiTokenResponse {
+        id: record.id,
+        name: record.name,
+        token_prefix: record.token_prefix,
+        scopes: record.scopes.split_whitespace().map(String::from).collect(),
+        expires_at: record.expires_at.to_utc(),
+        last_used_at: record.last_used_at.map(|t| t.to_utc()),
+        revoked_at: record.revoked_at.map(|t| t.to_utc()),
+        created_at: record.created_at.map(|t| t.to_utc()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_db::{create_user_with_id, setup_test_db};
+
+    #[test]
+    fn generated_token_has_lookup_prefix() {
+        let (token, prefix) = generate_token();
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(public_prefix(&token), Some(prefix.as_str()));
+        assert_eq!(public_prefix("pat_short_secret"), None);
+        assert_eq!(public_prefix("eyJhbGciOiJIUzI1NiJ9.e30.sig"), None);
+    }
+
+    #[test]
+    fn scopes_limit_session_permissions() {
+        let mut claims = TokenClaims {
+            user_uuid: String::new(),
+            user_name: String::new(),
+            user_id: 1,
+            jti: String::new(),
+            iat: 0,
+            iat_ms: 0,
+            exp: 0,
+            role_ids: vec![],
+            scopes: None,
+            sid: None,
+        };
+        assert!(claims.scope_allows("post:create"));
+        claims.scopes = Some(vec!["post:create".to_string()]);
+        assert!(claims.scope_allows("post:create"));
+        assert!(!claims.scope_allows("post:delete"));
+        assert!(ensure_session(&claims).is_err());
+    }
+
+    #[actix_web::test]
+    async fn authenticate_caches_verified_tokens() {
+        let db = setup_test_db().await;
+        let user = create_user_with_id(&db, 8902, "patuser").await;
+        let session = TokenClaims {
+            user_uuid: user.uuid.clone(),
+            user_name: user.user_name.clone(),
+            user_id: user.id,
+            jti: String::new(),
+            iat: 0,
+            iat_ms: 0,
+            exp: 0,
+            role_ids: vec![],
+            scopes: None,
+            sid: Some("session".to_string()),
+        };
+        let created = ApiTokenService::create(
+            &db,
+            &session,
+            CreateApiTokenRequest {
+                name: "ci".to_string(),
+                scopes: vec![],
+                expires_in_days: 30,
+            },
+        )
+        .await
+        .unwrap();
+        let token = created.token;
+        let prefix = public_prefix(&token).unwrap().to_string();
+
+        let claims = ApiTokenService::authenticate(&db, &token).await.unwrap();
+        assert_eq!(claims.user_id, user.id);
+        assert!(VERIFIED.lock().unwrap().contains_key(&prefix));
+        ApiTokenService::authenticate(&db, &token).await.unwrap();
+
+        // 前缀相同但密钥不同的令牌不能借用缓存
+        let forged = format!("{API_TOKEN_PREFIX}{prefix}_forged");
+        let err = ApiTokenService::authenticate(&db, &forged)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{err:?}");
+
+        // 缓存不影响撤销
+        ApiTokenService::revoke(&db, &session, created.info.id)
+            .await
+            .unwrap();
+        let err = ApiTokenService::authenticate(&db, &token)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{err:?}");
+    }
+}
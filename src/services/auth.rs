@@ -1,13 +1,24 @@
 use crate::config::AppError;
+use crate::dto::two_factor::{TwoFactorChallengeResponse, TwoFactorLoginRequest};
 use crate::dto::user::{EmailLogin, OAuthLogin, PasswordLogin, PhoneLogin, ValidationErrorJson};
 use crate::models::users::ActiveModel;
-use crate::models::{roles, user_roles};
-use crate::utils::crypto_pwd::{hash, verify};
-use crate::utils::jwt::generate_jwt;
+use crate::models::{roles, user_roles, users};
+use crate::services::account::ensure_unique;
+use crate::services::refresh_token::RefreshTokenService;
+use crate::services::{
+    AccountService, EmailService, LoginContext, LoginHistoryService, OAuthService, SessionService,
+    SmsVerificationManager, TwoFactorChallengeManager, TwoFactorService,
+};
+use crate::utils::auth_cookie::{
+    REFRESH_TOKEN_COOKIE, access_token_cookie, clear_auth_cookies, refresh_token_cookie,
+};
+use crate::utils::crypto_pwd::{hash, needs_rehash, validate_password, verify};
+use crate::utils::jwt::{TokenClaims, generate_jwt};
+use crate::utils::rate_limit;
+use crate::utils::token_revocation::{revoke_jti, revoke_user_tokens};
 use crate::{ApiResponse, EmailVerificationManager, SseNotifier};
 use crate::{HttpResult, RegisterResponse};
-use actix_web::cookie::{Cookie, SameSite, time::Duration as ActixDuration};
-use actix_web::{HttpResponse, web};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, web};
 use chrono::Utc;
 use sea_orm::ActiveValue::Set;
 use sea_orm::{
@@ -23,6 +34,7 @@ impl AuthService {
         db_pool: web::Data<DatabaseConnection>,
         user_data: web::Json<RegisterResponse>,
         notifier: web::Data<SseNotifier>,
+        email_service: web::Data<EmailService>,
     ) -> Result<crate::models::users::Model, AppError> {
         // 校验
         if let Err(errors) = user_data.validate() {
@@ -32,7 +44,12 @@ impl AuthService {
         let RegisterResponse {
             user_name,
             pass_word,
+            email,
         } = user_data.into_inner();
+        if let Some(email) = &email {
+            ensure_unique(&db_pool, users::Column::Email, email, "邮箱已被使用").await?;
+        }
+        validate_password(&pass_word, &user_name)?;
         let password_hash = hash(&pass_word)?;
         // —— 1. 开事务 ——
         let txn = db_pool
@@ -44,6 +61,7 @@ impl AuthService {
         let user_am = ActiveModel {
             user_name: Set(user_name.clone()),
             pass_word: Set(password_hash),
+            email: Set(email),
             uuid: Set(Uuid::new_v4().to_string()),
             created_at: Set(Utc::now()),
             updated_at: Set(Utc::now()),
@@ -56,7 +74,7 @@ impl AuthService {
 
         // —— 3. 查 VIEWER 角色 id ——
         let viewer_role = roles::Entity::find()
-            .filter(roles::Column::Code.eq("SUPER_ADMIN"))
+            .filter(roles::Column::Code.eq("VIEWER"))
             .one(&txn)
             .await
             .map_err(|_| AppError::DatabaseError("VIEWER role not found".into()))?
@@ -88,12 +106,22 @@ impl AuthService {
             }
         });
         notifier.notify(&notification.to_string());
+
+        // 验证邮件发送失败不影响注册，用户可稍后重新发送
+        if user.email.is_some()
+            && let Err(e) =
+                AccountService::send_email_verification(&db_pool, &email_service, &user).await
+        {
+            log::warn!("发送邮箱验证邮件失败: {e}");
+        }
         Ok(user)
     }
 
     pub async fn login_by_pwd(
         db_pool: web::Data<DatabaseConnection>,
         login: PasswordLogin,
+        challenges: web::Data<TwoFactorChallengeManager>,
+        ctx: &LoginContext,
     ) -> HttpResult {
         // 查询用户是否存在
         let user = match crate::models::users::Entity::find_by_name(&login.account)
@@ -107,29 +135,19 @@ impl AuthService {
             return Err(AppError::NotFound("用户不存在".to_string()));
         }
         let user = user.unwrap();
+        // 连续密码错误的账号在锁定期内直接拒绝
+        rate_limit::ensure_login_allowed(user.id)?;
         match verify(&login.password, user.pass_word.as_str()) {
             Ok(true) => {
+                rate_limit::record_login_success(user.id);
+                AccountService::ensure_active(&user)?;
+                let user = Self::rehash_if_needed(&db_pool, user, &login.password).await;
                 // 登录成功
-                let token = generate_jwt(&db_pool, &user).await?;
-                log::info!("login_by_pwd token: {token:?}");
-                // 2. 构造 Cookie
-                let cookie = Cookie::build("access_token", token)
-                    .http_only(true) // 防 XSS
-                    .same_site(SameSite::Strict) // 防 CSRF
-                    .secure(true) // 生产必须 true，本地可 false
-                    .max_age(ActixDuration::hours(1))
-                    .path("/") // 全局可用
-                    .finish();
-                Ok(HttpResponse::Ok()
-                    .cookie(cookie) // ← 关键：把 Cookie 塞进响应
-                    .json(json!({
-                        "code": 200,
-                        "message": "密码登录成功",
-                        "data":user
-                    })))
+                Self::login_or_challenge(&db_pool, user, "密码登录成功", &challenges, ctx).await
             }
             Ok(false) => {
                 // 登录失败
+                rate_limit::record_login_failure(user.id, &user.user_name);
                 Err(AppError::Unauthorized("密码错误".to_string()))
             }
             Err(e) => {
@@ -144,6 +162,8 @@ impl AuthService {
         email: EmailLogin,
         // email_service: web::Data<EmailService>,
         email_verification_manager: web::Data<EmailVerificationManager>,
+        challenges: web::Data<TwoFactorChallengeManager>,
+        ctx: &LoginContext,
     ) -> HttpResult {
         log::info!("login_by_email{email:?}");
 
@@ -170,44 +190,196 @@ impl AuthService {
         if user.is_none() {
             return Err(AppError::NotFound("用户不存在".to_string()));
         }
-        let user = user.unwrap();
+        // 能收到验证码即证明了邮箱归属
+        let user = AccountService::mark_email_verified(&db_pool, user.unwrap()).await?;
 
-        // 生成JWT令牌
-        let token = generate_jwt(&db_pool, &user).await?;
+        Self::login_or_challenge(&db_pool, user, "邮箱登录成功", &challenges, ctx).await
+    }
+    /// 两步验证登录第二步：挑战令牌 + 验证码（或恢复码）
+    pub async fn login_by_two_factor(
+        db_pool: &DatabaseConnection,
+        request: TwoFactorLoginRequest,
+        challenges: &TwoFactorChallengeManager,
+        ctx: &LoginContext,
+    ) -> HttpResult {
+        let user = challenges
+            .complete(db_pool, &request.challenge_token, &request.code)
+            .await?;
+        Self::login_response(db_pool, user, "两步验证登录成功", ctx).await
+    }
 
-        log::info!("login_by_email token: {token:?}");
+    /// 第一因素验证通过后的统一出口：已启用两步验证时只签发挑战令牌，
+    /// 凭验证码换取登录会话；否则直接登录。除两步验证外的登录方式都必须经过这里
+    pub(crate) async fn login_or_challenge(
+        db_pool: &DatabaseConnection,
+        user: users::Model,
+        message: &str,
+        challenges: &TwoFactorChallengeManager,
+        ctx: &LoginContext,
+    ) -> HttpResult {
+        AccountService::ensure_active(&user)?;
+        if TwoFactorService::is_enabled(db_pool, user.id).await? {
+            let challenge = TwoFactorChallengeResponse {
+                two_factor_required: true,
+                challenge_token: challenges.issue(user.id).await,
+                expires_in: challenges.ttl(),
+            };
+            return Ok(ApiResponse::success(challenge, "请输入两步验证码").to_http_response());
+        }
+        Self::login_response(db_pool, user, message, ctx).await
+    }
 
-        // 构造Cookie
-        let cookie = Cookie::build("access_token", token)
-            .http_only(true) // 防XSS
-            .same_site(SameSite::Strict) // 防CSRF
-            .secure(true) // 生产必须true，本地可false
-            .max_age(ActixDuration::hours(1))
-            .path("/") // 全局可用
-            .finish();
+    /// 登录成功：签发访问令牌与新的刷新令牌家族，并写入 Cookie
+    async fn login_response(
+        db_pool: &DatabaseConnection,
+        user: users::Model,
+        message: &str,
+        ctx: &LoginContext,
+    ) -> HttpResult {
+        AccountService::ensure_active(&user)?;
+        let session_id = SessionService::start(db_pool, user.id, ctx).await?;
+        let access_token = generate_jwt(db_pool, &user, &session_id).await?;
+        let refresh_token =
+            RefreshTokenService::issue(db_pool, user.id, Some(session_id.clone())).await?;
+        if let Err(e) = LoginHistoryService::record_success(db_pool, &user, ctx, &session_id).await
+        {
+            log::warn!("保存登录记录失败: {e}");
+        }
+        Ok(HttpResponse::Ok()
+            .cookie(access_token_cookie(access_token))
+            .cookie(refresh_token_cookie(refresh_token))
+            .json(json!({
+                "code": 200,
+                "message": message,
+                "data": user
+            })))
+    }
 
+    /// 库中的密码哈希参数弱于当前配置时，借本次登录的明文按新参数重新哈希；失败只记日志，不影响登录
+    async fn rehash_if_needed(
+        db_pool: &DatabaseConnection,
+        user: users::Model,
+        password: &str,
+    ) -> users::Model {
+        if !needs_rehash(&user.pass_word) {
+            return user;
+        }
+        let password_hash = match hash(password) {
+            Ok(password_hash) => password_hash,
+            Err(e) => {
+                log::warn!("重新哈希密码失败: {e}");
+                return user;
+            }
+        };
+        let mut active: ActiveModel = user.clone().into();
+        active.pass_word = Set(password_hash);
+        match active.update(db_pool).await {
+            Ok(user) => user,
+            Err(e) => {
+                log::warn!("保存重新哈希的密码失败: {e}");
+                user
+            }
+        }
+    }
+
+    /// 使用刷新令牌换取新的访问令牌，刷新令牌同时轮换
+    pub async fn refresh(db_pool: &DatabaseConnection, req: &HttpRequest) -> HttpResult {
+        let token = req
+            .cookie(REFRESH_TOKEN_COOKIE)
+            .ok_or_else(|| AppError::Unauthorized("refresh_token not found".to_string()))?;
+        let (user, refresh_token, session_id) =
+            RefreshTokenService::rotate(db_pool, token.value()).await?;
+        SessionService::touch(db_pool, &session_id).await?;
+        let access_token = generate_jwt(db_pool, &user, &session_id).await?;
         Ok(HttpResponse::Ok()
-            .cookie(cookie) // 把Cookie塞进响应
+            .cookie(access_token_cookie(access_token))
+            .cookie(refresh_token_cookie(refresh_token))
             .json(json!({
                 "code": 200,
-                "message": "邮箱登录成功",
+                "message": "令牌刷新成功",
                 "data": user
             })))
     }
-    pub fn login_by_phone(
-        _db_pool: web::Data<DatabaseConnection>,
+
+    /// 退出登录：作废刷新令牌家族、拉黑当前访问令牌并清除 Cookie
+    pub async fn logout(db_pool: &DatabaseConnection, req: &HttpRequest) -> HttpResult {
+        if let Some(token) = req.cookie(REFRESH_TOKEN_COOKIE) {
+            RefreshTokenService::revoke_by_token(db_pool, token.value()).await?;
+        }
+        // 登出接口为可选登录，令牌有效时中间件已放入载荷
+        let claims = req.extensions().get::<TokenClaims>().cloned();
+        if let Some(claims) = claims {
+            revoke_jti(&claims.jti, claims.exp).await;
+        }
+        let mut response = HttpResponse::Ok();
+        for cookie in clear_auth_cookies() {
+            response.cookie(cookie);
+        }
+        Ok(response.json(json!({
+            "code": 200,
+            "message": "已退出登录",
+            "data": null
+        })))
+    }
+
+    /// 强制用户下线：作废全部刷新令牌，并使已签发的访问令牌立即失效
+    pub async fn revoke_sessions(db_pool: &DatabaseConnection, user_uuid: &str) -> HttpResult {
+        let user = users::Entity::find()
+            .filter(users::Column::Uuid.eq(user_uuid))
+            .one(db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("UUID为{user_uuid}的用户不存在")))?;
+        let revoked = RefreshTokenService::revoke_user(db_pool, user.id).await?;
+        revoke_user_tokens(user.id).await;
+        log::warn!("sessions revoked for user {}", user.user_name);
+        Ok(ApiResponse::success(json!({ "revoked": revoked }), "已强制下线").to_http_response())
+    }
+
+    /// 手机号 + 短信验证码登录
+    pub async fn login_by_phone(
+        db_pool: web::Data<DatabaseConnection>,
         phone: PhoneLogin,
+        sms_verification_manager: web::Data<SmsVerificationManager>,
+        challenges: web::Data<TwoFactorChallengeManager>,
+        ctx: &LoginContext,
     ) -> HttpResult {
-        log::info!("login_by_phone{phone:?}");
+        log::info!("login_by_phone {}", phone.phone);
+
+        if !sms_verification_manager
+            .verify_code(&phone.phone, &phone.code)
+            .await
+        {
+            return Err(AppError::BadRequest("验证码无效或已过期".to_string()));
+        }
+
+        let user = users::Entity::find()
+            .filter(users::Column::Phone.eq(&phone.phone))
+            .one(db_pool.as_ref())
+            .await?
+            .ok_or_else(|| AppError::NotFound("该手机号未绑定用户".to_string()))?;
 
-        Ok(ApiResponse::success("user", "手机号").to_http_response())
+        Self::login_or_challenge(&db_pool, user, "手机号登录成功", &challenges, ctx).await
     }
-    pub fn login_by_oauth(
-        _db_pool: web::Data<DatabaseConnection>,
+
+    /// 客户端已取得服务商访问令牌时（如移动端 SDK）直接登录，`openid` 必须与令牌对应的用户一致
+    pub async fn login_by_oauth(
+        db_pool: web::Data<DatabaseConnection>,
         oauth: OAuthLogin,
+        oauth_service: web::Data<OAuthService>,
+        challenges: web::Data<TwoFactorChallengeManager>,
+        ctx: &LoginContext,
     ) -> HttpResult {
-        log::info!("login_by_oauth{:?}", oauth);
+        log::info!("login_by_oauth {} {}", oauth.provider, oauth.openid);
 
-        Ok(ApiResponse::success("user", "第三方").to_http_response())
+        let identity = oauth_service
+            .fetch_identity(&oauth.provider, &oauth.access_token)
+            .await?;
+        if identity.subject != oauth.openid {
+            return Err(AppError::Unauthorized(
+                "第三方访问令牌与 openid 不匹配".to_string(),
+            ));
+        }
+        let user = OAuthService::login_user(&db_pool, &oauth.provider, &identity).await?;
+        Self::login_or_challenge(&db_pool, user, "第三方登录成功", &challenges, ctx).await
     }
 }
@@ -1,16 +1,36 @@
 #[cfg(test)]
 mod tests {
     use crate::SseNotifier;
-    use crate::dto::user::RegisterResponse;
-    use crate::models::{roles, user_roles, users};
+    use crate::dto::user::{EmailLogin, OAuthLogin, PasswordLogin, PhoneLogin, RegisterResponse};
+    use crate::init_route_registry;
+    use crate::middleware::auth::Auth;
+    use crate::models::{roles, user_oauth_bindings, user_roles, user_two_factor, users};
     use crate::services::auth::AuthService;
-    use crate::utils::crypto_pwd::verify;
-    use actix_web::web;
+    use crate::services::oauth::{OAuthProviderSettings, OAuthSettings};
+    use crate::services::{
+        EmailService, EmailVerificationManager, LoginContext, OAuthService, SmsVerificationManager,
+        TwoFactorChallengeManager,
+    };
+    use crate::utils::crypto_pwd::{hash, verify};
+    use crate::utils::jwt::generate_jwt;
+    use crate::utils::perm_cache::{CACHE_TEST_LOCK, ROLE_PERMS};
+    use actix_web::body::to_bytes;
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::SET_COOKIE;
+    use actix_web::{App, HttpResponse, test, web};
     use chrono::Utc;
     use sea_orm::{
         ActiveModelTrait, ColumnTrait, ConnectionTrait, Database, DatabaseConnection, EntityTrait,
         PaginatorTrait, QueryFilter, QueryOrder, Set, Statement,
     };
+    use serde_json::{Value, json};
+    use std::collections::HashSet;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn email_service() -> web::Data<EmailService> {
+        web::Data::new(EmailService::default())
+    }
 
     // 测试数据库连接和初始化
     async fn setup_test_db() -> DatabaseConnection {
@@ -40,9 +60,14 @@ mod tests {
                 user_name TEXT NOT NULL UNIQUE,
                 pass_word TEXT NOT NULL,
                 email TEXT UNIQUE,
+                email_verified BOOLEAN NOT NULL DEFAULT FALSE,
                 image TEXT,
                 phone TEXT UNIQUE,
                 binding TEXT,
+                status TEXT NOT NULL DEFAULT 'active',
+                status_reason TEXT,
+                banned_until TEXT,
+                deleted_at TEXT,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             )
@@ -91,6 +116,48 @@ mod tests {
         db.execute(create_user_roles_table)
             .await
             .expect("Failed to create user_roles table");
+
+        // 创建两步验证表
+        let create_user_two_factor_table = Statement::from_string(
+            db.get_database_backend(),
+            r#"
+            CREATE TABLE user_two_factor (
+                user_id INTEGER PRIMARY KEY,
+                secret TEXT NOT NULL,
+                enabled_at TEXT,
+                last_used_step INTEGER,
+                created_at TEXT,
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            )
+            "#
+            .to_string(),
+        );
+        db.execute(create_user_two_factor_table)
+            .await
+            .expect("Failed to create user_two_factor table");
+
+        // 创建第三方账号绑定表
+        let create_oauth_bindings_table = Statement::from_string(
+            db.get_database_backend(),
+            r#"
+            CREATE TABLE user_oauth_bindings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                subject TEXT NOT NULL,
+                email TEXT,
+                display_name TEXT,
+                avatar TEXT,
+                created_at TEXT,
+                UNIQUE (provider, subject),
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            )
+            "#
+            .to_string(),
+        );
+        db.execute(create_oauth_bindings_table)
+            .await
+            .expect("Failed to create user_oauth_bindings table");
     }
 
     // 创建测试所需的角色
@@ -127,6 +194,17 @@ mod tests {
             ..Default::default()
         };
         super_admin_role.insert(db).await.unwrap();
+
+        // 创建查看者角色，自助注册与第三方登录创建的用户默认绑定该角色
+        let viewer_role = roles::ActiveModel {
+            code: Set("VIEWER".to_string()),
+            name: Set("查看者".to_string()),
+            description: Set(Some("只读权限".to_string())),
+            is_system: Set(Some(true)),
+            created_at: Set(Some(Utc::now().fixed_offset())),
+            ..Default::default()
+        };
+        viewer_role.insert(db).await.unwrap();
     }
 
     // 测试创建用户 - 成功案例
@@ -140,14 +218,20 @@ mod tests {
         let register_data = RegisterResponse {
             user_name: "testuser".to_string(),
             pass_word: "testpassword123".to_string(),
+            email: None,
         };
 
         // 创建一个模拟的通知器
         let notifier = web::Data::new(SseNotifier::new());
 
         // 调用注册服务
-        let result =
-            AuthService::register(db_pool.clone(), web::Json(register_data), notifier).await;
+        let result = AuthService::register(
+            db_pool.clone(),
+            web::Json(register_data),
+            notifier,
+            email_service(),
+        )
+        .await;
 
         // 验证结果
         assert!(result.is_ok(), "用户注册应该成功");
@@ -176,17 +260,91 @@ mod tests {
             "角色应该被标记为主角色"
         );
 
-        // 验证角色是否为超级管理员（当前代码的问题）
+        // 验证角色为只读的查看者
         let role = roles::Entity::find_by_id(user_role.role_id.unwrap())
             .one(db_pool.as_ref())
             .await
             .unwrap()
             .unwrap();
 
-        assert_eq!(
-            role.code, "SUPER_ADMIN",
-            "当前代码中，新用户被错误地分配为超级管理员"
-        );
+        assert_eq!(role.code, "VIEWER", "新用户应该被分配为查看者");
+    }
+
+    // 测试自助注册的用户访问管理接口被拒绝
+    #[actix_web::test]
+    async fn test_registered_user_denied_admin_route() {
+        let _guard = CACHE_TEST_LOCK.lock().await;
+        let db = setup_test_db().await;
+        // 令牌撤销名单按用户ID记录，先占用一个ID，让注册的用户避开其他测试使用的ID
+        users::ActiveModel {
+            id: Set(8800),
+            uuid: Set(uuid::Uuid::new_v4().to_string()),
+            user_name: Set("placeholder".to_string()),
+            pass_word: Set(hash("password123").unwrap()),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let db_pool = web::Data::new(db);
+
+        let user = AuthService::register(
+            db_pool.clone(),
+            web::Json(RegisterResponse {
+                user_name: "newcomer".to_string(),
+                pass_word: "testpassword123".to_string(),
+                email: None,
+            }),
+            web::Data::new(SseNotifier::new()),
+            email_service(),
+        )
+        .await
+        .unwrap();
+
+        let role_id = |code: &'static str| {
+            let db = db_pool.clone();
+            async move {
+                roles::Entity::find()
+                    .filter(roles::Column::Code.eq(code))
+                    .one(db.as_ref())
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .id
+            }
+        };
+        let super_admin = role_id("SUPER_ADMIN").await;
+        let viewer = role_id("VIEWER").await;
+        ROLE_PERMS.write().await.extend([
+            (super_admin, HashSet::from(["system:rbac".to_string()])),
+            (viewer, HashSet::from(["post:read".to_string()])),
+        ]);
+
+        init_route_registry().unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(Auth)
+                .default_service(web::route().to(HttpResponse::Ok)),
+        )
+        .await;
+        let token = generate_jwt(db_pool.as_ref(), &user, "session")
+            .await
+            .unwrap();
+        let req = test::TestRequest::get()
+            .uri("/api/v1/admin/roles")
+            .insert_header(("Authorization", format!("Bearer {token}")))
+            .to_request();
+        let status = match test::try_call_service(&app, req).await {
+            Ok(resp) => resp.status(),
+            Err(e) => e.error_response().status(),
+        };
+
+        let mut role_perms = ROLE_PERMS.write().await;
+        role_perms.remove(&super_admin);
+        role_perms.remove(&viewer);
+        assert_eq!(status, StatusCode::FORBIDDEN);
     }
 
     // 测试创建用户 - 用户名太短
@@ -199,12 +357,15 @@ mod tests {
         let register_data = RegisterResponse {
             user_name: "test".to_string(), // 用户名长度为4，小于最小要求5
             pass_word: "testpassword123".to_string(),
+            email: None,
         };
 
         let notifier = web::Data::new(SseNotifier::new());
 
         // 调用注册服务
-        let result = AuthService::register(db_pool, web::Json(register_data), notifier).await;
+        let result =
+            AuthService::register(db_pool, web::Json(register_data), notifier, email_service())
+                .await;
 
         // 验证结果
         assert!(result.is_err(), "用户名太短应该导致注册失败");
@@ -228,24 +389,27 @@ mod tests {
         // 创建密码太短的注册请求数据
         let register_data = RegisterResponse {
             user_name: "testuser".to_string(),
-            pass_word: "short".to_string(), // 密码长度为5，小于最小要求6
+            pass_word: "short".to_string(), // 密码长度为5，小于密码策略的最小长度8
+            email: None,
         };
 
         let notifier = web::Data::new(SseNotifier::new());
 
         // 调用注册服务
-        let result = AuthService::register(db_pool, web::Json(register_data), notifier).await;
+        let result =
+            AuthService::register(db_pool, web::Json(register_data), notifier, email_service())
+                .await;
 
         // 验证结果
         assert!(result.is_err(), "密码太短应该导致注册失败");
         let error = result.unwrap_err();
 
-        // 验证错误类型
+        // 验证错误类型：长度由密码策略统一校验
         match error {
-            crate::config::AppError::ValidationError(_) => {
-                // 验证错误是期望的
+            crate::config::AppError::BadRequest(msg) => {
+                assert!(msg.contains('8'), "错误信息应给出策略的最小长度: {msg}");
             }
-            _ => panic!("应该返回验证错误，但得到了: {:?}", error),
+            _ => panic!("应该返回密码策略错误，但得到了: {:?}", error),
         }
     }
 
@@ -259,21 +423,34 @@ mod tests {
         let register_data1 = RegisterResponse {
             user_name: "duplicateuser".to_string(),
             pass_word: "password123".to_string(),
+            email: None,
         };
 
         let notifier1 = web::Data::new(SseNotifier::new());
-        AuthService::register(db_pool.clone(), web::Json(register_data1), notifier1)
-            .await
-            .unwrap();
+        AuthService::register(
+            db_pool.clone(),
+            web::Json(register_data1),
+            notifier1,
+            email_service(),
+        )
+        .await
+        .unwrap();
 
         // 尝试使用相同用户名创建另一个用户
         let register_data2 = RegisterResponse {
             user_name: "duplicateuser".to_string(), // 相同的用户名
             pass_word: "anotherpassword123".to_string(),
+            email: None,
         };
 
         let notifier2 = web::Data::new(SseNotifier::new());
-        let result = AuthService::register(db_pool, web::Json(register_data2), notifier2).await;
+        let result = AuthService::register(
+            db_pool,
+            web::Json(register_data2),
+            notifier2,
+            email_service(),
+        )
+        .await;
 
         // 验证结果
         assert!(result.is_err(), "重复的用户名应该导致注册失败");
@@ -299,12 +476,18 @@ mod tests {
             let register_data = RegisterResponse {
                 user_name: format!("testuser{}", i),
                 pass_word: "password123".to_string(),
+                email: None,
             };
 
             let notifier = web::Data::new(SseNotifier::new());
-            AuthService::register(db_pool.clone(), web::Json(register_data), notifier)
-                .await
-                .unwrap();
+            AuthService::register(
+                db_pool.clone(),
+                web::Json(register_data),
+                notifier,
+                email_service(),
+            )
+            .await
+            .unwrap();
         }
 
         // 获取用户列表
@@ -331,13 +514,18 @@ mod tests {
         let register_data = RegisterResponse {
             user_name: "getuserbyid".to_string(),
             pass_word: "password123".to_string(),
+            email: None,
         };
 
         let notifier = web::Data::new(SseNotifier::new());
-        let created_user =
-            AuthService::register(db_pool.clone(), web::Json(register_data), notifier)
-                .await
-                .unwrap();
+        let created_user = AuthService::register(
+            db_pool.clone(),
+            web::Json(register_data),
+            notifier,
+            email_service(),
+        )
+        .await
+        .unwrap();
 
         // 按ID获取用户
         let found_user = users::Entity::find_by_id(created_user.id)
@@ -362,12 +550,18 @@ mod tests {
         let register_data = RegisterResponse {
             user_name: "getuserbyname".to_string(),
             pass_word: "password123".to_string(),
+            email: None,
         };
 
         let notifier = web::Data::new(SseNotifier::new());
-        AuthService::register(db_pool.clone(), web::Json(register_data), notifier)
-            .await
-            .unwrap();
+        AuthService::register(
+            db_pool.clone(),
+            web::Json(register_data),
+            notifier,
+            email_service(),
+        )
+        .await
+        .unwrap();
 
         // 按用户名获取用户
         let found_user = users::Entity::find()
@@ -393,12 +587,18 @@ mod tests {
             let register_data = RegisterResponse {
                 user_name: format!("paginateduser{}", i),
                 pass_word: "password123".to_string(),
+                email: None,
             };
 
             let notifier = web::Data::new(SseNotifier::new());
-            AuthService::register(db_pool.clone(), web::Json(register_data), notifier)
-                .await
-                .unwrap();
+            AuthService::register(
+                db_pool.clone(),
+                web::Json(register_data),
+                notifier,
+                email_service(),
+            )
+            .await
+            .unwrap();
         }
 
         // 获取第一页，每页2个用户
@@ -434,13 +634,18 @@ mod tests {
         let register_data = RegisterResponse {
             user_name: "deleteuser".to_string(),
             pass_word: "password123".to_string(),
+            email: None,
         };
 
         let notifier = web::Data::new(SseNotifier::new());
-        let created_user =
-            AuthService::register(db_pool.clone(), web::Json(register_data), notifier)
-                .await
-                .unwrap();
+        let created_user = AuthService::register(
+            db_pool.clone(),
+            web::Json(register_data),
+            notifier,
+            email_service(),
+        )
+        .await
+        .unwrap();
 
         // 删除用户
         let delete_result = users::Entity::delete_by_id(created_user.id)
@@ -469,13 +674,18 @@ mod tests {
         let register_data = RegisterResponse {
             user_name: "updateuser".to_string(),
             pass_word: "password123".to_string(),
+            email: None,
         };
 
         let notifier = web::Data::new(SseNotifier::new());
-        let created_user =
-            AuthService::register(db_pool.clone(), web::Json(register_data), notifier)
-                .await
-                .unwrap();
+        let created_user = AuthService::register(
+            db_pool.clone(),
+            web::Json(register_data),
+            notifier,
+            email_service(),
+        )
+        .await
+        .unwrap();
 
         // 更新用户信息
         let updated_user = users::ActiveModel {
@@ -493,4 +703,191 @@ mod tests {
         assert_eq!(updated_user.email, Some("updated@example.com".to_string()));
         assert_ne!(updated_user.updated_at, created_user.updated_at);
     }
+
+    // 创建已启用两步验证的用户，绑定邮箱、手机号与第三方账号
+    async fn create_two_factor_user(db: &DatabaseConnection, name: &str) -> users::Model {
+        let user = users::ActiveModel {
+            uuid: Set(uuid::Uuid::new_v4().to_string()),
+            user_name: Set(name.to_string()),
+            pass_word: Set(hash("password123").unwrap()),
+            email: Set(Some(format!("{name}@example.com"))),
+            phone: Set(Some("13800138000".to_string())),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        user_two_factor::ActiveModel {
+            user_id: Set(user.id),
+            secret: Set("JBSWY3DPEHPK3PXP".to_string()),
+            enabled_at: Set(Some(Utc::now().fixed_offset())),
+            created_at: Set(Some(Utc::now().fixed_offset())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        user_oauth_bindings::ActiveModel {
+            user_id: Set(user.id),
+            provider: Set("test-oidc".to_string()),
+            subject: Set("oidc-subject".to_string()),
+            created_at: Set(Some(Utc::now().fixed_offset())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        user
+    }
+
+    fn login_context(method: &'static str) -> LoginContext {
+        LoginContext {
+            method,
+            ip: "127.0.0.1".to_string(),
+            user_agent: None,
+            email_service: None,
+        }
+    }
+
+    fn challenges() -> web::Data<TwoFactorChallengeManager> {
+        web::Data::new(TwoFactorChallengeManager::new(300))
+    }
+
+    // 断言返回的是两步验证挑战，且没有写入登录 Cookie
+    async fn assert_two_factor_challenge(response: HttpResponse) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response.headers().get(SET_COOKIE).is_none(),
+            "启用两步验证时不应签发登录 Cookie"
+        );
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"]["two_factor_required"], true);
+        assert!(body["data"]["challenge_token"].as_str().is_some());
+    }
+
+    // 在本地启动只响应一次的 userinfo 接口
+    async fn serve_userinfo(userinfo: Value) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            let body = userinfo.to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{addr}/userinfo")
+    }
+
+    // 测试密码登录 - 已启用两步验证时只返回挑战
+    #[tokio::test]
+    async fn test_login_by_pwd_requires_two_factor() {
+        let db = setup_test_db().await;
+        create_two_factor_user(&db, "twofactorpwd").await;
+
+        let response = AuthService::login_by_pwd(
+            web::Data::new(db),
+            PasswordLogin {
+                account: "twofactorpwd".to_string(),
+                password: "password123".to_string(),
+            },
+            challenges(),
+            &login_context("password"),
+        )
+        .await
+        .unwrap();
+        assert_two_factor_challenge(response).await;
+    }
+
+    // 测试邮箱验证码登录 - 已启用两步验证时只返回挑战
+    #[tokio::test]
+    async fn test_login_by_email_requires_two_factor() {
+        let db = setup_test_db().await;
+        let user = create_two_factor_user(&db, "twofactoremail").await;
+        let email = user.email.unwrap();
+        let manager = web::Data::new(EmailVerificationManager::new());
+        manager.insert_code(&email, "123456").await;
+
+        let response = AuthService::login_by_email(
+            web::Data::new(db),
+            EmailLogin {
+                email,
+                code: "123456".to_string(),
+            },
+            manager,
+            challenges(),
+            &login_context("email"),
+        )
+        .await
+        .unwrap();
+        assert_two_factor_challenge(response).await;
+    }
+
+    // 测试手机号验证码登录 - 已启用两步验证时只返回挑战
+    #[tokio::test]
+    async fn test_login_by_phone_requires_two_factor() {
+        let db = setup_test_db().await;
+        let user = create_two_factor_user(&db, "twofactorphone").await;
+        let phone = user.phone.unwrap();
+        let manager = web::Data::new(SmsVerificationManager::new(300));
+        manager.insert_code(&phone, "123456").await;
+
+        let response = AuthService::login_by_phone(
+            web::Data::new(db),
+            PhoneLogin {
+                phone,
+                code: "123456".to_string(),
+            },
+            manager,
+            challenges(),
+            &login_context("phone"),
+        )
+        .await
+        .unwrap();
+        assert_two_factor_challenge(response).await;
+    }
+
+    // 测试第三方令牌登录 - 已启用两步验证时只返回挑战
+    #[tokio::test]
+    async fn test_login_by_oauth_requires_two_factor() {
+        let db = setup_test_db().await;
+        create_two_factor_user(&db, "twofactoroauth").await;
+        let userinfo_url = serve_userinfo(json!({ "sub": "oidc-subject" })).await;
+        let oauth_service = web::Data::new(OAuthService::new(OAuthSettings {
+            providers: vec![OAuthProviderSettings {
+                name: "test-oidc".to_string(),
+                kind: "oidc".to_string(),
+                client_id: "client".to_string(),
+                client_secret: "secret".to_string(),
+                redirect_uri: "http://localhost/callback".to_string(),
+                scopes: "openid".to_string(),
+                issuer: None,
+                authorize_url: Some("http://localhost/authorize".to_string()),
+                token_url: Some("http://localhost/token".to_string()),
+                userinfo_url: Some(userinfo_url),
+            }],
+        }));
+
+        let response = AuthService::login_by_oauth(
+            web::Data::new(db),
+            OAuthLogin {
+                provider: "test-oidc".to_string(),
+                openid: "oidc-subject".to_string(),
+                access_token: "access-token".to_string(),
+            },
+            oauth_service,
+            challenges(),
+            &login_context("oauth"),
+        )
+        .await
+        .unwrap();
+        assert_two_factor_challenge(response).await;
+    }
 }
@@ -66,8 +66,44 @@ impl EmailService {
         self.send_email(to_email, subject, &body)
     }
 
+    /// 发送邮箱验证邮件
+    pub fn send_email_verification(
+        &self,
+        to_email: &str,
+        verify_link: &str,
+        valid_minutes: i64,
+    ) -> Result<()> {
+        let subject = "博客系统邮箱验证";
+        let body = format!(
+            r#"
+            <html>
+                <body>
+                    <h2>博客系统邮箱验证</h2>
+                    <p>您好，</p>
+                    <p>请点击以下链接验证您的邮箱：</p>
+                    <a href="{}" style="color: blue; font-size: 18px;">验证邮箱</a>
+                    <p>如果您无法点击链接，请复制以下URL到浏览器地址栏：</p>
+                    <p>{}</p>
+                    <p>此链接有效期为{}分钟，请及时使用。</p>
+                    <p>如果这不是您的操作，请忽略此邮件。</p>
+                    <p>谢谢！</p>
+                    <p>博客系统团队</p>
+                </body>
+            </html>
+            "#,
+            verify_link, verify_link, valid_minutes
+        );
+
+        self.send_email(to_email, subject, &body)
+    }
+
     /// 发送密码重置邮件
-    pub fn send_password_reset(&self, to_email: &str, reset_link: &str) -> Result<()> {
+    pub fn send_password_reset(
+        &self,
+        to_email: &str,
+        reset_link: &str,
+        valid_minutes: i64,
+    ) -> Result<()> {
         let subject = "博客系统密码重置";
         let body = format!(
             r#"
@@ -86,9 +122,39 @@ impl EmailService {
                 </body>
             </html>
             "#,
-            reset_link,
-            reset_link,
-            self.settings.code_validity_period / 60
+            reset_link, reset_link, valid_minutes
+        );
+
+        self.send_email(to_email, subject, &body)
+    }
+
+    /// 发送新设备登录提醒
+    pub fn send_new_device_login(
+        &self,
+        to_email: &str,
+        username: &str,
+        ip: &str,
+        device: &str,
+        login_time: &str,
+    ) -> Result<()> {
+        let subject = "博客系统新设备登录提醒";
+        let body = format!(
+            r#"
+            <html>
+                <body>
+                    <h2>新设备登录提醒</h2>
+                    <p>{}，您好：</p>
+                    <p>您的账号刚刚在一台新设备上登录：</p>
+                    <p>时间：{}</p>
+                    <p>IP：{}</p>
+                    <p>设备：{}</p>
+                    <p>如果这是您本人的操作，请忽略此邮件；否则请立即修改密码，并在“登录设备”中移除该设备。</p>
+                    <p>谢谢！</p>
+                    <p>博客系统团队</p>
+                </body>
+            </html>
+            "#,
+            username, login_time, ip, device
         );
 
         self.send_email(to_email, subject, &body)
@@ -336,6 +402,15 @@ impl EmailVerificationManager {
         Ok(code)
     }
 
+    /// 测试用：直接写入已知的验证码
+    #[cfg(test)]
+    pub(crate) async fn insert_code(&self, email: &str, code: &str) {
+        self.codes.write().await.insert(
+            email.to_string(),
+            EmailVerificationCode::new(email.to_string(), code.to_string()),
+        );
+    }
+
     /// 验证验证码
     pub async fn verify_code(&self, email: &str, code: &str) -> Result<bool> {
         let mut codes = self.codes.write().await;
@@ -0,0 +1,281 @@
+//! 登录记录：成功与失败的登录都会写入 `login_history`，
+//! 可选在新设备（此前未成功登录过的 User-Agent）登录时发送邮件提醒。
+
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::dto::session::LoginHistoryResponse;
+use crate::dto::{PaginatedResp, Pagination};
+use crate::models::{login_history, users};
+use crate::services::session::LoginContext;
+use crate::utils::format_beijing;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+
+/// 登录记录配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginHistorySettings {
+    /// 新设备登录时是否向已验证的邮箱发送提醒
+    pub notify_new_device: bool,
+}
+
+/// 登录记录服务
+pub struct LoginHistoryService;
+
+impl LoginHistoryService {
+    /// 记录一次成功登录，新设备登录时按配置发送提醒邮件
+    pub async fn record_success(
+        db: &DatabaseConnection,
+        user: &users::Model,
+        ctx: &LoginContext,
+        session_id: &str,
+    ) -> Result<(), AppError> {
+        let new_device =
+            CONFIG.login_history.notify_new_device && Self::is_new_device(db, user.id, ctx).await?;
+        login_history::ActiveModel {
+            user_id: Set(Some(user.id)),
+            account: Set(Some(user.user_name.clone())),
+            login_method: Set(ctx.method.to_string()),
+            success: Set(true),
+            failure_reason: Set(None),
+            session_id: Set(Some(session_id.to_string())),
+            ip: Set(Some(ctx.ip.clone())),
+            user_agent: Set(ctx.user_agent.clone()),
+            created_at: Set(Some(Utc::now().into())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        if new_device
+            && user.email_verified
+            && let (Some(email), Some(email_service)) = (&user.email, &ctx.email_service)
+            && let Err(e) = email_service.send_new_device_login(
+                email,
+                &user.user_name,
+                &ctx.ip,
+                ctx.user_agent.as_deref().unwrap_or("未知设备"),
+                &format_beijing(Utc::now()),
+            )
+        {
+            log::warn!("发送新设备登录提醒失败: {e}");
+        }
+        Ok(())
+    }
+
+    /// 记录一次失败的登录；账号能对应到用户时一并记录用户ID
+    pub async fn record_failure(
+        db: &DatabaseConnection,
+        ctx: &LoginContext,
+        account: Option<&str>,
+        error: &AppError,
+    ) {
+        let user_id = match account {
+            Some(account) => Self::resolve_user(db, ctx.method, account).await,
+            None => None,
+        };
+        let failure_reason: String = error.to_string().chars().take(255).collect();
+        let record = login_history::ActiveModel {
+            user_id: Set(user_id),
+            account: Set(account.map(|account| account.chars().take(255).collect())),
+            login_method: Set(ctx.method.to_string()),
+            success: Set(false),
+            failure_reason: Set(Some(failure_reason)),
+            session_id: Set(None),
+            ip: Set(Some(ctx.ip.clone())),
+            user_agent: Set(ctx.user_agent.clone()),
+            created_at: Set(Some(Utc::now().into())),
+            ..Default::default()
+        };
+        // 记录失败不影响登录接口本身的响应
+        if let Err(e) = record.insert(db).await {
+            log::warn!("保存登录记录失败: {e}");
+        }
+    }
+
+    /// 当前用户的登录记录，最新的在前
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: i32,
+        page: u64,
+        limit: u64,
+    ) -> Result<PaginatedResp<LoginHistoryResponse>, AppError> {
+        let paginator = login_history::Entity::find()
+            .filter(login_history::Column::UserId.eq(user_id))
+            .order_by_desc(login_history::Column::CreatedAt)
+            .order_by_desc(login_history::Column::Id)
+            .paginate(db, limit);
+        let total = paginator.num_items().await?;
+        let data = paginator
+            .fetch_page(page.saturating_sub(1))
+            .await?
+            .into_iter()
+            .map(LoginHistoryResponse::from)
+            .collect();
+        Ok(PaginatedResp {
+            data,
+            pagination: Pagination { page, limit, total },
+        })
+    }
+
+    /// 此前有成功登录、但从未以该 User-Agent 成功登录过
+    async fn is_new_device(
+        db: &DatabaseConnection,
+        user_id: i32,
+        ctx: &LoginContext,
+    ) -> Result<bool, AppError> {
+        let successes = login_history::Entity::find()
+            .filter(login_history::Column::UserId.eq(user_id))
+            .filter(login_history::Column::Success.eq(true));
+        if successes.clone().count(db).await? == 0 {
+            // 首次登录不算新设备
+            return Ok(false);
+        }
+        let same_device = match &ctx.user_agent {
+            Some(ua) => successes.filter(login_history::Column::UserAgent.eq(ua)),
+            None => successes.filter(login_history::Column::UserAgent.is_null()),
+        };
+        Ok(same_device.count(db).await? == 0)
+    }
+
+    /// 按登录方式把提交的账号对应到用户
+    async fn resolve_user(db: &DatabaseConnection, method: &str, account: &str) -> Option<i32> {
+        let column = match method {
+            "password" => users::Column::UserName,
+            "email" => users::Column::Email,
+            "phone" => users::Column::Phone,
+            _ => return None,
+        };
+        users::Entity::find()
+            .filter(column.eq(account))
+            .one(db)
+            .await
+            .ok()
+            .flatten()
+            .map(|user| user.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_db::{create_user, setup_test_db};
+
+    fn login_context(method: &'static str, user_agent: Option<&str>) -> LoginContext {
+        LoginContext {
+            method,
+            ip: "127.0.0.1".to_string(),
+            user_agent: user_agent.map(String::from),
+            email_service: None,
+        }
+    }
+
+    async fn history(db: &DatabaseConnection) -> Vec<login_history::Model> {
+        login_history::Entity::find()
+            .order_by_asc(login_history::Column::Id)
+            .all(db)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn first_login_is_not_a_new_device() {
+        let db = setup_test_db().await;
+        let user = create_user(&db, "deviceuser").await;
+        let laptop = login_context("password", Some("laptop"));
+
+        assert!(
+            !LoginHistoryService::is_new_device(&db, user.id, &laptop)
+                .await
+                .unwrap()
+        );
+        LoginHistoryService::record_success(&db, &user, &laptop, "session-1")
+            .await
+            .unwrap();
+        assert!(
+            !LoginHistoryService::is_new_device(&db, user.id, &laptop)
+                .await
+                .unwrap()
+        );
+        let phone = login_context("password", Some("phone"));
+        assert!(
+            LoginHistoryService::is_new_device(&db, user.id, &phone)
+                .await
+                .unwrap()
+        );
+        let unknown = login_context("password", None);
+        assert!(
+            LoginHistoryService::is_new_device(&db, user.id, &unknown)
+                .await
+                .unwrap()
+        );
+
+        // 失败的登录不算用过的设备
+        LoginHistoryService::record_failure(
+            &db,
+            &phone,
+            Some("deviceuser"),
+            &AppError::Unauthorized("密码错误".to_string()),
+        )
+        .await;
+        assert!(
+            LoginHistoryService::is_new_device(&db, user.id, &phone)
+                .await
+                .unwrap()
+        );
+
+        let records = history(&db).await;
+        assert!(records[0].success);
+        assert_eq!(records[0].session_id.as_deref(), Some("session-1"));
+    }
+
+    #[tokio::test]
+    async fn record_failure_resolves_account_by_method() {
+        let db = setup_test_db().await;
+        let user = create_user(&db, "failureuser").await;
+        let error = AppError::Unauthorized("密码错误".to_string());
+
+        LoginHistoryService::record_failure(
+            &db,
+            &login_context("password", Some("ua")),
+            Some("failureuser"),
+            &error,
+        )
+        .await;
+        LoginHistoryService::record_failure(
+            &db,
+            &login_context("email", None),
+            user.email.as_deref(),
+            &error,
+        )
+        .await;
+        LoginHistoryService::record_failure(
+            &db,
+            &login_context("password", None),
+            Some("nobody"),
+            &error,
+        )
+        .await;
+        LoginHistoryService::record_failure(&db, &login_context("oauth", None), None, &error).await;
+
+        let records = history(&db).await;
+        assert_eq!(records.len(), 4);
+        assert!(records.iter().all(|r| !r.success && r.session_id.is_none()));
+        assert_eq!(
+            records.iter().map(|r| r.user_id).collect::<Vec<_>>(),
+            vec![Some(user.id), Some(user.id), None, None]
+        );
+        assert_eq!(records[0].failure_reason, Some(error.to_string()));
+        assert_eq!(records[0].user_agent.as_deref(), Some("ua"));
+        assert_eq!(records[2].account.as_deref(), Some("nobody"));
+
+        // 失败记录也出现在用户的登录记录中
+        let page = LoginHistoryService::list(&db, user.id, 1, 10)
+            .await
+            .unwrap();
+        assert_eq!(page.pagination.total, 2);
+    }
+}
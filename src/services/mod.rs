@@ -11,10 +11,30 @@ pub mod category;
 #[cfg(test)]
 mod auth_test;
 pub mod email;
+pub mod post_revision;
+pub mod post_search;
 pub mod posts;
 #[cfg(test)]
 mod posts_test;
+#[cfg(test)]
+pub(crate) mod test_db;
 
+pub mod account;
+pub mod api_token;
 pub mod images;
+pub mod login_history;
+pub mod oauth;
+pub mod permission_sync;
+pub mod rbac;
+pub mod refresh_token;
+pub mod session;
+pub mod sms;
+pub mod two_factor;
 pub mod upload;
+pub use account::AccountService;
 pub use email::{EmailService, EmailVerificationManager};
+pub use login_history::LoginHistoryService;
+pub use oauth::OAuthService;
+pub use session::{LoginContext, SessionService};
+pub use sms::{SmsService, SmsVerificationManager};
+pub use two_factor::{TwoFactorChallengeManager, TwoFactorService};
@@ -0,0 +1,641 @@
+//! 第三方登录（OAuth2 授权码模式 + PKCE）。
+//!
+//! 服务商由 `OAUTH_PROVIDERS` 配置，`github` 使用 GitHub 的固定端点，`oidc` 通过
+//! `{issuer}/.well-known/openid-configuration` 自动发现端点，也可以逐个覆盖。
+//! 一个用户可以绑定多个服务商，每个服务商只能绑定一个账号。
+
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::dto::oauth::OAuthBindingResponse;
+use crate::models::{roles, user_oauth_bindings, user_roles, users};
+use crate::utils::crypto_pwd::hash;
+use base64::engine::{Engine as _, general_purpose};
+use chrono::Utc;
+use rand::{Rng, RngCore, distributions::Alphanumeric};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// 授权请求（state）的有效期（秒）
+pub const STATE_VALIDITY_PERIOD: u64 = 600;
+const GITHUB_AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+const GITHUB_TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+const GITHUB_USERINFO_URL: &str = "https://api.github.com/user";
+const GITHUB_EMAILS_URL: &str = "https://api.github.com/user/emails";
+
+/// 第三方登录配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthSettings {
+    pub providers: Vec<OAuthProviderSettings>,
+}
+
+/// 单个服务商的配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthProviderSettings {
+    /// 服务商名称，出现在接口路径与绑定记录中
+    pub name: String,
+    /// 服务商类型：`github` 或 `oidc`
+    pub kind: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// 在服务商处登记的回调地址
+    pub redirect_uri: String,
+    /// 申请的权限范围，空格分隔
+    pub scopes: String,
+    /// OIDC 签发者，用于自动发现端点
+    pub issuer: Option<String>,
+    pub authorize_url: Option<String>,
+    pub token_url: Option<String>,
+    pub userinfo_url: Option<String>,
+}
+
+/// 服务商返回的用户身份
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalIdentity {
+    /// 服务商侧的用户唯一标识
+    pub subject: String,
+    pub user_name: Option<String>,
+    pub email: Option<String>,
+    pub avatar: Option<String>,
+}
+
+/// 授权回调的结果
+pub struct OAuthCallback {
+    pub identity: ExternalIdentity,
+    /// 发起授权时已登录的用户，存在时表示绑定而不是登录
+    pub link_user_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Endpoints {
+    #[serde(rename = "authorization_endpoint")]
+    authorize_url: String,
+    #[serde(rename = "token_endpoint")]
+    token_url: String,
+    #[serde(rename = "userinfo_endpoint")]
+    userinfo_url: String,
+}
+
+/// 等待回调的授权请求
+struct PendingAuthorization {
+    provider: String,
+    code_verifier: String,
+    link_user_id: Option<i32>,
+    created_at: u64,
+}
+
+/// 第三方登录服务
+pub struct OAuthService {
+    http: reqwest::Client,
+    providers: HashMap<String, OAuthProviderSettings>,
+    /// 服务商名称 → 已解析的端点
+    endpoints: RwLock<HashMap<String, Endpoints>>,
+    /// state → 授权请求（生产环境应使用Redis等）
+    states: Arc<RwLock<HashMap<String, PendingAuthorization>>>,
+}
+
+impl Default for OAuthService {
+    fn default() -> Self {
+        Self::new(CONFIG.oauth.clone())
+    }
+}
+
+impl OAuthService {
+    pub fn new(settings: OAuthSettings) -> Self {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("web-server/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build http client");
+        Self {
+            http,
+            providers: settings
+                .providers
+                .into_iter()
+                .map(|p| (p.name.clone(), p))
+                .collect(),
+            endpoints: RwLock::new(HashMap::new()),
+            states: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 生成授权地址，返回 (授权地址, state)
+    pub async fn authorize_url(
+        &self,
+        provider: &str,
+        link_user_id: Option<i32>,
+    ) -> Result<(String, String), AppError> {
+        let settings = self.provider(provider)?;
+        let endpoints = self.endpoints(settings).await?;
+        let state = random_token();
+        let code_verifier = random_token();
+        let url = url::Url::parse_with_params(
+            &endpoints.authorize_url,
+            &[
+                ("response_type", "code"),
+                ("client_id", settings.client_id.as_str()),
+                ("redirect_uri", settings.redirect_uri.as_str()),
+                ("scope", settings.scopes.as_str()),
+                ("state", state.as_str()),
+                ("code_challenge", pkce_challenge(&code_verifier).as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| AppError::ConfigurationError(format!("{provider} 授权地址无效: {e}")))?;
+
+        self.states.write().await.insert(
+            state.clone(),
+            PendingAuthorization {
+                provider: provider.to_string(),
+                code_verifier,
+                link_user_id,
+                created_at: now_secs(),
+            },
+        );
+        Ok((url.into(), state))
+    }
+
+    /// 处理授权回调：校验 state、用授权码换取令牌并读取用户身份。state 只能使用一次
+    pub async fn callback(
+        &self,
+        provider: &str,
+        code: &str,
+        state: &str,
+    ) -> Result<OAuthCallback, AppError> {
+        let pending = self
+            .states
+            .write()
+            .await
+            .remove(state)
+            .filter(|p| {
+                p.provider == provider && now_secs() - p.created_at <= STATE_VALIDITY_PERIOD
+            })
+            .ok_or_else(|| AppError::BadRequest("授权请求无效或已过期".to_string()))?;
+
+        let settings = self.provider(provider)?;
+        let endpoints = self.endpoints(settings).await?;
+        let token: Value = self
+            .http
+            .post(&endpoints.token_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", settings.redirect_uri.as_str()),
+                ("client_id", settings.client_id.as_str()),
+                ("client_secret", settings.client_secret.as_str()),
+                ("code_verifier", pending.code_verifier.as_str()),
+            ])
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| external_error(provider, e))?
+            .json()
+            .await
+            .map_err(|e| external_error(provider, e))?;
+        // GitHub 换取失败时也返回 200，错误信息在 error 字段中
+        let access_token = token["access_token"].as_str().ok_or_else(|| {
+            log::warn!("{provider} 授权码换取令牌失败: {token}");
+            AppError::Unauthorized("第三方授权失败".to_string())
+        })?;
+
+        Ok(OAuthCallback {
+            identity: self.fetch_identity(provider, access_token).await?,
+            link_user_id: pending.link_user_id,
+        })
+    }
+
+    /// 用服务商的访问令牌读取用户身份
+    pub async fn fetch_identity(
+        &self,
+        provider: &str,
+        access_token: &str,
+    ) -> Result<ExternalIdentity, AppError> {
+        let settings = self.provider(provider)?;
+        let endpoints = self.endpoints(settings).await?;
+        let response = self
+            .http
+            .get(&endpoints.userinfo_url)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .map_err(|e| external_error(provider, e))?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(AppError::Unauthorized("第三方访问令牌无效".to_string()));
+        }
+        let userinfo: Value = response
+            .error_for_status()
+            .map_err(|e| external_error(provider, e))?
+            .json()
+            .await
+            .map_err(|e| external_error(provider, e))?;
+        let mut identity = parse_identity(&settings.kind, &userinfo)
+            .ok_or_else(|| AppError::ExternalServiceError(format!("{provider} 未返回用户标识")))?;
+
+        // GitHub 隐藏邮箱时需要单独读取已验证的主邮箱
+        if settings.kind == "github" && identity.email.is_none() {
+            identity.email = self.github_primary_email(access_token).await;
+        }
+        Ok(identity)
+    }
+
+    /// 启动定期清理过期授权请求的任务
+    pub fn start_cleanup_task(&self) {
+        let states = self.states.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60 * 5)); // 每5分钟清理一次
+
+            loop {
+                interval.tick().await;
+                let now = now_secs();
+                states
+                    .write()
+                    .await
+                    .retain(|_, pending| now - pending.created_at <= STATE_VALIDITY_PERIOD);
+                log::debug!("Cleaned up expired oauth states");
+            }
+        });
+    }
+
+    /// 通过第三方身份登录：已绑定时返回绑定的用户，否则创建新用户并绑定
+    pub async fn login_user(
+        db: &DatabaseConnection,
+        provider: &str,
+        identity: &ExternalIdentity,
+    ) -> Result<users::Model, AppError> {
+        if let Some((_, Some(user))) = user_oauth_bindings::Entity::find()
+            .filter(user_oauth_bindings::Column::Provider.eq(provider))
+            .filter(user_oauth_bindings::Column::Subject.eq(&identity.subject))
+            .find_also_related(users::Entity)
+            .one(db)
+            .await?
+        {
+            return Ok(user);
+        }
+
+        let user_name = Self::available_user_name(db, provider, identity).await?;
+        // 邮箱已被其他账号使用时不自动合并，避免通过第三方账号接管已有用户
+        let email = match &identity.email {
+            Some(email)
+                if users::Entity::find()
+                    .filter(users::Column::Email.eq(email))
+                    .count(db)
+                    .await?
+                    == 0 =>
+            {
+                Some(email.clone())
+            }
+            _ => None,
+        };
+
+        let txn = db.begin().await?;
+        // 第三方用户没有密码，写入随机密码的哈希，之后可通过其他方式设置
+        let user = users::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            user_name: Set(user_name),
+            pass_word: Set(hash(&random_token())?),
+            // 服务商返回的邮箱视为已验证
+            email_verified: Set(email.is_some()),
+            email: Set(email),
+            image: Set(identity.avatar.clone()),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+        let viewer_role = roles::Entity::find()
+            .filter(roles::Column::Code.eq("VIEWER"))
+            .one(&txn)
+            .await?
+            .ok_or_else(|| AppError::DatabaseError("VIEWER role not found".into()))?;
+        user_roles::ActiveModel {
+            user_id: Set(Some(user.id)),
+            role_id: Set(Some(viewer_role.id)),
+            is_primary: Set(Some(true)),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+        insert_binding(&txn, user.id, provider, identity).await?;
+        txn.commit().await?;
+
+        log::info!("created user {} from {provider} login", user.user_name);
+        Ok(user)
+    }
+
+    /// 把第三方身份绑定到已有用户
+    pub async fn link(
+        db: &DatabaseConnection,
+        user_id: i32,
+        provider: &str,
+        identity: &ExternalIdentity,
+    ) -> Result<OAuthBindingResponse, AppError> {
+        let existing = user_oauth_bindings::Entity::find()
+            .filter(user_oauth_bindings::Column::Provider.eq(provider))
+            .filter(
+                user_oauth_bindings::Column::Subject
+                    .eq(&identity.subject)
+                    .or(user_oauth_bindings::Column::UserId.eq(user_id)),
+            )
+            .one(db)
+            .await?;
+        match existing {
+            Some(binding) if binding.user_id != user_id => {
+                Err(AppError::Conflict("该第三方账号已绑定其他用户".to_string()))
+            }
+            Some(_) => Err(AppError::Conflict(format!("已绑定 {provider} 账号"))),
+            None => Ok(to_response(
+                insert_binding(db, user_id, provider, identity).await?,
+            )),
+        }
+    }
+
+    /// 解除绑定
+    pub async fn unlink(
+        db: &DatabaseConnection,
+        user_id: i32,
+        provider: &str,
+    ) -> Result<(), AppError> {
+        let bindings = user_oauth_bindings::Entity::find()
+            .filter(user_oauth_bindings::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+        let binding = bindings
+            .iter()
+            .find(|b| b.provider == provider)
+            .ok_or_else(|| AppError::NotFound(format!("未绑定 {provider} 账号")))?;
+        // 解除最后一个绑定前需要有邮箱或手机号，否则用户将无法再登录
+        if bindings.len() == 1 {
+            let user = users::Entity::find_by_id(user_id)
+                .one(db)
+                .await?
+                .ok_or_else(|| AppError::NotFound("用户不存在".to_string()))?;
+            if user.email.is_none() && user.phone.is_none() {
+                return Err(AppError::BadRequest(
+                    "请先绑定邮箱或手机号，再解除最后一个第三方账号".to_string(),
+                ));
+            }
+        }
+        user_oauth_bindings::Entity::delete_by_id(binding.id)
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// 用户的全部第三方绑定
+    pub async fn list_bindings(
+        db: &DatabaseConnection,
+        user_id: i32,
+    ) -> Result<Vec<OAuthBindingResponse>, AppError> {
+        let bindings = user_oauth_bindings::Entity::find()
+            .filter(user_oauth_bindings::Column::UserId.eq(user_id))
+            .order_by_asc(user_oauth_bindings::Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(bindings.into_iter().map(to_response).collect())
+    }
+
+    fn provider(&self, provider: &str) -> Result<&OAuthProviderSettings, AppError> {
+        self.providers
+            .get(provider)
+            .ok_or_else(|| AppError::NotFound(format!("不支持的第三方登录: {provider}")))
+    }
+
+    /// 解析服务商端点：显式配置优先，其次为 GitHub 默认端点或 OIDC 自动发现
+    async fn endpoints(&self, settings: &OAuthProviderSettings) -> Result<Endpoints, AppError> {
+        if let Some(endpoints) = self.endpoints.read().await.get(&settings.name) {
+            return Ok(endpoints.clone());
+        }
+
+        let discovered = match (settings.kind.as_str(), &settings.issuer) {
+            ("github", _) => Some(Endpoints {
+                authorize_url: GITHUB_AUTHORIZE_URL.to_string(),
+                token_url: GITHUB_TOKEN_URL.to_string(),
+                userinfo_url: GITHUB_USERINFO_URL.to_string(),
+            }),
+            (_, Some(issuer))
+                if settings.authorize_url.is_none()
+                    || settings.token_url.is_none()
+                    || settings.userinfo_url.is_none() =>
+            {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    issuer.trim_end_matches('/')
+                );
+                let endpoints = self
+                    .http
+                    .get(&url)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status)
+                    .map_err(|e| external_error(&settings.name, e))?
+                    .json::<Endpoints>()
+                    .await
+                    .map_err(|e| external_error(&settings.name, e))?;
+                Some(endpoints)
+            }
+            _ => None,
+        };
+        let missing =
+            || AppError::ConfigurationError(format!("{} 缺少授权端点配置", settings.name));
+        let pick = |explicit: &Option<String>, discovered: Option<&String>| {
+            explicit.clone().or_else(|| discovered.cloned())
+        };
+        let endpoints = Endpoints {
+            authorize_url: pick(
+                &settings.authorize_url,
+                discovered.as_ref().map(|e| &e.authorize_url),
+            )
+            .ok_or_else(missing)?,
+            token_url: pick(
+                &settings.token_url,
+                discovered.as_ref().map(|e| &e.token_url),
+            )
+            .ok_or_else(missing)?,
+            userinfo_url: pick(
+                &settings.userinfo_url,
+                discovered.as_ref().map(|e| &e.userinfo_url),
+            )
+            .ok_or_else(missing)?,
+        };
+
+        self.endpoints
+            .write()
+            .await
+            .insert(settings.name.clone(), endpoints.clone());
+        Ok(endpoints)
+    }
+
+    async fn github_primary_email(&self, access_token: &str) -> Option<String> {
+        let emails: Vec<Value> = self
+            .http
+            .get(GITHUB_EMAILS_URL)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .ok()?
+            .error_for_status()
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+        emails
+            .iter()
+            .find(|e| e["primary"] == true && e["verified"] == true)
+            .and_then(|e| e["email"].as_str())
+            .map(String::from)
+    }
+
+    /// 以服务商用户名为基础生成未被占用的用户名
+    async fn available_user_name(
+        db: &DatabaseConnection,
+        provider: &str,
+        identity: &ExternalIdentity,
+    ) -> Result<String, AppError> {
+        let base = identity
+            .user_name
+            .clone()
+            .unwrap_or_else(|| format!("{provider}_{}", identity.subject));
+        let mut candidate = base.clone();
+        for _ in 0..5 {
+            if users::Entity::find_by_name(&candidate).count(db).await? == 0 {
+                return Ok(candidate);
+            }
+            let suffix: String = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(6)
+                .map(char::from)
+                .collect();
+            candidate = format!("{base}_{suffix}");
+        }
+        Err(AppError::Conflict("无法生成可用的用户名".to_string()))
+    }
+}
+
+async fn insert_binding<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    provider: &str,
+    identity: &ExternalIdentity,
+) -> Result<user_oauth_bindings::Model, AppError> {
+    Ok(user_oauth_bindings::ActiveModel {
+        user_id: Set(user_id),
+        provider: Set(provider.to_string()),
+        subject: Set(identity.subject.clone()),
+        email: Set(identity.email.clone()),
+        display_name: Set(identity.user_name.clone()),
+        avatar: Set(identity.avatar.clone()),
+        created_at: Set(Some(Utc::now().into())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?)
+}
+
+/// 按服务商类型解析用户信息，缺少用户标识时返回 `None`
+fn parse_identity(kind: &str, userinfo: &Value) -> Option<ExternalIdentity> {
+    let text = |key: &str| {
+        userinfo[key]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    match kind {
+        "github" => Some(ExternalIdentity {
+            // GitHub 的 id 是数字
+            subject: userinfo["id"].as_i64()?.to_string(),
+            user_name: text("login"),
+            email: text("email"),
+            avatar: text("avatar_url"),
+        }),
+        _ => Some(ExternalIdentity {
+            subject: text("sub")?,
+            user_name: text("preferred_username").or_else(|| text("name")),
+            // 未验证的邮箱不可信
+            email: text("email").filter(|_| userinfo["email_verified"] != false),
+            avatar: text("picture"),
+        }),
+    }
+}
+
+fn to_response(binding: user_oauth_bindings::Model) -> OAuthBindingResponse {
+    OAuthBindingResponse {
+        provider: binding.provider,
+        email: binding.email,
+        display_name: binding.display_name,
+        avatar: binding.avatar,
+        created_at: binding.created_at.map(|t| t.to_utc()),
+    }
+}
+
+/// PKCE S256：`BASE64URL(SHA256(code_verifier))`
+fn pkce_challenge(code_verifier: &str) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// 32 字节随机数的 base64url 编码，用作 state 与 code_verifier
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn external_error(provider: &str, e: reqwest::Error) -> AppError {
+    log::error!("{provider} 请求失败: {e}");
+    AppError::ExternalServiceError(format!("{provider} 服务暂时不可用"))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn pkce_challenge_matches_rfc7636() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn parse_provider_identities() {
+        let github =
+            json!({ "id": 42, "login": "octocat", "email": null, "avatar_url": "https://a/b.png" });
+        assert_eq!(
+            parse_identity("github", &github),
+            Some(ExternalIdentity {
+                subject: "42".to_string(),
+                user_name: Some("octocat".to_string()),
+                email: None,
+                avatar: Some("https://a/b.png".to_string()),
+            })
+        );
+
+        let oidc = json!({ "sub": "abc", "name": "Alice", "email": "a@example.com", "email_verified": false });
+        let identity = parse_identity("oidc", &oidc).unwrap();
+        assert_eq!(identity.subject, "abc");
+        assert_eq!(identity.user_name.as_deref(), Some("Alice"));
+        assert_eq!(identity.email, None);
+        assert_eq!(parse_identity("oidc", &json!({ "name": "x" })), None);
+    }
+}
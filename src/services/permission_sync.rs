@@ -0,0 +1,199 @@
+use crate::config::AppError;
+use crate::get_all_routes;
+use crate::models::{permissions, role_permissions, roles};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    Set, TransactionTrait,
+};
+use std::collections::{BTreeSet, HashMap};
+
+/// 同步结果
+#[derive(Debug, Default)]
+pub struct PermissionSyncReport {
+    /// 新插入 permissions 表的权限码
+    pub inserted: Vec<String>,
+    /// 补全了分类的已有权限码
+    pub updated: Vec<String>,
+    /// 表中存在、但已没有任何路由使用的权限码
+    pub orphaned: Vec<String>,
+    /// 自动授予配置角色的权限数量
+    pub granted: usize,
+}
+
+/// 把 `#[route_permission]` / `crud_entity!` 声明的权限码同步到 permissions 表
+pub struct PermissionSyncService;
+
+impl PermissionSyncService {
+    /// 执行同步。调用前需先执行 `init_route_registry`。
+    ///
+    /// 已存在的权限只补全缺失的分类，不覆盖人工维护的名称和描述；
+    /// 孤立的权限码只做报告，不会删除。
+    pub async fn sync(
+        db: &DatabaseConnection,
+        auto_grant_role: Option<&str>,
+    ) -> Result<PermissionSyncReport, AppError> {
+        let codes: BTreeSet<String> = get_all_routes()?
+            .into_iter()
+            .filter(|route| !route.permission.is_empty())
+            .map(|route| route.permission.to_string())
+            .collect();
+
+        let existing: HashMap<String, permissions::Model> = permissions::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| (p.code.clone(), p))
+            .collect();
+
+        let mut report = PermissionSyncReport::default();
+        let txn = db.begin().await?;
+        let mut new_ids = Vec::new();
+        for code in &codes {
+            let (name, category) = derive_code_meta(code);
+            match existing.get(code) {
+                Some(perm) if perm.category.is_none() => {
+                    let mut perm = perm.clone().into_active_model();
+                    perm.category = Set(Some(category));
+                    perm.update(&txn).await?;
+                    report.updated.push(code.clone());
+                }
+                Some(_) => {}
+                None => {
+                    let perm = permissions::ActiveModel {
+                        code: Set(code.clone()),
+                        name: Set(name),
+                        description: Set(Some("由路由声明自动同步".to_string())),
+                        category: Set(Some(category)),
+                        ..Default::default()
+                    }
+                    .insert(&txn)
+                    .await?;
+                    new_ids.push(perm.id);
+                    report.inserted.push(code.clone());
+                }
+            }
+        }
+
+        // 新增的权限码自动授予配置的角色
+        if let Some(role_code) = auto_grant_role
+            && !new_ids.is_empty()
+        {
+            let role = roles::Entity::find()
+                .filter(roles::Column::Code.eq(role_code))
+                .one(&txn)
+                .await?;
+            match role {
+                Some(role) => {
+                    for permission_id in new_ids {
+                        role_permissions::ActiveModel {
+                            role_id: Set(Some(role.id)),
+                            permission_id: Set(Some(permission_id)),
+                            created_at: Set(Some(Utc::now().into())),
+                            ..Default::default()
+                        }
+                        .insert(&txn)
+                        .await?;
+                        report.granted += 1;
+                    }
+                }
+                None => log::warn!("自动授权角色 {role_code} 不存在，已跳过"),
+            }
+        }
+        txn.commit().await?;
+
+        report.orphaned = existing
+            .keys()
+            .filter(|code| !codes.contains(*code))
+            .cloned()
+            .collect();
+        report.orphaned.sort();
+        Ok(report)
+    }
+
+    /// 打印同步结果
+    pub fn log_report(report: &PermissionSyncReport) {
+        log::info!(
+            "权限同步完成: 新增 {} 个, 补全分类 {} 个, 自动授权 {} 个",
+            report.inserted.len(),
+            report.updated.len(),
+            report.granted
+        );
+        for code in &report.inserted {
+            log::info!("  + {code}");
+        }
+        if !report.orphaned.is_empty() {
+            log::warn!(
+                "以下 {} 个权限码没有路由使用: {}",
+                report.orphaned.len(),
+                report.orphaned.join(", ")
+            );
+        }
+    }
+}
+
+/// 由权限码推导显示名称与分类。
+///
+/// 先去掉 `crud_entity!` 生成的 `get::` 之类的方法前缀，第一段作为分类，
+/// 例如 `get::tags:read::list` → (`tags 查看列表`, `tags`)，`post:update:own` → (`post 更新(own)`, `post`)。
+fn derive_code_meta(code: &str) -> (String, String) {
+    let code = code.split_once("::").map_or(code, |(prefix, rest)| {
+        if ["get", "post", "put", "delete", "patch"].contains(&prefix) {
+            rest
+        } else {
+            code
+        }
+    });
+    let mut parts = code.split(':').filter(|s| !s.is_empty());
+    let category = parts.next().unwrap_or(code).to_string();
+
+    let mut name = category.clone();
+    let mut action = String::new();
+    let mut scopes = Vec::new();
+    for part in parts {
+        match part {
+            "read" => action.push_str("查看"),
+            "create" => action.push_str("创建"),
+            "update" => action.push_str("更新"),
+            "delete" => action.push_str("删除"),
+            "list" => action.push_str("列表"),
+            "upload" => action.push_str("上传"),
+            // crud_entity! 生成的 `delete:id` 中的 id 不体现在名称里
+            "id" => {}
+            other => scopes.push(other),
+        }
+    }
+    if !action.is_empty() {
+        name.push(' ');
+        name.push_str(&action);
+    }
+    if !scopes.is_empty() {
+        name.push_str(&format!("({})", scopes.join(":")));
+    }
+    (name, category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::derive_code_meta;
+
+    #[test]
+    fn derive_name_and_category_from_code() {
+        assert_eq!(
+            derive_code_meta("get::tags:read::list"),
+            ("tags 查看列表".to_string(), "tags".to_string())
+        );
+        assert_eq!(
+            derive_code_meta("tags:delete:id"),
+            ("tags 删除".to_string(), "tags".to_string())
+        );
+        assert_eq!(
+            derive_code_meta("post:update:own"),
+            ("post 更新(own)".to_string(), "post".to_string())
+        );
+        assert_eq!(
+            derive_code_meta("system:rbac"),
+            ("system(rbac)".to_string(), "system".to_string())
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! 文章修订记录：每次更新文章前保存旧版本，支持查看、比较与恢复。
+//!
+//! 修订记录的 `editor_id` 与 `created_at` 是覆盖该版本的那次修改的操作人与时间。
+
+use crate::config::AppError;
+use crate::dto::post_revision::{PostRevisionDiff, PostRevisionResponse, PostRevisionSummary};
+use crate::dto::posts::{PostResponse, UpdatePostRequest};
+use crate::dto::{PaginatedResp, Pagination};
+use crate::models::{categories, post_revisions, post_tags, posts, tags};
+use crate::services::posts::PostService;
+use crate::utils::text_diff::line_diff;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set,
+};
+
+/// 文章修订记录服务
+pub struct PostRevisionService;
+
+impl PostRevisionService {
+    /// 保存文章当前版本（含分类与标签），在更新文章的事务中调用
+    pub async fn snapshot<C: ConnectionTrait>(
+        conn: &C,
+        post: &posts::Model,
+        editor_id: i32,
+    ) -> Result<(), AppError> {
+        let tag_ids: Vec<i32> = post_tags::Entity::find()
+            .filter(post_tags::Column::PostId.eq(post.id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|post_tag| post_tag.tag_id)
+            .collect();
+        post_revisions::ActiveModel {
+            post_id: Set(post.id),
+            title: Set(post.title.clone()),
+            summary: Set(post.summary.clone()),
+            content: Set(post.content.clone()),
+            markdowncontent: Set(post.markdowncontent.clone()),
+            category_id: Set(Some(post.category_id)),
+            tag_ids: Set(serde_json::json!(tag_ids)),
+            editor_id: Set(Some(editor_id)),
+            created_at: Set(Some(Utc::now().into())),
+            ..Default::default()
+        }
+        .insert(conn)
+        .await
+        .map_err(|e| {
+            log::error!("保存文章修订记录失败: {e}");
+            AppError::DatabaseError("保存文章修订记录失败".to_string())
+        })?;
+        Ok(())
+    }
+
+    /// 分页列出文章的修订记录，最新的在前
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: i32,
+        uuid: &str,
+        page: u64,
+        limit: u64,
+    ) -> Result<PaginatedResp<PostRevisionSummary>, AppError> {
+        let post = find_own_post(db, user_id, uuid).await?;
+        let paginator = post_revisions::Entity::find()
+            .filter(post_revisions::Column::PostId.eq(post.id))
+            .order_by_desc(post_revisions::Column::CreatedAt)
+            .order_by_desc(post_revisions::Column::Id)
+            .paginate(db, limit);
+        let total = paginator.num_items().await?;
+        let revisions = paginator.fetch_page(page.saturating_sub(1)).await?;
+        let editors =
+            PostService::find_authors(db, revisions.iter().filter_map(|r| r.editor_id)).await?;
+        let data = revisions
+            .into_iter()
+            .map(|revision| PostRevisionSummary {
+                id: revision.id,
+                title: revision.title,
+                editor: revision
+                    .editor_id
+                    .map(|id| PostService::author_of(&editors, id).0),
+                created_at: revision.created_at.map(|t| t.to_utc()),
+            })
+            .collect();
+        Ok(PaginatedResp {
+            data,
+            pagination: Pagination { page, limit, total },
+        })
+    }
+
+    /// 查看某个修订版本
+    pub async fn get(
+        db: &DatabaseConnection,
+        user_id: i32,
+        uuid: &str,
+        revision_id: i32,
+    ) -> Result<PostRevisionResponse, AppError> {
+        let post = find_own_post(db, user_id, uuid).await?;
+        let revision = find_revision(db, post.id, revision_id).await?;
+        let editor = match revision.editor_id {
+            Some(editor_id) => Some(PostService::find_author(db, editor_id).await?.0),
+            None => None,
+        };
+        Ok(PostRevisionResponse::from_model(revision, editor))
+    }
+
+    /// 比较两个修订版本的标题与 Markdown 内容，`to` 为空时与文章当前内容比较
+    pub async fn diff(
+        db: &DatabaseConnection,
+        user_id: i32,
+        uuid: &str,
+        from: i32,
+        to: Option<i32>,
+    ) -> Result<PostRevisionDiff, AppError> {
+        let post = find_own_post(db, user_id, uuid).await?;
+        let old = find_revision(db, post.id, from).await?;
+        let (new_title, new_markdown) = match to {
+            Some(to) => {
+                let new = find_revision(db, post.id, to).await?;
+                (new.title, new.markdowncontent)
+            }
+            None => (post.title, post.markdowncontent),
+        };
+        Ok(PostRevisionDiff {
+            from,
+            to,
+            title: line_diff(&old.title, &new_title),
+            markdowncontent: line_diff(&old.markdowncontent, &new_markdown),
+        })
+    }
+
+    /// 将文章恢复为某个修订版本，作为一次新的更新（当前版本同样会保存为修订记录）
+    pub async fn restore(
+        db: &DatabaseConnection,
+        user_id: i32,
+        uuid: &str,
+        revision_id: i32,
+    ) -> Result<PostResponse, AppError> {
+        let post = find_own_post(db, user_id, uuid).await?;
+        let revision = find_revision(db, post.id, revision_id).await?;
+        let tag_ids: Vec<i32> = serde_json::from_value(revision.tag_ids).unwrap_or_default();
+        // 修订之后被删除的标签不再恢复
+        let tag_ids = if tag_ids.is_empty() {
+            tag_ids
+        } else {
+            tags::Entity::find()
+                .filter(tags::Column::Id.is_in(tag_ids))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|tag| tag.id)
+                .collect()
+        };
+        // 文章必须有分类，修订之后被删除的分类不再恢复，保留当前分类
+        let category_id = match revision.category_id {
+            Some(category_id) => categories::Entity::find_by_id(category_id)
+                .one(db)
+                .await?
+                .map(|category| category.id),
+            None => None,
+        };
+        let request = UpdatePostRequest {
+            title: Some(revision.title),
+            slug: None,
+            // 空字符串表示清空，修订版本没有摘要时恢复后同样没有摘要
+            summary: Some(revision.summary.unwrap_or_default()),
+            content: None,
+            markdowncontent: Some(revision.markdowncontent),
+            cover_image: None,
+            category_id,
+            tag_ids: Some(tag_ids),
+            status: None,
+            published_at: None,
+            featured: None,
+        };
+        PostService::update_post(db, user_id, uuid, request).await
+    }
+}
+
+/// 查询文章，只有作者可以查看与恢复修订记录
+async fn find_own_post(
+    db: &DatabaseConnection,
+    user_id: i32,
+    uuid: &str,
+) -> Result<posts::Model, AppError> {
+    let post = posts::Entity::find_by_key(uuid)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("文章不存在".to_string()))?;
+    if post.author_id != user_id {
+        return Err(AppError::Unauthorized(
+            "没有权限查看此文章的修订记录".to_string(),
+        ));
+    }
+    Ok(post)
+}
+
+async fn find_revision(
+    db: &DatabaseConnection,
+    post_id: i32,
+    revision_id: i32,
+) -> Result<post_revisions::Model, AppError> {
+    post_revisions::Entity::find_by_id(revision_id)
+        .filter(post_revisions::Column::PostId.eq(post_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("修订记录不存在".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::posts::PostStatus;
+    use crate::services::post_search::PostSearchService;
+    use crate::services::test_db::{create_user, setup_test_db};
+    use sea_orm::ConnectionTrait;
+    use uuid::Uuid;
+
+    // 文章相关的表，字段与 src/sql/posts.sql 一致
+    const POST_TABLES: &[&str] = &[
+        "CREATE TABLE categories (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            slug TEXT NOT NULL,
+            description TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        "CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        "CREATE TABLE posts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            uuid TEXT NOT NULL UNIQUE,
+            slug TEXT NOT NULL UNIQUE,
+            author_id INTEGER NOT NULL,
+            category_id INTEGER,
+            title TEXT NOT NULL,
+            summary TEXT,
+            content TEXT NOT NULL,
+            markdowncontent TEXT NOT NULL,
+            cover_image TEXT,
+            status INTEGER NOT NULL DEFAULT 0,
+            featured BOOLEAN NOT NULL DEFAULT FALSE,
+            size INTEGER NOT NULL DEFAULT 0,
+            reading_minutes INTEGER NOT NULL DEFAULT 0,
+            toc TEXT NOT NULL DEFAULT '[]',
+            view_count INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            published_at TEXT
+        )",
+        "CREATE TABLE post_tags (
+            post_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (post_id, tag_id)
+        )",
+        "CREATE TABLE post_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            post_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            summary TEXT,
+            content TEXT NOT NULL,
+            markdowncontent TEXT NOT NULL,
+            category_id INTEGER,
+            tag_ids TEXT NOT NULL DEFAULT '[]',
+            editor_id INTEGER,
+            created_at TEXT
+        )",
+        "CREATE TABLE post_slug_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            post_id INTEGER NOT NULL,
+            slug TEXT NOT NULL UNIQUE,
+            created_at TEXT
+        )",
+    ];
+
+    async fn create_category(db: &DatabaseConnection, name: &str) -> i32 {
+        categories::ActiveModel {
+            name: Set(name.to_string()),
+            slug: Set(name.to_lowercase()),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    fn update_request(summary: &str, category_id: i32) -> UpdatePostRequest {
+        UpdatePostRequest {
+            title: None,
+            slug: None,
+            summary: Some(summary.to_string()),
+            content: None,
+            markdowncontent: None,
+            cover_image: None,
+            category_id: Some(category_id),
+            tag_ids: None,
+            status: None,
+            published_at: None,
+            featured: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn restore_clears_summary_missing_from_revision() {
+        let db = setup_test_db().await;
+        for sql in POST_TABLES {
+            db.execute_unprepared(sql).await.unwrap();
+        }
+        PostSearchService::ensure_index(&db).await.unwrap();
+        let author = create_user(&db, "reviser").await;
+        let rust = create_category(&db, "Rust").await;
+        let go = create_category(&db, "Go").await;
+        let post = posts::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            slug: Set("draft".to_string()),
+            author_id: Set(author.id),
+            category_id: Set(rust),
+            title: Set("草稿".to_string()),
+            summary: Set(None),
+            content: Set("<p>正文</p>".to_string()),
+            markdowncontent: Set("正文".to_string()),
+            status: Set(PostStatus::Draft),
+            featured: Set(false),
+            view_count: Set(0),
+            size: Set(2),
+            reading_minutes: Set(1),
+            toc: Set(serde_json::json!([])),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        // 第一次修改保存了没有摘要的原始版本
+        PostService::update_post(&db, author.id, &post.uuid, update_request("新摘要", go))
+            .await
+            .unwrap();
+        let original = post_revisions::Entity::find()
+            .filter(post_revisions::Column::PostId.eq(post.id))
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(original.summary, None);
+
+        let restored = PostRevisionService::restore(&db, author.id, &post.uuid, original.id)
+            .await
+            .unwrap();
+        assert_eq!(restored.description, "");
+        assert_eq!(restored.category.map(|category| category.id), Some(rust));
+        let post = posts::Entity::find_by_id(post.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(post.summary, None);
+        assert_eq!(post.category_id, rust);
+
+        // 修订之后被删除的分类不再恢复
+        PostService::update_post(&db, author.id, &post.uuid, update_request("摘要", go))
+            .await
+            .unwrap();
+        categories::Entity::delete_by_id(rust)
+            .exec(&db)
+            .await
+            .unwrap();
+        let restored = PostRevisionService::restore(&db, author.id, &post.uuid, original.id)
+            .await
+            .unwrap();
+        assert_eq!(restored.category.map(|category| category.id), Some(go));
+    }
+}
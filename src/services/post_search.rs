@@ -0,0 +1,498 @@
+//! 文章全文搜索
+//!
+//! - PostgreSQL：`post_search` 表保存分词后的标题、摘要与正文，`document` 列由三者按 A/B/C 加权生成 tsvector，
+//!   使用 GIN 索引，按 `ts_rank` 排序；
+//! - SQLite（开发环境）：同名的 FTS5 虚拟表，`rowid` 即文章ID，按 `bm25` 排序。
+//!
+//! 两种数据库内置的分词器都不会切分中文，因此入库与查询前由服务端先行分词：
+//! 连续的中日韩文字按相邻两字切分，入库时另外保留每个单字，使单字关键词（如「鱼」）也能命中「鲨鱼」；
+//! 其他文字按单词小写，关键词须全部命中。
+//! 高亮片段同样由服务端生成，两种数据库的结果一致。
+
+use crate::config::AppError;
+use crate::dto::posts::{PostSearchQuery, PostSearchResult, SearchHighlight};
+use crate::dto::{PaginatedResp, Pagination};
+use crate::models::{categories, post_tags, posts};
+use crate::services::posts::PostService;
+use crate::utils::markdown::{self, is_cjk};
+use sea_orm::sea_query::{Query, SimpleExpr};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, Order,
+    PaginatorTrait, QueryFilter, QueryOrder, Statement, prelude::Expr,
+};
+
+/// 正文高亮片段的长度（字符数）
+const SNIPPET_CHARS: usize = 120;
+
+/// 片段中第一处命中之前保留的字符数
+const SNIPPET_LEADING_CHARS: usize = 20;
+
+/// 重建索引时每批处理的文章数
+const REINDEX_BATCH: u64 = 100;
+
+/// 文章全文搜索服务
+pub struct PostSearchService;
+
+impl PostSearchService {
+    /// 创建搜索索引表；PostgreSQL 的 `post_search` 表随建表脚本创建，这里只处理 SQLite
+    pub async fn ensure_index(db: &DatabaseConnection) -> Result<(), AppError> {
+        if db.get_database_backend() != DbBackend::Sqlite {
+            return Ok(());
+        }
+        db.execute_unprepared(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS post_search USING fts5(title, summary, body)",
+        )
+        .await
+        .map_err(|e| {
+            log::error!("创建搜索索引失败: {e}");
+            AppError::SearchServiceError("创建搜索索引失败".to_string())
+        })?;
+        Ok(())
+    }
+
+    /// 写入或更新文章的搜索索引，在创建、更新文章的事务中调用
+    pub async fn index_post<C: ConnectionTrait>(
+        conn: &C,
+        post: &posts::Model,
+    ) -> Result<(), AppError> {
+        let backend = conn.get_database_backend();
+        let title = index_text(&post.title);
+        let summary = index_text(post.summary.as_deref().unwrap_or_default());
+        let body = index_text(&markdown::plain_text(&post.markdowncontent));
+        let statements = match backend {
+            DbBackend::Postgres => vec![Statement::from_sql_and_values(
+                backend,
+                "INSERT INTO post_search (post_id, title, summary, body) VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT (post_id) DO UPDATE \
+                 SET title = EXCLUDED.title, summary = EXCLUDED.summary, body = EXCLUDED.body",
+                [post.id.into(), title.into(), summary.into(), body.into()],
+            )],
+            DbBackend::Sqlite => vec![
+                Statement::from_sql_and_values(
+                    backend,
+                    "DELETE FROM post_search WHERE rowid = ?",
+                    [post.id.into()],
+                ),
+                Statement::from_sql_and_values(
+                    backend,
+                    "INSERT INTO post_search (rowid, title, summary, body) VALUES (?, ?, ?, ?)",
+                    [post.id.into(), title.into(), summary.into(), body.into()],
+                ),
+            ],
+            DbBackend::MySql => return Ok(()),
+        };
+        for statement in statements {
+            conn.execute(statement).await.map_err(|e| {
+                log::error!("更新文章 {} 的搜索索引失败: {e}", post.id);
+                AppError::SearchServiceError("更新搜索索引失败".to_string())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// 删除文章的搜索索引（PostgreSQL 中随文章级联删除，这里一并处理）
+    pub async fn remove<C: ConnectionTrait>(conn: &C, post_id: i32) -> Result<(), AppError> {
+        let backend = conn.get_database_backend();
+        let sql = match backend {
+            DbBackend::Postgres => "DELETE FROM post_search WHERE post_id = $1",
+            DbBackend::Sqlite => "DELETE FROM post_search WHERE rowid = ?",
+            DbBackend::MySql => return Ok(()),
+        };
+        conn.execute(Statement::from_sql_and_values(
+            backend,
+            sql,
+            [post_id.into()],
+        ))
+        .await
+        .map_err(|e| {
+            log::error!("删除文章 {post_id} 的搜索索引失败: {e}");
+            AppError::SearchServiceError("删除搜索索引失败".to_string())
+        })?;
+        Ok(())
+    }
+
+    /// 为所有文章重建搜索索引，返回处理的文章数
+    pub async fn reindex_all(db: &DatabaseConnection) -> Result<u64, AppError> {
+        let mut pages = posts::Entity::find()
+            .order_by_asc(posts::Column::Id)
+            .paginate(db, REINDEX_BATCH);
+        let mut count = 0;
+        while let Some(batch) = pages.fetch_and_next().await? {
+            for post in &batch {
+                Self::index_post(db, post).await?;
+            }
+            count += batch.len() as u64;
+        }
+        Ok(count)
+    }
+
+    /// 按相关度分页搜索文章，可见范围与文章列表一致
+    pub async fn search(
+        db: &DatabaseConnection,
+        viewer_id: Option<i32>,
+        query: PostSearchQuery,
+    ) -> Result<PaginatedResp<PostSearchResult>, AppError> {
+        let terms = tokenize(&query.q);
+        if terms.is_empty() {
+            return Err(AppError::BadRequest("搜索关键词不能为空".to_string()));
+        }
+        let (matched, rank, order) = match_exprs(db.get_database_backend(), &terms)?;
+
+        let mut select = posts::Entity::find()
+            .filter(posts::Entity::visible_to(viewer_id))
+            .filter(matched)
+            .find_also_related(categories::Entity);
+        if let Some(category_id) = query.category {
+            select = select.filter(posts::Column::CategoryId.eq(category_id));
+        }
+        if let Some(tag_id) = query.tag {
+            // 用子查询而不是连接，避免 DISTINCT 与按相关度排序冲突
+            select = select.filter(
+                posts::Column::Id.in_subquery(
+                    Query::select()
+                        .column(post_tags::Column::PostId)
+                        .from(post_tags::Entity)
+                        .and_where(post_tags::Column::TagId.eq(tag_id))
+                        .to_owned(),
+                ),
+            );
+        }
+        if let Some(status) = query.status {
+            select = select.filter(posts::Column::Status.eq(status));
+        }
+        let paginator = select
+            .order_by(rank, order)
+            .order_by_desc(posts::Column::CreatedAt)
+            .paginate(db, query.limit);
+
+        let search_error = |e: sea_orm::DbErr| {
+            log::error!("搜索文章失败: {e}");
+            AppError::SearchServiceError("搜索失败，请稍后重试".to_string())
+        };
+        let total = paginator.num_items().await.map_err(search_error)?;
+        let posts_with_categories = paginator
+            .fetch_page(query.page.saturating_sub(1))
+            .await
+            .map_err(search_error)?;
+
+        let highlights: Vec<SearchHighlight> = posts_with_categories
+            .iter()
+            .map(|(post, _)| SearchHighlight {
+                title: highlight(&post.title, &terms),
+                summary: post.summary.as_deref().map(|s| highlight(s, &terms)),
+                content: snippet(&markdown::plain_text(&post.markdowncontent), &terms),
+            })
+            .collect();
+        let data = PostService::list_responses(db, posts_with_categories)
+            .await?
+            .into_iter()
+            .zip(highlights)
+            .map(|(post, highlight)| PostSearchResult { post, highlight })
+            .collect();
+        Ok(PaginatedResp {
+            data,
+            pagination: Pagination {
+                page: query.page,
+                limit: query.limit,
+                total,
+            },
+        })
+    }
+}
+
+/// 按数据库生成匹配条件与相关度表达式
+fn match_exprs(
+    backend: DbBackend,
+    terms: &[String],
+) -> Result<(SimpleExpr, SimpleExpr, Order), AppError> {
+    match backend {
+        DbBackend::Postgres => {
+            let ts_query = ts_query(terms);
+            Ok((
+                Expr::cust_with_values(
+                    r#""posts"."id" IN (SELECT post_id FROM post_search WHERE document @@ to_tsquery('simple', $1))"#,
+                    [ts_query.clone()],
+                ),
+                Expr::cust_with_values(
+                    r#"(SELECT ts_rank(document, to_tsquery('simple', $1)) FROM post_search WHERE post_id = "posts"."id")"#,
+                    [ts_query],
+                ),
+                Order::Desc,
+            ))
+        }
+        DbBackend::Sqlite => {
+            let fts_query = fts5_query(terms);
+            Ok((
+                Expr::cust_with_values(
+                    r#""posts"."id" IN (SELECT rowid FROM post_search WHERE post_search MATCH ?)"#,
+                    [fts_query.clone()],
+                ),
+                // bm25 越小越相关，标题、摘要、正文的权重依次递减
+                Expr::cust_with_values(
+                    r#"(SELECT bm25(post_search, 10.0, 5.0, 1.0) FROM post_search WHERE post_search MATCH ? AND rowid = "posts"."id")"#,
+                    [fts_query],
+                ),
+                Order::Asc,
+            ))
+        }
+        DbBackend::MySql => Err(AppError::SearchServiceError(
+            "当前数据库不支持全文搜索".to_string(),
+        )),
+    }
+}
+
+/// PostgreSQL 查询：词元只含字母、数字与中日韩文字，无需转义
+fn ts_query(terms: &[String]) -> String {
+    terms.join(" & ")
+}
+
+/// FTS5 查询：每个词元加引号作为短语，避免被解析为查询语法
+fn fts5_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{term}\""))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// 大小写不敏感比较时使用的字符，逐字转换以保持下标一一对应
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// 分词：中日韩文字按相邻两字切分（单字保留），其他字母数字按单词小写，其余字符作为分隔
+pub fn tokenize(text: &str) -> Vec<String> {
+    split_terms(text, false)
+}
+
+/// `with_unigrams` 为 true 时，连续的中日韩文字除两字切分外还输出每个单字，供单字查询命中
+fn split_terms(text: &str, with_unigrams: bool) -> Vec<String> {
+    let flush_cjk = |run: &mut Vec<char>, terms: &mut Vec<String>| {
+        match run.len() {
+            0 => {}
+            1 => terms.push(run[0].to_string()),
+            _ => {
+                terms.extend(run.windows(2).map(|pair| pair.iter().collect::<String>()));
+                if with_unigrams {
+                    terms.extend(run.iter().map(char::to_string));
+                }
+            }
+        }
+        run.clear();
+    };
+    fn flush_word(word: &mut String, terms: &mut Vec<String>) {
+        if !word.is_empty() {
+            terms.push(std::mem::take(word));
+        }
+    }
+
+    let mut terms = Vec::new();
+    let mut run = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut terms);
+            run.push(c);
+        } else if c.is_alphanumeric() {
+            flush_cjk(&mut run, &mut terms);
+            word.push(fold(c));
+        } else {
+            flush_cjk(&mut run, &mut terms);
+            flush_word(&mut word, &mut terms);
+        }
+    }
+    flush_cjk(&mut run, &mut terms);
+    flush_word(&mut word, &mut terms);
+    terms
+}
+
+/// 写入索引的文本：分词结果（含中日韩单字）以空格连接
+fn index_text(text: &str) -> String {
+    split_terms(text, true).join(" ")
+}
+
+/// 找出所有命中的字符区间，合并重叠或相邻的区间
+fn match_ranges(chars: &[char], terms: &[String]) -> Vec<(usize, usize)> {
+    let folded: Vec<char> = chars.iter().map(|&c| fold(c)).collect();
+    let mut ranges = Vec::new();
+    for term in terms {
+        let term: Vec<char> = term.chars().collect();
+        if term.is_empty() || term.len() > folded.len() {
+            continue;
+        }
+        for start in 0..=folded.len() - term.len() {
+            if folded[start..start + term.len()] == term[..] {
+                ranges.push((start, start + term.len()));
+            }
+        }
+    }
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn escape_into(out: &mut String, chars: &[char]) {
+    for &c in chars {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// 转义 `chars[from..to]` 并用 `<mark>` 包裹命中的区间
+fn render_marks(chars: &[char], ranges: &[(usize, usize)], from: usize, to: usize) -> String {
+    let mut out = String::new();
+    let mut pos = from;
+    for &(start, end) in ranges {
+        let (start, end) = (start.max(from), end.min(to));
+        if start >= end {
+            continue;
+        }
+        escape_into(&mut out, &chars[pos..start]);
+        out.push_str("<mark>");
+        escape_into(&mut out, &chars[start..end]);
+        out.push_str("</mark>");
+        pos = end;
+    }
+    escape_into(&mut out, &chars[pos..to]);
+    out
+}
+
+/// 整段高亮
+fn highlight(text: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let ranges = match_ranges(&chars, terms);
+    render_marks(&chars, &ranges, 0, chars.len())
+}
+
+/// 截取第一处命中附近的片段并高亮，未命中时为空
+fn snippet(text: &str, terms: &[String]) -> Option<String> {
+    let chars: Vec<char> = text
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+    let ranges = match_ranges(&chars, terms);
+    let first = ranges.first()?;
+    let start = first.0.saturating_sub(SNIPPET_LEADING_CHARS);
+    let end = (start + SNIPPET_CHARS).min(chars.len());
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    out.push_str(&render_marks(&chars, &ranges, start, end));
+    if end < chars.len() {
+        out.push('…');
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Database;
+
+    #[test]
+    fn tokenize_splits_cjk_into_bigrams() {
+        assert_eq!(
+            tokenize("Rust 系统编程，入门!"),
+            ["rust", "系统", "统编", "编程", "入门"]
+        );
+        assert_eq!(tokenize("学 C++"), ["学", "c"]);
+        assert!(tokenize("  ，。!").is_empty());
+        assert_eq!(index_text("鲨鱼 go"), "鲨鱼 鲨 鱼 go");
+    }
+
+    #[test]
+    fn highlight_escapes_and_marks() {
+        let terms = tokenize("rust 编程");
+        assert_eq!(
+            highlight("<Rust> 系统编程", &terms),
+            "&lt;<mark>Rust</mark>&gt; 系统<mark>编程</mark>"
+        );
+        let text = format!("{}Rust 编程", "前文".repeat(40));
+        let snippet = snippet(&text, &terms).unwrap();
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.contains("<mark>Rust</mark> <mark>编程</mark>"));
+        assert_eq!(super::snippet("无关内容", &terms), None);
+    }
+
+    #[tokio::test]
+    async fn sqlite_fts_ranks_title_matches_first() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        PostSearchService::ensure_index(&db).await.unwrap();
+        for (id, title, body) in [
+            (1, "随笔", "今天学习了 Rust 系统编程"),
+            (2, "Rust 系统编程入门", "从所有权讲起"),
+            (3, "Go 并发", "goroutine 与 channel"),
+        ] {
+            db.execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO post_search (rowid, title, summary, body) VALUES (?, ?, '', ?)",
+                [id.into(), index_text(title).into(), index_text(body).into()],
+            ))
+            .await
+            .unwrap();
+        }
+        let rows = db
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT rowid FROM post_search WHERE post_search MATCH ? \
+                 ORDER BY bm25(post_search, 10.0, 5.0, 1.0)",
+                [fts5_query(&tokenize("系统编程 rust")).into()],
+            ))
+            .await
+            .unwrap();
+        let ids: Vec<i32> = rows
+            .iter()
+            .map(|row| row.try_get_by_index(0).unwrap())
+            .collect();
+        assert_eq!(ids, [2, 1]);
+    }
+
+    #[tokio::test]
+    async fn sqlite_fts_matches_single_cjk_character() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        PostSearchService::ensure_index(&db).await.unwrap();
+        for (id, title) in [(1, "鲨鱼"), (2, "鱼"), (3, "大海")] {
+            db.execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO post_search (rowid, title, summary, body) VALUES (?, ?, '', '')",
+                [id.into(), index_text(title).into()],
+            ))
+            .await
+            .unwrap();
+        }
+        let search = |query: &str| {
+            let db = &db;
+            let fts_query = fts5_query(&tokenize(query));
+            async move {
+                let rows = db
+                    .query_all(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "SELECT rowid FROM post_search WHERE post_search MATCH ? ORDER BY rowid",
+                        [fts_query.into()],
+                    ))
+                    .await
+                    .unwrap();
+                rows.iter()
+                    .map(|row| row.try_get_by_index(0).unwrap())
+                    .collect::<Vec<i32>>()
+            }
+        };
+        assert_eq!(search("鱼").await, [1, 2]);
+        assert_eq!(search("鲨鱼").await, [1]);
+        assert_eq!(search("海").await, [3]);
+        assert_eq!(highlight("鲨鱼", &tokenize("鱼")), "鲨<mark>鱼</mark>");
+    }
+}
@@ -1,21 +1,335 @@
 use crate::config::AppError;
+use crate::config::manager::CONFIG;
 use crate::dto::posts::{
+    CategoryResponse,
     CreatePostRequest,
     PostResponse,
+    TagResponse,
     UpdatePostRequest,
     // UpdatePostRequest
 };
-use crate::models::{categories, post_tags, posts, tags, users};
-use chrono::Utc;
+use crate::models::posts::PostStatus;
+use crate::models::{categories, post_slug_history, post_tags, posts, tags, users};
+use crate::services::post_revision::PostRevisionService;
+use crate::services::post_search::PostSearchService;
+use crate::utils::{markdown, slug};
+use chrono::{DateTime, Utc};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
-    TransactionTrait,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait, prelude::Expr,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use uuid::Uuid;
 
+/// 文章配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostSettings {
+    /// 定时发布任务的检查间隔（秒）
+    pub scheduler_interval: u64,
+}
+
 pub struct PostService;
 
+/// 作者被删除时显示的名称
+pub const UNKNOWN_AUTHOR: &str = "未知作者";
+
+/// 升级已有文章时每批处理的数量
+const UPGRADE_BATCH: u64 = 100;
+
 impl PostService {
+    /// 查询文章作者的用户名与头像
+    pub async fn find_author(
+        db: &DatabaseConnection,
+        author_id: i32,
+    ) -> Result<(String, Option<String>), AppError> {
+        let author = users::Entity::find_by_id(author_id)
+            .one(db)
+            .await
+            .map_err(|e| {
+                log::error!("查询作者信息失败: {e}");
+                AppError::DatabaseError("查询作者信息失败".to_string())
+            })?;
+        Ok(author.map_or_else(
+            || (UNKNOWN_AUTHOR.to_string(), None),
+            |user| (user.user_name, user.image),
+        ))
+    }
+
+    /// 批量查询文章作者（避免N+1查询），返回 作者ID → (用户名, 头像)
+    pub async fn find_authors(
+        db: &DatabaseConnection,
+        author_ids: impl IntoIterator<Item = i32>,
+    ) -> Result<HashMap<i32, (String, Option<String>)>, AppError> {
+        let author_ids: HashSet<i32> = author_ids.into_iter().collect();
+        if author_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let authors = users::Entity::find()
+            .filter(users::Column::Id.is_in(author_ids))
+            .all(db)
+            .await
+            .map_err(|e| {
+                log::error!("查询作者信息失败: {e}");
+                AppError::DatabaseError("查询作者信息失败".to_string())
+            })?;
+        Ok(authors
+            .into_iter()
+            .map(|user| (user.id, (user.user_name, user.image)))
+            .collect())
+    }
+
+    /// 从 `find_authors` 的结果中取出作者，找不到时显示为未知作者
+    pub fn author_of(
+        authors: &HashMap<i32, (String, Option<String>)>,
+        author_id: i32,
+    ) -> (String, Option<String>) {
+        authors
+            .get(&author_id)
+            .cloned()
+            .unwrap_or_else(|| (UNKNOWN_AUTHOR.to_string(), None))
+    }
+
+    /// slug 是否已被其他文章使用，其他文章曾用的 slug 也不能占用
+    async fn slug_taken(
+        db: &DatabaseConnection,
+        slug: &str,
+        post_id: Option<i32>,
+    ) -> Result<bool, AppError> {
+        let mut current = posts::Entity::find().filter(posts::Column::Slug.eq(slug));
+        let mut history =
+            post_slug_history::Entity::find().filter(post_slug_history::Column::Slug.eq(slug));
+        if let Some(post_id) = post_id {
+            current = current.filter(posts::Column::Id.ne(post_id));
+            history = history.filter(post_slug_history::Column::PostId.ne(post_id));
+        }
+        Ok(current.count(db).await? > 0 || history.count(db).await? > 0)
+    }
+
+    /// 由标题生成未被占用的 slug，重名时追加 `-2`、`-3`
+    async fn generate_slug(db: &DatabaseConnection, title: &str) -> Result<String, AppError> {
+        let base = slug::slugify(title);
+        let mut candidate = base.clone();
+        let mut n = 2;
+        while Self::slug_taken(db, &candidate, None).await? {
+            candidate = slug::with_suffix(&base, n);
+            n += 1;
+        }
+        Ok(candidate)
+    }
+
+    /// 升级已有文章，由 `reindex-posts` 子命令在执行 `src/sql/upgrade_posts.sql` 后调用：
+    /// 为没有 slug 的文章由标题生成 slug，加上 slug 的非空与唯一约束，
+    /// 再按当前规则重新渲染正文 HTML、字数、阅读时长与目录。返回生成 slug 的文章数
+    pub async fn upgrade_existing(db: &DatabaseConnection) -> Result<u64, AppError> {
+        let missing: Vec<(i32, String)> = posts::Entity::find()
+            .select_only()
+            .columns([posts::Column::Id, posts::Column::Title])
+            .filter(posts::Column::Slug.is_null())
+            .order_by_asc(posts::Column::Id)
+            .into_tuple()
+            .all(db)
+            .await?;
+        // 逐篇写入，后面的文章生成 slug 时能看到前面已占用的
+        for (id, title) in &missing {
+            let slug = Self::generate_slug(db, title).await?;
+            posts::Entity::update_many()
+                .col_expr(posts::Column::Slug, Expr::value(slug))
+                .filter(posts::Column::Id.eq(*id))
+                .exec(db)
+                .await?;
+        }
+        Self::ensure_slug_constraint(db).await?;
+
+        // 只更新渲染结果，不修改 updated_at
+        let mut pages = posts::Entity::find()
+            .order_by_asc(posts::Column::Id)
+            .paginate(db, UPGRADE_BATCH);
+        while let Some(batch) = pages.fetch_and_next().await? {
+            for post in batch {
+                let rendered = markdown::render_post(&post.markdowncontent);
+                posts::Entity::update_many()
+                    .col_expr(posts::Column::Content, Expr::value(rendered.html))
+                    .col_expr(posts::Column::Size, Expr::value(rendered.word_count as i32))
+                    .col_expr(
+                        posts::Column::ReadingMinutes,
+                        Expr::value(rendered.reading_minutes as i32),
+                    )
+                    .col_expr(
+                        posts::Column::Toc,
+                        Expr::value(serde_json::json!(rendered.toc)),
+                    )
+                    .filter(posts::Column::Id.eq(post.id))
+                    .exec(db)
+                    .await?;
+            }
+        }
+        Ok(missing.len() as u64)
+    }
+
+    /// 回填 slug 后加上非空与唯一约束，可重复执行
+    async fn ensure_slug_constraint(db: &DatabaseConnection) -> Result<(), AppError> {
+        if db.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+        db.execute_unprepared(
+            "ALTER TABLE posts ALTER COLUMN slug SET NOT NULL; \
+             DO $$ BEGIN \
+                 IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'posts_slug_key') THEN \
+                     ALTER TABLE posts ADD CONSTRAINT posts_slug_key UNIQUE (slug); \
+                 END IF; \
+             END $$",
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// 查询曾用 slug 对应文章的当前 slug，用于旧链接重定向
+    pub async fn find_moved_slug(
+        db: &DatabaseConnection,
+        slug: &str,
+        viewer_id: Option<i32>,
+    ) -> Result<Option<String>, AppError> {
+        let Some(entry) = post_slug_history::Entity::find()
+            .filter(post_slug_history::Column::Slug.eq(slug))
+            .one(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+        Ok(posts::Entity::find_by_id(entry.post_id)
+            .filter(posts::Entity::visible_to(viewer_id))
+            .one(db)
+            .await?
+            .map(|post| post.slug))
+    }
+
+    /// 将文章（及其分类）转换为列表响应，批量查询标签与作者（避免N+1查询）
+    pub async fn list_responses(
+        db: &DatabaseConnection,
+        posts_with_categories: Vec<(posts::Model, Option<categories::Model>)>,
+    ) -> Result<Vec<PostResponse>, AppError> {
+        // 收集文章ID用于批量查询标签
+        let post_ids: Vec<i32> = posts_with_categories
+            .iter()
+            .map(|(post, _)| post.id)
+            .collect();
+
+        // 批量查询所有文章的标签
+        let mut post_tags_map: HashMap<i32, Vec<TagResponse>> = HashMap::new();
+        if !post_ids.is_empty() {
+            // 通过中间表查询标签
+            let tag_relations = post_tags::Entity::find()
+                .filter(post_tags::Column::PostId.is_in(post_ids))
+                .find_also_related(tags::Entity)
+                .all(db)
+                .await
+                .unwrap_or_default();
+            for (post_tag, tag_option) in tag_relations {
+                if let Some(tag) = tag_option {
+                    post_tags_map
+                        .entry(post_tag.post_id)
+                        .or_default()
+                        .push(TagResponse {
+                            id: tag.id,
+                            name: tag.name,
+                        });
+                }
+            }
+        }
+        // 批量查询作者
+        let authors = Self::find_authors(
+            db,
+            posts_with_categories.iter().map(|(post, _)| post.author_id),
+        )
+        .await?;
+
+        Ok(posts_with_categories
+            .into_iter()
+            .map(|(post, category_option)| {
+                let category = category_option.map(|category| CategoryResponse {
+                    id: category.id,
+                    name: category.name,
+                });
+                let tags = post_tags_map.remove(&post.id).unwrap_or_default();
+                let (author, author_avatar) = Self::author_of(&authors, post.author_id);
+                PostResponse {
+                    id: post.id,
+                    uuid: post.uuid,
+                    slug: post.slug,
+                    title: post.title,
+                    cover: post.cover_image.unwrap_or_default(),
+                    author,
+                    author_avatar,
+                    publish_time: post.published_at,
+                    update_time: post.updated_at,
+                    views: post.view_count,
+                    is_top: post.featured,
+                    status: post.status,
+                    is_publish: post.status == PostStatus::Published,
+                    is_hide: post.status == PostStatus::Offline,
+                    description: post.summary.unwrap_or_default(),
+                    size: post.size,
+                    category,
+                    tags,
+                }
+            })
+            .collect())
+    }
+
+    /// 按目标状态计算发布时间：
+    /// 发布时沿用已到期的发布时间（下线后重新发布保持首次发布时间），否则取当前时间；
+    /// 定时发布须指定晚于当前的时间；草稿清空发布时间；下线保持不变
+    fn resolve_published_at(
+        status: PostStatus,
+        current: Option<DateTime<Utc>>,
+        requested: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, AppError> {
+        match status {
+            PostStatus::Draft => Ok(None),
+            PostStatus::Published => Ok(Some(current.filter(|at| *at <= now).unwrap_or(now))),
+            PostStatus::Offline => Ok(current),
+            PostStatus::Scheduled => match requested.or(current) {
+                Some(at) if at > now => Ok(Some(at)),
+                _ => Err(AppError::BadRequest(
+                    "定时发布需要指定晚于当前时间的发布时间".to_string(),
+                )),
+            },
+        }
+    }
+
+    /// 将已到计划时间的定时文章改为已发布，返回发布的数量
+    pub async fn publish_scheduled(db: &DatabaseConnection) -> Result<u64, AppError> {
+        let now = Utc::now();
+        let result = posts::Entity::update_many()
+            .col_expr(posts::Column::Status, Expr::value(PostStatus::Published))
+            .col_expr(posts::Column::UpdatedAt, Expr::value(now))
+            .filter(posts::Column::Status.eq(PostStatus::Scheduled))
+            .filter(posts::Column::PublishedAt.lte(now))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 启动定时发布任务
+    pub fn start_scheduler(db: DatabaseConnection) {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(CONFIG.post.scheduler_interval));
+
+            loop {
+                interval.tick().await;
+                match Self::publish_scheduled(&db).await {
+                    Ok(0) => {}
+                    Ok(count) => log::info!("定时发布了 {count} 篇文章"),
+                    Err(e) => log::error!("定时发布文章失败: {e}"),
+                }
+            }
+        });
+    }
+
     /// 创建文章
     pub async fn create_post(
         db: &DatabaseConnection,
@@ -23,15 +337,25 @@ impl PostService {
         post_data: CreatePostRequest,
     ) -> Result<PostResponse, AppError> {
         let now = Utc::now();
-        let published_at = if post_data.status == 1 {
-            Some(now)
-        } else {
-            None
+        let published_at =
+            Self::resolve_published_at(post_data.status, None, post_data.published_at, now)?;
+
+        // HTML、字数、阅读时长与目录由 Markdown 生成，客户端提交的 content 不再使用
+        let rendered = markdown::render_post(&post_data.markdowncontent);
+
+        // 未指定 slug 时由标题生成
+        let slug = match post_data.slug {
+            Some(slug) => {
+                if Self::slug_taken(db, &slug, None).await? {
+                    return Err(AppError::AlreadyExists(
+                        "文章英文名（slug）已存在".to_string(),
+                    ));
+                }
+                slug
+            }
+            None => Self::generate_slug(db, &post_data.title).await?,
         };
 
-        // 计算文章大小（字符数）
-        let size = post_data.content.len() as i32;
-
         // 开启事务
         let txn = db.begin().await.map_err(|e| {
             log::error!("开启事务失败: {e}");
@@ -41,11 +365,12 @@ impl PostService {
         // 创建文章
         let new_post = posts::ActiveModel {
             uuid: Set(Uuid::new_v4().to_string()),
+            slug: Set(slug),
             author_id: Set(user_id),
             category_id: Set(post_data.category_id),
             title: Set(post_data.title),
             summary: Set(post_data.summary),
-            content: Set(post_data.content),
+            content: Set(rendered.html),
             markdowncontent: Set(post_data.markdowncontent),
             cover_image: Set(post_data.cover_image),
             status: Set(post_data.status),
@@ -53,8 +378,10 @@ impl PostService {
             view_count: Set(0),
             created_at: Set(now),
             updated_at: Set(now),
-            published_at: Set(published_at.unwrap_or(now)),
-            size: Set(size),
+            published_at: Set(published_at),
+            size: Set(rendered.word_count as i32),
+            reading_minutes: Set(rendered.reading_minutes as i32),
+            toc: Set(serde_json::json!(rendered.toc)),
             ..Default::default()
         };
 
@@ -63,6 +390,8 @@ impl PostService {
             AppError::DatabaseError("创建文章失败".to_string())
         })?;
 
+        PostSearchService::index_post(&txn, &created_post).await?;
+
         // 添加文章标签关联
         for tag_id in post_data.tag_ids {
             let post_tag = post_tags::ActiveModel {
@@ -83,14 +412,7 @@ impl PostService {
         })?;
 
         // 查询作者信息
-        let author = users::Entity::find_by_id(created_post.author_id)
-            .one(db)
-            .await
-            .map_err(|e| {
-                log::error!("查询作者信息失败: {e}");
-                AppError::DatabaseError("查询作者信息失败".to_string())
-            })?
-            .map_or_else(|| "未知作者".to_string(), |user| user.user_name);
+        let (author, author_avatar) = Self::find_author(db, created_post.author_id).await?;
 
         // 查询分类信息
         let category = categories::Entity::find_by_id(created_post.category_id)
@@ -129,15 +451,18 @@ impl PostService {
         Ok(PostResponse {
             id: created_post.id,
             uuid: created_post.uuid,
+            slug: created_post.slug,
             title: created_post.title,
             cover: created_post.cover_image.unwrap_or_default(),
             author,
+            author_avatar,
             publish_time: created_post.published_at,
             update_time: created_post.updated_at,
             views: created_post.view_count,
             is_top: created_post.featured,
-            is_publish: created_post.status == 1,
-            is_hide: created_post.status == 2,
+            status: created_post.status,
+            is_publish: created_post.status == PostStatus::Published,
+            is_hide: created_post.status == PostStatus::Offline,
             description: created_post.summary.unwrap_or_default(),
             size: created_post.size,
             category,
@@ -155,7 +480,7 @@ impl PostService {
         let now = Utc::now();
 
         // 查询文章是否存在
-        let post = posts::Entity::find_by_uuid(uuid)
+        let post = posts::Entity::find_by_key(uuid)
             .one(db)
             .await
             .map_err(|e| {
@@ -169,12 +494,66 @@ impl PostService {
             return Err(AppError::Unauthorized("没有权限编辑此文章".to_string()));
         }
 
+        // 状态变更须符合状态机，发布时间随目标状态调整
+        let published_at = if post_data.status.is_some() || post_data.published_at.is_some() {
+            let status = post_data.status.unwrap_or(post.status);
+            if !post.status.can_transition_to(status) {
+                return Err(AppError::BadRequest(format!(
+                    "文章不能从{}切换为{}",
+                    post.status.label(),
+                    status.label()
+                )));
+            }
+            Some(Self::resolve_published_at(
+                status,
+                post.published_at,
+                post_data.published_at,
+                now,
+            )?)
+        } else {
+            None
+        };
+
+        // 修改 slug 时不能占用其他文章的 slug
+        let new_slug = post_data.slug.filter(|slug| *slug != post.slug);
+        if let Some(slug) = &new_slug
+            && Self::slug_taken(db, slug, Some(post.id)).await?
+        {
+            return Err(AppError::AlreadyExists(
+                "文章英文名（slug）已存在".to_string(),
+            ));
+        }
+
         // 开启事务
         let txn = db.begin().await.map_err(|e| {
             log::error!("开启事务失败: {e}");
             AppError::DatabaseError("服务器内部错误".to_string())
         })?;
 
+        // 保存更新前的版本
+        PostRevisionService::snapshot(&txn, &post, user_id).await?;
+
+        // 旧的 slug 记入历史，改回曾用的 slug 时从历史中移除
+        if let Some(slug) = &new_slug {
+            post_slug_history::Entity::delete_many()
+                .filter(post_slug_history::Column::PostId.eq(post.id))
+                .filter(post_slug_history::Column::Slug.eq(slug.as_str()))
+                .exec(&txn)
+                .await?;
+            post_slug_history::ActiveModel {
+                post_id: Set(post.id),
+                slug: Set(post.slug.clone()),
+                created_at: Set(Some(now.into())),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await
+            .map_err(|e| {
+                log::error!("保存文章 slug 历史失败: {e}");
+                AppError::DatabaseError("更新文章失败".to_string())
+            })?;
+        }
+
         // 更新文章
         let mut active_post: posts::ActiveModel = post.into();
 
@@ -182,16 +561,19 @@ impl PostService {
         if let Some(title) = post_data.title {
             active_post.title = Set(title);
         }
-        if let Some(summary) = post_data.summary {
-            active_post.summary = Set(Some(summary));
+        if let Some(slug) = new_slug {
+            active_post.slug = Set(slug);
         }
-        if let Some(content) = post_data.content {
-            active_post.content = Set(content.clone());
-            let size = i32::try_from(content.len())
-                .map_err(|e| AppError::BadRequest(format!("文章内容过长: {}", e)))?;
-            active_post.size = Set(size);
+        // 摘要传空字符串时清空
+        if let Some(summary) = post_data.summary {
+            active_post.summary = Set(Some(summary).filter(|summary| !summary.is_empty()));
         }
         if let Some(markdowncontent) = post_data.markdowncontent {
+            let rendered = markdown::render_post(&markdowncontent);
+            active_post.content = Set(rendered.html);
+            active_post.size = Set(rendered.word_count as i32);
+            active_post.reading_minutes = Set(rendered.reading_minutes as i32);
+            active_post.toc = Set(serde_json::json!(rendered.toc));
             active_post.markdowncontent = Set(markdowncontent);
         }
         if let Some(cover_image) = post_data.cover_image {
@@ -202,10 +584,9 @@ impl PostService {
         }
         if let Some(status) = post_data.status {
             active_post.status = Set(status);
-            // // 如果状态从非发布变为发布，设置发布时间
-            // if post.status != 1 && status == 1 {
-            //     active_post.published_at = Set(now);
-            // }
+        }
+        if let Some(published_at) = published_at {
+            active_post.published_at = Set(published_at);
         }
         if let Some(featured) = post_data.featured {
             active_post.featured = Set(featured);
@@ -217,6 +598,7 @@ impl PostService {
             log::error!("更新文章失败: {e}");
             AppError::DatabaseError("更新文章失败".to_string())
         })?;
+        PostSearchService::index_post(&txn, &updated_post).await?;
 
         // 如果提供了标签，更新文章标签关联
         if let Some(tag_ids) = post_data.tag_ids {
@@ -251,14 +633,7 @@ impl PostService {
         })?;
 
         // 查询作者信息
-        let author = users::Entity::find_by_id(updated_post.author_id)
-            .one(db)
-            .await
-            .map_err(|e| {
-                log::error!("查询作者信息失败: {e}");
-                AppError::DatabaseError("查询作者信息失败".to_string())
-            })?
-            .map_or_else(|| "未知作者".to_string(), |user| user.user_name);
+        let (author, author_avatar) = Self::find_author(db, updated_post.author_id).await?;
 
         // 查询分类信息
         let category = categories::Entity::find_by_id(updated_post.category_id)
@@ -297,15 +672,18 @@ impl PostService {
         Ok(PostResponse {
             id: updated_post.id,
             uuid: updated_post.uuid,
+            slug: updated_post.slug,
             title: updated_post.title,
             cover: updated_post.cover_image.unwrap_or_default(),
             author,
+            author_avatar,
             publish_time: updated_post.published_at,
             update_time: updated_post.updated_at,
             views: updated_post.view_count,
             is_top: updated_post.featured,
-            is_publish: updated_post.status == 1,
-            is_hide: updated_post.status == 2,
+            status: updated_post.status,
+            is_publish: updated_post.status == PostStatus::Published,
+            is_hide: updated_post.status == PostStatus::Offline,
             description: updated_post.summary.unwrap_or_default(),
             size: updated_post.size,
             category,
@@ -320,7 +698,7 @@ impl PostService {
         uuid: &str,
     ) -> Result<(), AppError> {
         // 查询文章是否存在
-        let post = posts::Entity::find_by_uuid(uuid)
+        let post = posts::Entity::find_by_key(uuid)
             .one(db)
             .await
             .map_err(|e| {
@@ -350,6 +728,8 @@ impl PostService {
                 AppError::DatabaseError("删除文章失败".to_string())
             })?;
 
+        PostSearchService::remove(&txn, post.id).await?;
+
         // 删除文章
         posts::Entity::delete_by_id(post.id)
             .exec(&txn)
@@ -368,3 +748,130 @@ impl PostService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+    use sea_orm::{Database, Statement};
+
+    #[test]
+    fn status_transitions_and_publish_time() {
+        use PostStatus::*;
+        assert!(Draft.can_transition_to(Scheduled));
+        assert!(Offline.can_transition_to(Published));
+        assert!(!Draft.can_transition_to(Offline));
+        assert!(!Published.can_transition_to(Scheduled));
+
+        let now = Utc::now();
+        let earlier = now - TimeDelta::days(3);
+        let later = now + TimeDelta::hours(1);
+        let resolve = PostService::resolve_published_at;
+        assert_eq!(resolve(Draft, Some(earlier), None, now).unwrap(), None);
+        assert_eq!(resolve(Published, None, None, now).unwrap(), Some(now));
+        assert_eq!(
+            resolve(Published, Some(earlier), None, now).unwrap(),
+            Some(earlier)
+        );
+        assert_eq!(
+            resolve(Published, Some(later), None, now).unwrap(),
+            Some(now)
+        );
+        assert_eq!(
+            resolve(Scheduled, None, Some(later), now).unwrap(),
+            Some(later)
+        );
+        assert!(resolve(Scheduled, None, Some(earlier), now).is_err());
+        assert!(resolve(Scheduled, None, None, now).is_err());
+    }
+
+    #[tokio::test]
+    async fn upgrade_existing_backfills_slugs_and_rendering() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        // 模拟执行升级脚本后的旧数据：slug 为空，渲染字段为默认值
+        for sql in [
+            "CREATE TABLE posts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                uuid TEXT NOT NULL UNIQUE,
+                slug TEXT,
+                author_id INTEGER NOT NULL,
+                category_id INTEGER,
+                title TEXT NOT NULL,
+                summary TEXT,
+                content TEXT NOT NULL,
+                markdowncontent TEXT NOT NULL,
+                cover_image TEXT,
+                status INTEGER NOT NULL DEFAULT 0,
+                featured BOOLEAN NOT NULL DEFAULT FALSE,
+                size INTEGER NOT NULL DEFAULT 0,
+                reading_minutes INTEGER NOT NULL DEFAULT 0,
+                toc TEXT NOT NULL DEFAULT '[]',
+                view_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                published_at TEXT
+            )",
+            "CREATE TABLE post_slug_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                post_id INTEGER NOT NULL,
+                slug TEXT NOT NULL UNIQUE,
+                created_at TEXT
+            )",
+        ] {
+            db.execute_unprepared(sql).await.unwrap();
+        }
+        for (id, slug, title) in [
+            (1, None, "你好世界"),
+            (2, None, "你好世界"),
+            (3, Some("ni-hao-shi-jie"), "Hello"),
+            (4, None, "Rust 入门"),
+        ] {
+            db.execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO posts (id, uuid, slug, author_id, category_id, title, content, \
+                 markdowncontent, created_at, updated_at) \
+                 VALUES (?, ?, ?, 1, 1, ?, '', ?, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+                [
+                    id.into(),
+                    Uuid::new_v4().to_string().into(),
+                    slug.into(),
+                    title.into(),
+                    "# 标题\n\n正文内容".into(),
+                ],
+            ))
+            .await
+            .unwrap();
+        }
+        db.execute_unprepared(
+            "INSERT INTO post_slug_history (post_id, slug) VALUES (3, 'rust-ru-men')",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(PostService::upgrade_existing(&db).await.unwrap(), 3);
+        let posts = posts::Entity::find()
+            .order_by_asc(posts::Column::Id)
+            .all(&db)
+            .await
+            .unwrap();
+        let slugs: Vec<&str> = posts.iter().map(|post| post.slug.as_str()).collect();
+        assert_eq!(
+            slugs,
+            [
+                "ni-hao-shi-jie-2",
+                "ni-hao-shi-jie-3",
+                "ni-hao-shi-jie",
+                "rust-ru-men-2"
+            ]
+        );
+        for post in &posts {
+            assert!(post.content.contains("<h1"), "{}", post.content);
+            assert_eq!(post.size, 6);
+            assert_eq!(post.reading_minutes, 1);
+            assert_eq!(post.toc[0]["title"], "标题");
+        }
+
+        // 再次执行不会改动已有的 slug
+        assert_eq!(PostService::upgrade_existing(&db).await.unwrap(), 0);
+    }
+}
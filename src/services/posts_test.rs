@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::dto::posts::{CreatePostRequest, UpdatePostRequest};
+    use crate::models::posts::PostStatus;
     use crate::models::{categories, post_tags, posts, tags, users};
     use crate::services::posts::PostService;
     use sea_orm::{
@@ -27,13 +28,15 @@ mod tests {
         // 构建创建文章的请求
         let create_post_request = CreatePostRequest {
             title: "测试文章标题".to_string(),
+            slug: None,
             summary: Some("这是测试文章的摘要".to_string()),
-            content: "这是测试文章的内容，包含了一些示例文本。".to_string(),
+            content: Some("这是测试文章的内容，包含了一些示例文本。".to_string()),
             markdowncontent: "# 测试文章\n\n这是测试文章的内容。".to_string(),
             cover_image: Some("https://example.com/cover.jpg".to_string()),
             category_id: category.id,
             tag_ids: vec![tag1.id, tag2.id],
-            status: 1, // 发布状态
+            status: PostStatus::Published, // 发布状态
+            published_at: None,
             featured: false,
         };
 
@@ -85,13 +88,15 @@ mod tests {
         // 构建创建草稿文章的请求
         let create_post_request = CreatePostRequest {
             title: "草稿文章标题".to_string(),
+            slug: None,
             summary: None,
-            content: "这是草稿文章的内容。".to_string(),
+            content: Some("这是草稿文章的内容。".to_string()),
             markdowncontent: "# 草稿文章\n\n这是草稿文章的内容。".to_string(),
             cover_image: None,
             category_id: category.id,
             tag_ids: vec![tag1.id],
-            status: 0, // 草稿状态
+            status: PostStatus::Draft, // 草稿状态
+            published_at: None,
             featured: false,
         };
 
@@ -134,13 +139,15 @@ mod tests {
         // 构建创建隐藏文章的请求
         let create_post_request = CreatePostRequest {
             title: "隐藏文章标题".to_string(),
+            slug: None,
             summary: Some("这是隐藏文章的摘要".to_string()),
-            content: "这是隐藏文章的内容。".to_string(),
+            content: Some("这是隐藏文章的内容。".to_string()),
             markdowncontent: "# 隐藏文章\n\n这是隐藏文章的内容。".to_string(),
             cover_image: Some("https://example.com/hidden.jpg".to_string()),
             category_id: category.id,
             tag_ids: vec![tag1.id],
-            status: 2, // 隐藏状态
+            status: PostStatus::Offline, // 隐藏状态
+            published_at: None,
             featured: false,
         };
 
@@ -183,13 +190,15 @@ mod tests {
         // 构建创建置顶文章的请求
         let create_post_request = CreatePostRequest {
             title: "置顶文章标题".to_string(),
+            slug: None,
             summary: Some("这是置顶文章的摘要".to_string()),
-            content: "这是置顶文章的内容。".to_string(),
+            content: Some("这是置顶文章的内容。".to_string()),
             markdowncontent: "# 置顶文章\n\n这是置顶文章的内容。".to_string(),
             cover_image: Some("https://example.com/featured.jpg".to_string()),
             category_id: category.id,
             tag_ids: vec![tag1.id],
-            status: 1,      // 发布状态
+            status: PostStatus::Published, // 发布状态
+            published_at: None,
             featured: true, // 置顶
         };
 
@@ -303,13 +312,15 @@ mod tests {
         // 先创建一篇文章
         let create_post_request = CreatePostRequest {
             title: "原始文章标题".to_string(),
+            slug: None,
             summary: Some("这是原始文章的摘要".to_string()),
-            content: "这是原始文章的内容。".to_string(),
+            content: Some("这是原始文章的内容。".to_string()),
             markdowncontent: "# 原始文章\n\n这是原始文章的内容。".to_string(),
             cover_image: Some("https://example.com/original.jpg".to_string()),
             category_id: category.id,
             tag_ids: vec![tag1.id],
-            status: 1, // 发布状态
+            status: PostStatus::Published, // 发布状态
+            published_at: None,
             featured: false,
         };
 
@@ -325,6 +336,7 @@ mod tests {
         // 构建更新文章的请求
         let update_post_request = UpdatePostRequest {
             title: Some("更新后的文章标题".to_string()),
+            slug: None,
             summary: Some("这是更新后的文章摘要".to_string()),
             content: Some("这是更新后的文章内容，比原来更长更详细。".to_string()),
             markdowncontent: Some(
@@ -333,7 +345,8 @@ mod tests {
             cover_image: Some("https://example.com/updated.jpg".to_string()),
             category_id: Some(new_category.id),
             tag_ids: Some(vec![tag2.id, tag3.id]),
-            status: Some(1),      // 保持发布状态
+            status: Some(PostStatus::Published), // 保持发布状态
+            published_at: None,
             featured: Some(true), // 设置为置顶
         };
 
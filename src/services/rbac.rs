@@ -0,0 +1,801 @@
+use crate::config::AppError;
+use crate::dto::rbac::{
+    AssignRoleRequest, CreatePermissionRequest, CreateRoleRequest, GrantUserPermissionRequest,
+    PermissionResponse, RegistryPermissionResponse, RoleResponse, UpdatePermissionRequest,
+    UpdateRoleRequest, UserPermissionResponse, UserRoleResponse,
+};
+use crate::get_all_routes;
+use crate::models::{permissions, role_permissions, roles, user_permissions, user_roles, users};
+use crate::utils::db_err_map;
+use crate::utils::perm_cache::load_perm_cache;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel,
+    QueryFilter, QueryOrder, Set, SqlErr, TransactionTrait, sea_query::Expr,
+};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// 角色 / 权限管理服务，所有写操作完成后都会重新加载权限缓存
+pub struct RbacService;
+
+impl RbacService {
+    // ------------------------------ 角色 ------------------------------
+
+    /// 获取所有角色及其权限码
+    pub async fn list_roles(db: &DatabaseConnection) -> Result<Vec<RoleResponse>, AppError> {
+        let roles = roles::Entity::find()
+            .order_by_asc(roles::Column::Id)
+            .all(db)
+            .await
+            .map_err(map_db_err)?;
+        let links = role_permissions::Entity::find()
+            .find_also_related(permissions::Entity)
+            .all(db)
+            .await
+            .map_err(map_db_err)?;
+
+        Ok(roles
+            .into_iter()
+            .map(|role| {
+                let mut codes: Vec<String> = links
+                    .iter()
+                    .filter(|(rp, _)| rp.role_id == Some(role.id))
+                    .filter_map(|(_, perm)| perm.as_ref().map(|p| p.code.clone()))
+                    .collect();
+                codes.sort();
+                RoleResponse {
+                    id: role.id,
+                    code: role.code,
+                    name: role.name,
+                    description: role.description,
+                    is_system: role.is_system.unwrap_or(false),
+                    permissions: codes,
+                }
+            })
+            .collect())
+    }
+
+    /// 创建角色
+    pub async fn create_role(
+        db: &DatabaseConnection,
+        req: CreateRoleRequest,
+    ) -> Result<roles::Model, AppError> {
+        roles::ActiveModel {
+            code: Set(req.code),
+            name: Set(req.name),
+            description: Set(req.description),
+            is_system: Set(Some(false)),
+            created_at: Set(Some(Utc::now().into())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(map_db_err)
+    }
+
+    /// 更新角色名称与描述
+    pub async fn update_role(
+        db: &DatabaseConnection,
+        role_id: i32,
+        req: UpdateRoleRequest,
+    ) -> Result<roles::Model, AppError> {
+        let mut role = find_role(db, role_id).await?.into_active_model();
+        if let Some(name) = req.name {
+            role.name = Set(name);
+        }
+        if let Some(description) = req.description {
+            role.description = Set(Some(description));
+        }
+        role.update(db).await.map_err(map_db_err)
+    }
+
+    /// 删除角色（系统内置角色不可删除）
+    pub async fn delete_role(db: &DatabaseConnection, role_id: i32) -> Result<(), AppError> {
+        let role = find_role(db, role_id).await?;
+        if role.is_system.unwrap_or(false) {
+            return Err(AppError::Forbidden(format!(
+                "系统内置角色 {} 不可删除",
+                role.code
+            )));
+        }
+        roles::Entity::delete_by_id(role_id)
+            .exec(db)
+            .await
+            .map_err(map_db_err)?;
+        load_perm_cache(db).await
+    }
+
+    /// 为角色挂载权限（已挂载的忽略），有不存在的权限时整体拒绝
+    pub async fn attach_permissions(
+        db: &DatabaseConnection,
+        role_id: i32,
+        permission_ids: Vec<i32>,
+    ) -> Result<(), AppError> {
+        find_role(db, role_id).await?;
+        let permission_ids: BTreeSet<i32> = permission_ids.into_iter().collect();
+        let known: HashSet<i32> = permissions::Entity::find()
+            .filter(permissions::Column::Id.is_in(permission_ids.iter().copied()))
+            .all(db)
+            .await
+            .map_err(map_db_err)?
+            .into_iter()
+            .map(|perm| perm.id)
+            .collect();
+        let missing: Vec<String> = permission_ids
+            .iter()
+            .filter(|id| !known.contains(id))
+            .map(i32::to_string)
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::NotFound(format!(
+                "ID为{}的权限不存在",
+                missing.join(", ")
+            )));
+        }
+        let existing: HashSet<i32> = role_permissions::Entity::find()
+            .filter(role_permissions::Column::RoleId.eq(role_id))
+            .all(db)
+            .await
+            .map_err(map_db_err)?
+            .into_iter()
+            .filter_map(|rp| rp.permission_id)
+            .collect();
+
+        let txn = db.begin().await.map_err(map_db_err)?;
+        for permission_id in permission_ids {
+            if existing.contains(&permission_id) {
+                continue;
+            }
+            role_permissions::ActiveModel {
+                role_id: Set(Some(role_id)),
+                permission_id: Set(Some(permission_id)),
+                created_at: Set(Some(Utc::now().into())),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await
+            .map_err(map_db_err)?;
+        }
+        txn.commit().await.map_err(map_db_err)?;
+        load_perm_cache(db).await
+    }
+
+    /// 从角色上移除权限
+    pub async fn detach_permission(
+        db: &DatabaseConnection,
+        role_id: i32,
+        permission_id: i32,
+    ) -> Result<(), AppError> {
+        let result = role_permissions::Entity::delete_many()
+            .filter(role_permissions::Column::RoleId.eq(role_id))
+            .filter(role_permissions::Column::PermissionId.eq(permission_id))
+            .exec(db)
+            .await
+            .map_err(map_db_err)?;
+        if result.rows_affected == 0 {
+            return Err(AppError::NotFound("角色未挂载该权限".to_string()));
+        }
+        load_perm_cache(db).await
+    }
+
+    // ------------------------------ 权限 ------------------------------
+
+    /// 获取所有权限
+    pub async fn list_permissions(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<PermissionResponse>, AppError> {
+        let perms = permissions::Entity::find()
+            .order_by_asc(permissions::Column::Category)
+            .order_by_asc(permissions::Column::Code)
+            .all(db)
+            .await
+            .map_err(map_db_err)?;
+        Ok(perms.into_iter().map(PermissionResponse::from).collect())
+    }
+
+    /// 创建权限
+    pub async fn create_permission(
+        db: &DatabaseConnection,
+        req: CreatePermissionRequest,
+    ) -> Result<PermissionResponse, AppError> {
+        let perm = permissions::ActiveModel {
+            code: Set(req.code),
+            name: Set(req.name),
+            description: Set(req.description),
+            category: Set(req.category),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(map_db_err)?;
+        Ok(perm.into())
+    }
+
+    /// 更新权限名称、描述与分类
+    pub async fn update_permission(
+        db: &DatabaseConnection,
+        permission_id: i32,
+        req: UpdatePermissionRequest,
+    ) -> Result<PermissionResponse, AppError> {
+        let mut perm = find_permission(db, permission_id)
+            .await?
+            .into_active_model();
+        if let Some(name) = req.name {
+            perm.name = Set(name);
+        }
+        if let Some(description) = req.description {
+            perm.description = Set(Some(description));
+        }
+        if let Some(category) = req.category {
+            perm.category = Set(Some(category));
+        }
+        Ok(perm.update(db).await.map_err(map_db_err)?.into())
+    }
+
+    /// 删除权限，关联的角色权限与用户权限会级联删除
+    pub async fn delete_permission(
+        db: &DatabaseConnection,
+        permission_id: i32,
+    ) -> Result<(), AppError> {
+        find_permission(db, permission_id).await?;
+        permissions::Entity::delete_by_id(permission_id)
+            .exec(db)
+            .await
+            .map_err(map_db_err)?;
+        load_perm_cache(db).await
+    }
+
+    /// 列出路由注册表中声明的所有权限码，并标记权限表中缺失的
+    pub async fn registry_permissions(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<RegistryPermissionResponse>, AppError> {
+        let mut codes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for route in get_all_routes()? {
+            if route.permission.is_empty() {
+                continue;
+            }
+            codes
+                .entry(route.permission.to_string())
+                .or_default()
+                .push(format!("{} {}", route.method.to_uppercase(), route.path));
+        }
+
+        let known: HashSet<String> = permissions::Entity::find()
+            .all(db)
+            .await
+            .map_err(map_db_err)?
+            .into_iter()
+            .map(|p| p.code)
+            .collect();
+
+        Ok(codes
+            .into_iter()
+            .map(|(code, mut routes)| {
+                routes.sort();
+                RegistryPermissionResponse {
+                    missing: !known.contains(&code),
+                    code,
+                    routes,
+                }
+            })
+            .collect())
+    }
+
+    // ------------------------------ 用户角色 ------------------------------
+
+    /// 获取用户的角色
+    pub async fn list_user_roles(
+        db: &DatabaseConnection,
+        user_uuid: &str,
+    ) -> Result<Vec<UserRoleResponse>, AppError> {
+        let user = find_user(db, user_uuid).await?;
+        let rows = user_roles::Entity::find()
+            .filter(user_roles::Column::UserId.eq(user.id))
+            .find_also_related(roles::Entity)
+            .all(db)
+            .await
+            .map_err(map_db_err)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(ur, role)| {
+                role.map(|role| UserRoleResponse {
+                    role_id: role.id,
+                    code: role.code,
+                    name: role.name,
+                    is_primary: ur.is_primary.unwrap_or(false),
+                })
+            })
+            .collect())
+    }
+
+    /// 为用户分配角色，已分配时仅更新主要角色标记
+    pub async fn assign_role(
+        db: &DatabaseConnection,
+        user_uuid: &str,
+        req: AssignRoleRequest,
+    ) -> Result<(), AppError> {
+        let user = find_user(db, user_uuid).await?;
+        find_role(db, req.role_id).await?;
+
+        let txn = db.begin().await.map_err(map_db_err)?;
+        if req.is_primary {
+            // 同一用户只能有一个主要角色
+            user_roles::Entity::update_many()
+                .col_expr(user_roles::Column::IsPrimary, Expr::value(false))
+                .filter(user_roles::Column::UserId.eq(user.id))
+                .exec(&txn)
+                .await
+                .map_err(map_db_err)?;
+        }
+        let existing = user_roles::Entity::find()
+            .filter(user_roles::Column::UserId.eq(user.id))
+            .filter(user_roles::Column::RoleId.eq(req.role_id))
+            .one(&txn)
+            .await
+            .map_err(map_db_err)?;
+        match existing {
+            Some(ur) => {
+                let mut ur = ur.into_active_model();
+                ur.is_primary = Set(Some(req.is_primary));
+                ur.update(&txn).await.map_err(map_db_err)?;
+            }
+            None => {
+                user_roles::ActiveModel {
+                    user_id: Set(Some(user.id)),
+                    role_id: Set(Some(req.role_id)),
+                    is_primary: Set(Some(req.is_primary)),
+                    created_at: Set(Utc::now()),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await
+                .map_err(map_db_err)?;
+            }
+        }
+        txn.commit().await.map_err(map_db_err)?;
+        load_perm_cache(db).await
+    }
+
+    /// 移除用户的角色
+    pub async fn remove_role(
+        db: &DatabaseConnection,
+        user_uuid: &str,
+        role_id: i32,
+    ) -> Result<(), AppError> {
+        let user = find_user(db, user_uuid).await?;
+        let result = user_roles::Entity::delete_many()
+            .filter(user_roles::Column::UserId.eq(user.id))
+            .filter(user_roles::Column::RoleId.eq(role_id))
+            .exec(db)
+            .await
+            .map_err(map_db_err)?;
+        if result.rows_affected == 0 {
+            return Err(AppError::NotFound("用户未分配该角色".to_string()));
+        }
+        load_perm_cache(db).await
+    }
+
+    // ------------------------------ 用户权限 ------------------------------
+
+    /// 获取用户的特殊权限
+    pub async fn list_user_permissions(
+        db: &DatabaseConnection,
+        user_uuid: &str,
+    ) -> Result<Vec<UserPermissionResponse>, AppError> {
+        let user = find_user(db, user_uuid).await?;
+        let rows = user_permissions::Entity::find()
+            .filter(user_permissions::Column::UserId.eq(user.id))
+            .find_also_related(permissions::Entity)
+            .all(db)
+            .await
+            .map_err(map_db_err)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(up, perm)| {
+                perm.map(|perm| UserPermissionResponse {
+                    permission_id: perm.id,
+                    code: perm.code,
+                    granted: up.granted.unwrap_or(true),
+                    expires_at: up.expires_at.map(|t| t.with_timezone(&Utc)),
+                })
+            })
+            .collect())
+    }
+
+    /// 授予或拒绝用户权限，已存在时覆盖
+    pub async fn grant_user_permission(
+        db: &DatabaseConnection,
+        user_uuid: &str,
+        req: GrantUserPermissionRequest,
+    ) -> Result<(), AppError> {
+        let user = find_user(db, user_uuid).await?;
+        find_permission(db, req.permission_id).await?;
+
+        let existing = user_permissions::Entity::find()
+            .filter(user_permissions::Column::UserId.eq(user.id))
+            .filter(user_permissions::Column::PermissionId.eq(req.permission_id))
+            .one(db)
+            .await
+            .map_err(map_db_err)?;
+        let mut model = match existing {
+            Some(up) => up.into_active_model(),
+            None => user_permissions::ActiveModel {
+                user_id: Set(Some(user.id)),
+                permission_id: Set(Some(req.permission_id)),
+                created_at: Set(Some(Utc::now().into())),
+                ..Default::default()
+            },
+        };
+        model.granted = Set(Some(req.granted));
+        model.expires_at = Set(req.expires_at.map(Into::into));
+        model.save(db).await.map_err(map_db_err)?;
+        load_perm_cache(db).await
+    }
+
+    /// 撤销用户的特殊权限（恢复为角色权限）
+    pub async fn revoke_user_permission(
+        db: &DatabaseConnection,
+        user_uuid: &str,
+        permission_id: i32,
+    ) -> Result<(), AppError> {
+        let user = find_user(db, user_uuid).await?;
+        let result = user_permissions::Entity::delete_many()
+            .filter(user_permissions::Column::UserId.eq(user.id))
+            .filter(user_permissions::Column::PermissionId.eq(permission_id))
+            .exec(db)
+            .await
+            .map_err(map_db_err)?;
+        if result.rows_affected == 0 {
+            return Err(AppError::NotFound("用户没有该特殊权限".to_string()));
+        }
+        load_perm_cache(db).await
+    }
+}
+
+impl From<permissions::Model> for PermissionResponse {
+    fn from(p: permissions::Model) -> Self {
+        Self {
+            id: p.id,
+            code: p.code,
+            name: p.name,
+            description: p.description,
+            category: p.category,
+        }
+    }
+}
+
+fn map_db_err(e: DbErr) -> AppError {
+    log::error!("RBAC 数据库操作失败: {e}");
+    match e.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => AppError::Conflict(db_err_map(e).to_owned()),
+        _ => AppError::DatabaseError(db_err_map(e).to_owned()),
+    }
+}
+
+async fn find_role(db: &DatabaseConnection, role_id: i32) -> Result<roles::Model, AppError> {
+    roles::Entity::find_by_id(role_id)
+        .one(db)
+        .await
+        .map_err(map_db_err)?
+        .ok_or_else(|| AppError::NotFound(format!("ID为{role_id}的角色不存在")))
+}
+
+async fn find_permission(
+    db: &DatabaseConnection,
+    permission_id: i32,
+) -> Result<permissions::Model, AppError> {
+    permissions::Entity::find_by_id(permission_id)
+        .one(db)
+        .await
+        .map_err(map_db_err)?
+        .ok_or_else(|| AppError::NotFound(format!("ID为{permission_id}的权限不存在")))
+}
+
+async fn find_user(db: &DatabaseConnection, user_uuid: &str) -> Result<users::Model, AppError> {
+    users::Entity::find()
+        .filter(users::Column::Uuid.eq(user_uuid))
+        .one(db)
+        .await
+        .map_err(map_db_err)?
+        .ok_or_else(|| AppError::NotFound(format!("UUID为{user_uuid}的用户不存在")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_db::{create_user, setup_test_db};
+    use crate::utils::perm_cache::{
+        CACHE_TEST_LOCK, ROLE_PERMS, USER_PERMS, USER_ROLES, effective_permissions,
+    };
+
+    fn role_request(code: &str) -> CreateRoleRequest {
+        CreateRoleRequest {
+            code: code.to_string(),
+            name: format!("{code} 角色"),
+            description: None,
+        }
+    }
+
+    fn permission_request(code: &str) -> CreatePermissionRequest {
+        CreatePermissionRequest {
+            code: code.to_string(),
+            name: format!("{code} 权限"),
+            description: None,
+            category: Some("post".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn role_crud_and_duplicate_code() {
+        let _guard = CACHE_TEST_LOCK.lock().await;
+        let db = setup_test_db().await;
+
+        let role = RbacService::create_role(&db, role_request("EDITOR"))
+            .await
+            .unwrap();
+        assert!(!role.is_system.unwrap());
+        let err = RbacService::create_role(&db, role_request("EDITOR"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "{err:?}");
+
+        let updated = RbacService::update_role(
+            &db,
+            role.id,
+            UpdateRoleRequest {
+                name: Some("编辑".to_string()),
+                description: Some("负责文章".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.name, "编辑");
+        assert_eq!(updated.description.as_deref(), Some("负责文章"));
+
+        RbacService::delete_role(&db, role.id).await.unwrap();
+        assert!(RbacService::list_roles(&db).await.unwrap().is_empty());
+        let err = RbacService::delete_role(&db, role.id).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+
+        // 系统内置角色不可删除
+        let system = roles::ActiveModel {
+            code: Set("ADMIN".to_string()),
+            name: Set("管理员".to_string()),
+            is_system: Set(Some(true)),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let err = RbacService::delete_role(&db, system.id).await.unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+    }
+
+    #[tokio::test]
+    async fn permission_crud_and_duplicate_code() {
+        let _guard = CACHE_TEST_LOCK.lock().await;
+        let db = setup_test_db().await;
+
+        let perm = RbacService::create_permission(&db, permission_request("post:create"))
+            .await
+            .unwrap();
+        let err = RbacService::create_permission(&db, permission_request("post:create"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "{err:?}");
+
+        let updated = RbacService::update_permission(
+            &db,
+            perm.id,
+            UpdatePermissionRequest {
+                name: Some("发布文章".to_string()),
+                description: None,
+                category: Some("content".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.name, "发布文章");
+        assert_eq!(updated.category.as_deref(), Some("content"));
+
+        // 删除权限时角色上挂载的权限级联删除
+        let role = RbacService::create_role(&db, role_request("WRITER"))
+            .await
+            .unwrap();
+        RbacService::attach_permissions(&db, role.id, vec![perm.id])
+            .await
+            .unwrap();
+        RbacService::delete_permission(&db, perm.id).await.unwrap();
+        assert!(RbacService::list_permissions(&db).await.unwrap().is_empty());
+        let roles = RbacService::list_roles(&db).await.unwrap();
+        assert!(roles[0].permissions.is_empty());
+        let err = RbacService::delete_permission(&db, perm.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn attach_and_detach_permissions_reload_cache() {
+        let _guard = CACHE_TEST_LOCK.lock().await;
+        let db = setup_test_db().await;
+        let role = RbacService::create_role(&db, role_request("AUTHOR"))
+            .await
+            .unwrap();
+        let create = RbacService::create_permission(&db, permission_request("post:create"))
+            .await
+            .unwrap();
+        let delete = RbacService::create_permission(&db, permission_request("post:delete"))
+            .await
+            .unwrap();
+
+        // 重复挂载被忽略
+        RbacService::attach_permissions(&db, role.id, vec![create.id, delete.id, create.id])
+            .await
+            .unwrap();
+        RbacService::attach_permissions(&db, role.id, vec![create.id])
+            .await
+            .unwrap();
+        // 有不存在的权限时整体拒绝，返回缺失的ID
+        let err = RbacService::attach_permissions(&db, role.id, vec![create.id, 9999])
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, AppError::NotFound(msg) if msg.contains("9999")),
+            "{err:?}"
+        );
+        let roles = RbacService::list_roles(&db).await.unwrap();
+        assert_eq!(roles[0].permissions, vec!["post:create", "post:delete"]);
+        assert_eq!(ROLE_PERMS.read().await[&role.id].len(), 2);
+
+        RbacService::detach_permission(&db, role.id, delete.id)
+            .await
+            .unwrap();
+        assert!(!ROLE_PERMS.read().await[&role.id].contains("post:delete"));
+        let err = RbacService::detach_permission(&db, role.id, delete.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn assign_and_remove_roles_reload_cache() {
+        let _guard = CACHE_TEST_LOCK.lock().await;
+        let db = setup_test_db().await;
+        let user = create_user(&db, "rbacuser").await;
+        let author = RbacService::create_role(&db, role_request("AUTHOR"))
+            .await
+            .unwrap();
+        let editor = RbacService::create_role(&db, role_request("EDITOR"))
+            .await
+            .unwrap();
+        let perm = RbacService::create_permission(&db, permission_request("post:create"))
+            .await
+            .unwrap();
+        RbacService::attach_permissions(&db, author.id, vec![perm.id])
+            .await
+            .unwrap();
+
+        for (role_id, is_primary) in [(author.id, true), (editor.id, true)] {
+            RbacService::assign_role(
+                &db,
+                &user.uuid,
+                AssignRoleRequest {
+                    role_id,
+                    is_primary,
+                },
+            )
+            .await
+            .unwrap();
+        }
+        // 同一用户只有一个主要角色
+        let roles = RbacService::list_user_roles(&db, &user.uuid).await.unwrap();
+        assert_eq!(roles.len(), 2);
+        assert_eq!(
+            roles
+                .iter()
+                .filter(|r| r.is_primary)
+                .map(|r| r.role_id)
+                .collect::<Vec<_>>(),
+            vec![editor.id]
+        );
+        assert_eq!(
+            USER_ROLES.read().await[&user.id],
+            vec![editor.id, author.id]
+        );
+        assert!(
+            effective_permissions(user.id, &[])
+                .await
+                .contains("post:create")
+        );
+
+        RbacService::remove_role(&db, &user.uuid, author.id)
+            .await
+            .unwrap();
+        RbacService::remove_role(&db, &user.uuid, editor.id)
+            .await
+            .unwrap();
+        // 角色被全部移除后，不再回退到令牌中的角色
+        assert!(USER_ROLES.read().await[&user.id].is_empty());
+        assert!(
+            effective_permissions(user.id, &[author.id])
+                .await
+                .is_empty()
+        );
+        let err = RbacService::remove_role(&db, &user.uuid, author.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+
+        let err = RbacService::assign_role(
+            &db,
+            "missing-uuid",
+            AssignRoleRequest {
+                role_id: author.id,
+                is_primary: false,
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn grant_and_revoke_user_permissions_reload_cache() {
+        let _guard = CACHE_TEST_LOCK.lock().await;
+        let db = setup_test_db().await;
+        let user = create_user(&db, "grantuser").await;
+        let perm = RbacService::create_permission(&db, permission_request("system:backup"))
+            .await
+            .unwrap();
+
+        RbacService::grant_user_permission(
+            &db,
+            &user.uuid,
+            GrantUserPermissionRequest {
+                permission_id: perm.id,
+                granted: true,
+                expires_at: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(
+            effective_permissions(user.id, &[])
+                .await
+                .contains("system:backup")
+        );
+
+        // 再次授予时覆盖原记录
+        RbacService::grant_user_permission(
+            &db,
+            &user.uuid,
+            GrantUserPermissionRequest {
+                permission_id: perm.id,
+                granted: false,
+                expires_at: None,
+            },
+        )
+        .await
+        .unwrap();
+        let perms = RbacService::list_user_permissions(&db, &user.uuid)
+            .await
+            .unwrap();
+        assert_eq!(perms.len(), 1);
+        assert!(!perms[0].granted);
+        assert!(
+            !effective_permissions(user.id, &[])
+                .await
+                .contains("system:backup")
+        );
+
+        RbacService::revoke_user_permission(&db, &user.uuid, perm.id)
+            .await
+            .unwrap();
+        assert!(!USER_PERMS.read().await.contains_key(&user.id));
+        let err = RbacService::revoke_user_permission(&db, &user.uuid, perm.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+    }
+}
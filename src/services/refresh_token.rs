@@ -0,0 +1,282 @@
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::models::{refresh_tokens, user_sessions, users};
+use crate::services::AccountService;
+use base64::engine::{Engine as _, general_purpose};
+use chrono::{Duration as ChronoDuration, Utc};
+use rand::RngCore;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    sea_query::Expr,
+};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// 数据库保存的刷新令牌（只存哈希），每次使用都会轮换为新令牌。
+///
+/// 同一次登录轮换出的令牌属于同一个 family；已轮换的旧令牌被再次使用，
+/// 说明令牌可能被盗用，此时整个 family 立即作废。
+pub struct RefreshTokenService;
+
+impl RefreshTokenService {
+    /// 为用户签发新的刷新令牌，`family_id` 为空时开启新的令牌家族（即新的登录会话）
+    pub async fn issue(
+        db: &DatabaseConnection,
+        user_id: i32,
+        family_id: Option<String>,
+    ) -> Result<String, AppError> {
+        let token = generate_token();
+        let now = Utc::now();
+        refresh_tokens::ActiveModel {
+            user_id: Set(user_id),
+            token_hash: Set(hash_token(&token)),
+            family_id: Set(family_id.unwrap_or_else(|| Uuid::new_v4().to_string())),
+            expires_at: Set((now + ChronoDuration::seconds(CONFIG.jwt.refresh_expiry)).into()),
+            revoked_at: Set(None),
+            created_at: Set(Some(now.into())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(|e| {
+            log::error!("保存刷新令牌失败: {e}");
+            AppError::DatabaseError("签发刷新令牌失败".to_string())
+        })?;
+        Ok(token)
+    }
+
+    /// 使用刷新令牌换取新的刷新令牌，返回对应用户、新令牌与会话ID（即令牌家族）
+    pub async fn rotate(
+        db: &DatabaseConnection,
+        token: &str,
+    ) -> Result<(users::Model, String, String), AppError> {
+        let record = find_by_token(db, token)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("刷新令牌无效".to_string()))?;
+
+        if record.revoked_at.is_some() {
+            return Err(Self::reused(db, &record).await);
+        }
+        if record.expires_at < Utc::now() {
+            return Err(AppError::Unauthorized(
+                "刷新令牌已过期，请重新登录".to_string(),
+            ));
+        }
+
+        let user = users::Entity::find_by_id(record.user_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("用户不存在".to_string()))?;
+        AccountService::ensure_active(&user)?;
+
+        // 只作废仍有效的令牌：并发请求使用同一令牌时只有一个能完成轮换，其余视为重复使用
+        let result = refresh_tokens::Entity::update_many()
+            .col_expr(
+                refresh_tokens::Column::RevokedAt,
+                Expr::value(Utc::now().fixed_offset()),
+            )
+            .filter(refresh_tokens::Column::Id.eq(record.id))
+            .filter(refresh_tokens::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        if result.rows_affected != 1 {
+            return Err(Self::reused(db, &record).await);
+        }
+
+        let new_token = Self::issue(db, user.id, Some(record.family_id.clone())).await?;
+        Ok((user, new_token, record.family_id))
+    }
+
+    /// 已作废的令牌被重复使用，作废整个令牌家族
+    async fn reused(db: &DatabaseConnection, record: &refresh_tokens::Model) -> AppError {
+        log::warn!(
+            "refresh token reuse detected, user_id={}, family={}",
+            record.user_id,
+            record.family_id
+        );
+        if let Err(e) = Self::revoke_family(db, &record.family_id).await {
+            return e;
+        }
+        AppError::Unauthorized("刷新令牌已失效，请重新登录".to_string())
+    }
+
+    /// 作废刷新令牌所在的整个令牌家族（登出）
+    pub async fn revoke_by_token(db: &DatabaseConnection, token: &str) -> Result<(), AppError> {
+        if let Some(record) = find_by_token(db, token).await? {
+            Self::revoke_family(db, &record.family_id).await?;
+        }
+        Ok(())
+    }
+
+    /// 作废令牌家族中所有仍有效的令牌，对应的登录会话随之结束
+    pub async fn revoke_family(db: &DatabaseConnection, family_id: &str) -> Result<(), AppError> {
+        refresh_tokens::Entity::update_many()
+            .col_expr(
+                refresh_tokens::Column::RevokedAt,
+                Expr::value(Utc::now().fixed_offset()),
+            )
+            .filter(refresh_tokens::Column::FamilyId.eq(family_id))
+            .filter(refresh_tokens::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        user_sessions::Entity::update_many()
+            .col_expr(
+                user_sessions::Column::RevokedAt,
+                Expr::value(Utc::now().fixed_offset()),
+            )
+            .filter(user_sessions::Column::Id.eq(family_id))
+            .filter(user_sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// 作废用户的全部刷新令牌并结束全部登录会话，返回作废的令牌数量
+    pub async fn revoke_user(db: &DatabaseConnection, user_id: i32) -> Result<u64, AppError> {
+        let result = refresh_tokens::Entity::update_many()
+            .col_expr(
+                refresh_tokens::Column::RevokedAt,
+                Expr::value(Utc::now().fixed_offset()),
+            )
+            .filter(refresh_tokens::Column::UserId.eq(user_id))
+            .filter(refresh_tokens::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        user_sessions::Entity::update_many()
+            .col_expr(
+                user_sessions::Column::RevokedAt,
+                Expr::value(Utc::now().fixed_offset()),
+            )
+            .filter(user_sessions::Column::UserId.eq(user_id))
+            .filter(user_sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
+
+async fn find_by_token(
+    db: &DatabaseConnection,
+    token: &str,
+) -> Result<Option<refresh_tokens::Model>, AppError> {
+    Ok(refresh_tokens::Entity::find()
+        .filter(refresh_tokens::Column::TokenHash.eq(hash_token(token)))
+        .one(db)
+        .await?)
+}
+
+/// 生成 32 字节随机令牌（URL 安全的 base64）
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// 令牌的 SHA-256 十六进制哈希
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_db::{create_user, setup_test_db};
+    use sea_orm::IntoActiveModel;
+
+    async fn family_tokens(db: &DatabaseConnection, family_id: &str) -> Vec<refresh_tokens::Model> {
+        refresh_tokens::Entity::find()
+            .filter(refresh_tokens::Column::FamilyId.eq(family_id))
+            .all(db)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rotate_issues_new_token_in_same_family() {
+        let db = setup_test_db().await;
+        let user = create_user(&db, "rotateuser").await;
+        let token = RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+
+        let (rotated_user, new_token, family_id) =
+            RefreshTokenService::rotate(&db, &token).await.unwrap();
+        assert_eq!(rotated_user.id, user.id);
+        assert_ne!(new_token, token);
+
+        let tokens = family_tokens(&db, &family_id).await;
+        assert_eq!(tokens.len(), 2);
+        let old = find_by_token(&db, &token).await.unwrap().unwrap();
+        assert!(old.revoked_at.is_some(), "旧令牌应被作废");
+        let new = find_by_token(&db, &new_token).await.unwrap().unwrap();
+        assert!(new.revoked_at.is_none());
+
+        // 新令牌可以继续轮换
+        RefreshTokenService::rotate(&db, &new_token).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reused_token_revokes_whole_family() {
+        let db = setup_test_db().await;
+        let user = create_user(&db, "reuseuser").await;
+        let token = RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+        let (_, new_token, family_id) = RefreshTokenService::rotate(&db, &token).await.unwrap();
+
+        // 已轮换的旧令牌再次使用
+        let err = RefreshTokenService::rotate(&db, &token).await.unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)));
+        assert!(
+            family_tokens(&db, &family_id)
+                .await
+                .iter()
+                .all(|t| t.revoked_at.is_some()),
+            "整个令牌家族应被作废"
+        );
+        // 合法持有者的新令牌也随之失效
+        assert!(RefreshTokenService::rotate(&db, &new_token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn concurrent_rotation_succeeds_only_once() {
+        let db = setup_test_db().await;
+        let user = create_user(&db, "raceuser").await;
+        let token = RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+
+        let (first, second) = tokio::join!(
+            RefreshTokenService::rotate(&db, &token),
+            RefreshTokenService::rotate(&db, &token)
+        );
+        assert_eq!(
+            [first.is_ok(), second.is_ok()]
+                .iter()
+                .filter(|ok| **ok)
+                .count(),
+            1,
+            "同一令牌只能轮换一次"
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_rejected() {
+        let db = setup_test_db().await;
+        let user = create_user(&db, "expireduser").await;
+        let token = RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+        let mut record = find_by_token(&db, &token)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_active_model();
+        record.expires_at = Set((Utc::now() - ChronoDuration::seconds(1)).into());
+        record.update(&db).await.unwrap();
+
+        let err = RefreshTokenService::rotate(&db, &token).await.unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(msg) if msg.contains("过期")));
+        assert!(RefreshTokenService::rotate(&db, "unknown").await.is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! 登录会话：每次登录创建一个会话，对应一个刷新令牌家族（`refresh_tokens.family_id`），
+//! 会话签发的访问令牌带有会话ID（`sid`），撤销会话时一并失效。
+
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::dto::session::SessionResponse;
+use crate::models::user_sessions;
+use crate::services::EmailService;
+use crate::services::refresh_token::RefreshTokenService;
+use crate::utils::jwt::TokenClaims;
+use crate::utils::rate_limit::client_ip;
+use crate::utils::token_revocation::revoke_session;
+use actix_web::http::header::USER_AGENT;
+use actix_web::{HttpRequest, web};
+use chrono::{Duration as ChronoDuration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+/// User-Agent 最大保存长度，与表结构一致
+const USER_AGENT_MAX_CHARS: usize = 512;
+
+/// 登录请求的来源信息
+#[derive(Clone)]
+pub struct LoginContext {
+    /// 登录方式：password / email / phone / oauth / two_factor
+    pub method: &'static str,
+    pub ip: String,
+    pub user_agent: Option<String>,
+    /// 用于发送新设备登录提醒
+    pub email_service: Option<web::Data<EmailService>>,
+}
+
+impl LoginContext {
+    pub fn new(req: &HttpRequest, method: &'static str) -> Self {
+        let user_agent = req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|ua| ua.chars().take(USER_AGENT_MAX_CHARS).collect());
+        Self {
+            method,
+            ip: client_ip(req),
+            user_agent,
+            email_service: req.app_data::<web::Data<EmailService>>().cloned(),
+        }
+    }
+}
+
+/// 登录会话服务
+pub struct SessionService;
+
+impl SessionService {
+    /// 登录成功时创建会话，返回会话ID
+    pub async fn start(
+        db: &DatabaseConnection,
+        user_id: i32,
+        ctx: &LoginContext,
+    ) -> Result<String, AppError> {
+        let now = Utc::now();
+        let session = user_sessions::ActiveModel {
+            id: Set(Uuid::new_v4().to_string()),
+            user_id: Set(user_id),
+            login_method: Set(ctx.method.to_string()),
+            ip: Set(Some(ctx.ip.clone())),
+            user_agent: Set(ctx.user_agent.clone()),
+            expires_at: Set((now + ChronoDuration::seconds(CONFIG.jwt.refresh_expiry)).into()),
+            last_used_at: Set(Some(now.into())),
+            revoked_at: Set(None),
+            created_at: Set(Some(now.into())),
+        }
+        .insert(db)
+        .await?;
+        Ok(session.id)
+    }
+
+    /// 刷新令牌时顺延会话有效期
+    pub async fn touch(db: &DatabaseConnection, session_id: &str) -> Result<(), AppError> {
+        let now = Utc::now();
+        user_sessions::Entity::update_many()
+            .col_expr(
+                user_sessions::Column::LastUsedAt,
+                Expr::value(now.fixed_offset()),
+            )
+            .col_expr(
+                user_sessions::Column::ExpiresAt,
+                Expr::value(
+                    (now + ChronoDuration::seconds(CONFIG.jwt.refresh_expiry)).fixed_offset(),
+                ),
+            )
+            .filter(user_sessions::Column::Id.eq(session_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// 当前用户仍然有效的会话，最近使用的在前
+    pub async fn list(
+        db: &DatabaseConnection,
+        claims: &TokenClaims,
+    ) -> Result<Vec<SessionResponse>, AppError> {
+        let sessions = active_sessions(claims.user_id)
+            .order_by_desc(user_sessions::Column::LastUsedAt)
+            .all(db)
+            .await?;
+        Ok(sessions
+            .into_iter()
+            .map(|session| {
+                let current = claims.sid.as_deref() == Some(session.id.as_str());
+                SessionResponse::from_model(session, current)
+            })
+            .collect())
+    }
+
+    /// 撤销当前用户的某个会话
+    pub async fn revoke(
+        db: &DatabaseConnection,
+        claims: &TokenClaims,
+        session_id: &str,
+    ) -> Result<(), AppError> {
+        ensure_session(claims)?;
+        let session = active_sessions(claims.user_id)
+            .filter(user_sessions::Column::Id.eq(session_id))
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("会话不存在或已失效".to_string()))?;
+        revoke_one(db, &session.id).await
+    }
+
+    /// 撤销当前会话以外的全部会话，返回撤销数量
+    pub async fn revoke_others(
+        db: &DatabaseConnection,
+        claims: &TokenClaims,
+    ) -> Result<usize, AppError> {
+        let current = ensure_session(claims)?;
+        let sessions = active_sessions(claims.user_id)
+            .filter(user_sessions::Column::Id.ne(current))
+            .all(db)
+            .await?;
+        let count = sessions.len();
+        for session in sessions {
+            revoke_one(db, &session.id).await?;
+        }
+        Ok(count)
+    }
+}
+
+fn active_sessions(user_id: i32) -> sea_orm::Select<user_sessions::Entity> {
+    user_sessions::Entity::find()
+        .filter(user_sessions::Column::UserId.eq(user_id))
+        .filter(user_sessions::Column::RevokedAt.is_null())
+        .filter(user_sessions::Column::ExpiresAt.gt(Utc::now()))
+}
+
+/// 作废会话的刷新令牌家族，并让该会话签发的访问令牌立即失效
+async fn revoke_one(db: &DatabaseConnection, session_id: &str) -> Result<(), AppError> {
+    RefreshTokenService::revoke_family(db, session_id).await?;
+    revoke_session(session_id).await;
+    Ok(())
+}
+
+/// 会话管理只能在登录会话中进行，返回当前会话ID
+fn ensure_session(claims: &TokenClaims) -> Result<&str, AppError> {
+    claims
+        .sid
+        .as_deref()
+        .ok_or_else(|| AppError::Forbidden("个人访问令牌不能用于管理登录会话".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::refresh_tokens;
+    use crate::services::test_db::{create_user_with_id, setup_test_db};
+    use crate::utils::token_revocation::is_revoked;
+
+    fn login_context() -> LoginContext {
+        LoginContext {
+            method: "password",
+            ip: "127.0.0.1".to_string(),
+            user_agent: Some("test-agent".to_string()),
+            email_service: None,
+        }
+    }
+
+    fn claims(user_id: i32, sid: Option<&str>) -> TokenClaims {
+        let now = Utc::now();
+        TokenClaims {
+            user_uuid: Uuid::new_v4().to_string(),
+            user_name: "sessionuser".to_string(),
+            user_id,
+            jti: Uuid::new_v4().to_string(),
+            iat: now.timestamp(),
+            iat_ms: now.timestamp_millis(),
+            exp: now.timestamp() + 600,
+            role_ids: Vec::new(),
+            scopes: None,
+            sid: sid.map(String::from),
+        }
+    }
+
+    /// 登录一次：创建会话及对应的刷新令牌家族
+    async fn login(db: &DatabaseConnection, user_id: i32) -> String {
+        let sid = SessionService::start(db, user_id, &login_context())
+            .await
+            .unwrap();
+        RefreshTokenService::issue(db, user_id, Some(sid.clone()))
+            .await
+            .unwrap();
+        sid
+    }
+
+    async fn is_session_revoked(db: &DatabaseConnection, sid: &str) -> bool {
+        let session = user_sessions::Entity::find_by_id(sid.to_string())
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap();
+        let family_revoked = refresh_tokens::Entity::find()
+            .filter(refresh_tokens::Column::FamilyId.eq(sid))
+            .all(db)
+            .await
+            .unwrap()
+            .iter()
+            .all(|token| token.revoked_at.is_some());
+        assert_eq!(
+            session.revoked_at.is_some(),
+            family_revoked,
+            "会话与刷新令牌家族应同时作废"
+        );
+        family_revoked
+    }
+
+    #[tokio::test]
+    async fn revoke_ends_session_and_refresh_family() {
+        let db = setup_test_db().await;
+        let user = create_user_with_id(&db, 8101, "revokeone").await;
+        let current = login(&db, user.id).await;
+        let other = login(&db, user.id).await;
+        let current_claims = claims(user.id, Some(&current));
+
+        SessionService::revoke(&db, &current_claims, &other)
+            .await
+            .unwrap();
+        assert!(is_session_revoked(&db, &other).await);
+        assert!(!is_session_revoked(&db, &current).await);
+        // 被撤销会话签发的访问令牌立即失效
+        assert!(is_revoked(&claims(user.id, Some(&other))).await);
+        assert!(!is_revoked(&current_claims).await);
+
+        let sessions = SessionService::list(&db, &current_claims).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].current);
+
+        let err = SessionService::revoke(&db, &current_claims, &other)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+        // 不能撤销其他用户的会话
+        let stranger = create_user_with_id(&db, 8102, "stranger").await;
+        let err = SessionService::revoke(&db, &claims(stranger.id, Some("x")), &current)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn revoke_others_keeps_current_session() {
+        let db = setup_test_db().await;
+        let user = create_user_with_id(&db, 8201, "revokeothers").await;
+        let current = login(&db, user.id).await;
+        let others = [login(&db, user.id).await, login(&db, user.id).await];
+        let current_claims = claims(user.id, Some(&current));
+
+        let count = SessionService::revoke_others(&db, &current_claims)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        for sid in &others {
+            assert!(is_session_revoked(&db, sid).await);
+            assert!(is_revoked(&claims(user.id, Some(sid))).await);
+        }
+        assert!(!is_session_revoked(&db, &current).await);
+        assert!(!is_revoked(&current_claims).await);
+        assert_eq!(
+            SessionService::revoke_others(&db, &current_claims)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn personal_access_token_cannot_manage_sessions() {
+        let db = setup_test_db().await;
+        let user = create_user_with_id(&db, 8301, "patuser").await;
+        let sid = login(&db, user.id).await;
+        let pat_claims = claims(user.id, None);
+
+        assert!(matches!(
+            SessionService::revoke(&db, &pat_claims, &sid).await,
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            SessionService::revoke_others(&db, &pat_claims).await,
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(!is_session_revoked(&db, &sid).await);
+    }
+}
@@ -0,0 +1,310 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+use crate::config::manager::CONFIG;
+use crate::services::EmailService;
+
+/// 同一验证码允许输错的次数，超过后作废，需重新获取
+const MAX_ATTEMPTS: u32 = 5;
+
+/// 短信配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmsSettings {
+    /// 短信服务商：`log` 只打印日志，`file` 追加写入文件（均用于开发环境）
+    pub provider: String,
+    /// `file` 服务商写入的文件路径
+    pub file_path: String,
+    /// 验证码有效期（秒）
+    pub code_validity_period: u64,
+}
+
+/// 短信服务商。接入真实服务商（阿里云、腾讯云等）时实现该 trait 即可
+pub trait SmsProvider: Send + Sync {
+    /// 发送短信
+    fn send(&self, phone: &str, message: &str) -> Result<()>;
+}
+
+/// 开发用：把短信内容打印到日志
+pub struct LogSmsProvider;
+
+impl SmsProvider for LogSmsProvider {
+    fn send(&self, phone: &str, message: &str) -> Result<()> {
+        log::info!("[SMS] to {phone}: {message}");
+        Ok(())
+    }
+}
+
+/// 开发用：把短信内容追加写入文件，便于测试脚本读取验证码
+pub struct FileSmsProvider {
+    path: String,
+}
+
+impl FileSmsProvider {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl SmsProvider for FileSmsProvider {
+    fn send(&self, phone: &str, message: &str) -> Result<()> {
+        if let Some(dir) = std::path::Path::new(&self.path).parent() {
+            std::fs::create_dir_all(dir).context("Failed to create sms file directory")?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open sms file {}", self.path))?;
+        writeln!(
+            file,
+            "{}\t{phone}\t{message}",
+            chrono::Utc::now().to_rfc3339()
+        )
+        .context("Failed to write sms file")?;
+        Ok(())
+    }
+}
+
+/// 短信服务
+pub struct SmsService {
+    provider: Box<dyn SmsProvider>,
+    settings: SmsSettings,
+}
+
+impl Default for SmsService {
+    fn default() -> Self {
+        let settings = CONFIG.sms.clone();
+        let provider: Box<dyn SmsProvider> = match settings.provider.as_str() {
+            "file" => Box::new(FileSmsProvider::new(settings.file_path.clone())),
+            "log" => Box::new(LogSmsProvider),
+            other => {
+                log::warn!("未知的短信服务商 {other}，改用日志输出");
+                Box::new(LogSmsProvider)
+            }
+        };
+        Self::new(provider, settings)
+    }
+}
+
+impl SmsService {
+    pub fn new(provider: Box<dyn SmsProvider>, settings: SmsSettings) -> Self {
+        Self { provider, settings }
+    }
+
+    /// 发送验证码短信
+    pub fn send_verification_code(&self, phone: &str, code: &str) -> Result<()> {
+        let message = format!(
+            "【博客系统】您的验证码是{}，{}分钟内有效。如非本人操作，请忽略本短信。",
+            code,
+            self.settings.code_validity_period / 60
+        );
+        self.provider.send(phone, &message)
+    }
+}
+
+/// 短信验证码缓存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmsVerificationCode {
+    /// 验证码
+    pub code: String,
+    /// 创建时间（Unix时间戳）
+    pub created_at: u64,
+    /// 是否已使用
+    pub used: bool,
+    /// 已输错的次数
+    #[serde(default)]
+    pub failed_attempts: u32,
+}
+
+impl SmsVerificationCode {
+    /// 创建新的验证码记录
+    pub fn new(code: String) -> Self {
+        Self {
+            code,
+            created_at: now_secs(),
+            used: false,
+            failed_attempts: 0,
+        }
+    }
+
+    /// 检查验证码是否有效
+    pub fn is_valid(&self, input_code: &str, validity_period: u64) -> bool {
+        !self.used && self.code == input_code && !self.is_expired(validity_period)
+    }
+
+    fn is_expired(&self, validity_period: u64) -> bool {
+        now_secs().saturating_sub(self.created_at) > validity_period
+    }
+}
+
+/// 短信验证码管理器，与 `EmailVerificationManager` 相同：一次性使用、过期失效
+pub struct SmsVerificationManager {
+    /// 手机号 → 验证码（生产环境应使用Redis等）
+    codes: Arc<RwLock<HashMap<String, SmsVerificationCode>>>,
+    /// 验证码有效期（秒）
+    validity_period: u64,
+}
+
+impl Default for SmsVerificationManager {
+    fn default() -> Self {
+        Self::new(CONFIG.sms.code_validity_period)
+    }
+}
+
+impl SmsVerificationManager {
+    /// 创建新的验证码管理器
+    pub fn new(validity_period: u64) -> Self {
+        Self {
+            codes: Arc::new(RwLock::new(HashMap::new())),
+            validity_period,
+        }
+    }
+
+    /// 生成并发送验证码，新验证码会覆盖该手机号之前的验证码
+    pub async fn generate_and_send_code(
+        &self,
+        sms_service: &SmsService,
+        phone: &str,
+    ) -> Result<()> {
+        let code = EmailService::generate_verification_code();
+        sms_service.send_verification_code(phone, &code)?;
+        self.codes
+            .write()
+            .await
+            .insert(phone.to_string(), SmsVerificationCode::new(code));
+        Ok(())
+    }
+
+    /// 测试用：直接写入已知的验证码
+    #[cfg(test)]
+    pub(crate) async fn insert_code(&self, phone: &str, code: &str) {
+        self.codes.write().await.insert(
+            phone.to_string(),
+            SmsVerificationCode::new(code.to_string()),
+        );
+    }
+
+    /// 验证验证码，验证通过后立即作废；输错 `MAX_ATTEMPTS` 次后同样作废，防止暴力猜测
+    pub async fn verify_code(&self, phone: &str, code: &str) -> bool {
+        let mut codes = self.codes.write().await;
+        let Some(verification_code) = codes.get_mut(phone) else {
+            return false;
+        };
+        if verification_code.is_valid(code, self.validity_period) {
+            verification_code.used = true;
+            return true;
+        }
+        verification_code.failed_attempts += 1;
+        if verification_code.failed_attempts >= MAX_ATTEMPTS {
+            log::warn!("sms code for {phone} invalidated after {MAX_ATTEMPTS} failed attempts");
+            verification_code.used = true;
+        }
+        false
+    }
+
+    /// 启动定期清理过期验证码的任务
+    pub fn start_cleanup_task(&self) {
+        let codes = self.codes.clone();
+        let validity_period = self.validity_period;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60 * 5)); // 每5分钟清理一次
+
+            loop {
+                interval.tick().await;
+                codes.write().await.retain(|_, verification_code| {
+                    !verification_code.used && !verification_code.is_expired(validity_period)
+                });
+                log::debug!("Cleaned up expired sms verification codes");
+            }
+        });
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 记录发送内容的测试服务商
+    struct RecordingProvider(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl SmsProvider for RecordingProvider {
+        fn send(&self, phone: &str, message: &str) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((phone.to_string(), message.to_string()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn sms_code_is_single_use() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let settings = SmsSettings {
+            provider: "test".to_string(),
+            file_path: String::new(),
+            code_validity_period: 300,
+        };
+        let service = SmsService::new(Box::new(RecordingProvider(sent.clone())), settings);
+        let manager = SmsVerificationManager::new(300);
+
+        manager
+            .generate_and_send_code(&service, "13800138000")
+            .await
+            .unwrap();
+        let code = {
+            let sent = sent.lock().unwrap();
+            assert_eq!(sent[0].0, "13800138000");
+            sent[0]
+                .1
+                .chars()
+                .filter(char::is_ascii_digit)
+                .take(6)
+                .collect::<String>()
+        };
+
+        assert!(!manager.verify_code("13800138001", &code).await);
+        assert!(manager.verify_code("13800138000", &code).await);
+        assert!(!manager.verify_code("13800138000", &code).await);
+    }
+
+    #[tokio::test]
+    async fn sms_code_is_invalidated_after_too_many_failures() {
+        let manager = SmsVerificationManager::new(300);
+        manager.insert_code("13800138000", "123456").await;
+        for _ in 0..MAX_ATTEMPTS - 1 {
+            assert!(!manager.verify_code("13800138000", "000000").await);
+        }
+        // 未达上限前仍可使用
+        assert!(manager.verify_code("13800138000", "123456").await);
+
+        manager.insert_code("13800138000", "123456").await;
+        for _ in 0..MAX_ATTEMPTS {
+            assert!(!manager.verify_code("13800138000", "000000").await);
+        }
+        assert!(!manager.verify_code("13800138000", "123456").await);
+    }
+
+    #[test]
+    fn future_created_at_does_not_overflow() {
+        let mut code = SmsVerificationCode::new("123456".to_string());
+        // 系统时间回拨时 created_at 可能晚于当前时间
+        code.created_at = now_secs() + 60;
+        assert!(!code.is_expired(300));
+        assert!(code.is_valid("123456", 300));
+    }
+}
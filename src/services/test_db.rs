@@ -0,0 +1,204 @@
+//! 服务层测试共用的 SQLite 内存数据库：用户、角色权限、登录会话与账号令牌相关的表
+use crate::models::users;
+use crate::utils::crypto_pwd::hash;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ConnectionTrait, Database, DatabaseConnection, NotSet, Set,
+    Statement,
+};
+use uuid::Uuid;
+
+// 简化的表结构，与 src/sql 中的定义保持字段一致
+const TABLES: &[&str] = &[
+    r#"
+    CREATE TABLE users (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        uuid TEXT NOT NULL UNIQUE,
+        user_name TEXT NOT NULL UNIQUE,
+        pass_word TEXT NOT NULL,
+        email TEXT UNIQUE,
+        email_verified BOOLEAN NOT NULL DEFAULT FALSE,
+        image TEXT,
+        phone TEXT UNIQUE,
+        binding TEXT,
+        status TEXT NOT NULL DEFAULT 'active',
+        status_reason TEXT,
+        banned_until TEXT,
+        deleted_at TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    )
+    "#,
+    r#"
+    CREATE TABLE roles (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        code TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        description TEXT,
+        is_system INTEGER,
+        created_at TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE user_roles (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+        role_id INTEGER REFERENCES roles(id) ON DELETE CASCADE,
+        is_primary INTEGER,
+        created_at TEXT NOT NULL,
+        UNIQUE (user_id, role_id)
+    )
+    "#,
+    r#"
+    CREATE TABLE permissions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        code TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        description TEXT,
+        category TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE role_permissions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        role_id INTEGER REFERENCES roles(id) ON DELETE CASCADE,
+        permission_id INTEGER REFERENCES permissions(id) ON DELETE CASCADE,
+        created_at TEXT,
+        UNIQUE (role_id, permission_id)
+    )
+    "#,
+    r#"
+    CREATE TABLE user_permissions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+        permission_id INTEGER REFERENCES permissions(id) ON DELETE CASCADE,
+        granted INTEGER,
+        expires_at TEXT,
+        created_at TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE user_sessions (
+        id TEXT PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        login_method TEXT NOT NULL,
+        ip TEXT,
+        user_agent TEXT,
+        expires_at TEXT NOT NULL,
+        last_used_at TEXT,
+        revoked_at TEXT,
+        created_at TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE refresh_tokens (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        token_hash TEXT NOT NULL UNIQUE,
+        family_id TEXT NOT NULL,
+        expires_at TEXT NOT NULL,
+        revoked_at TEXT,
+        created_at TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE login_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+        account TEXT,
+        login_method TEXT NOT NULL,
+        success INTEGER NOT NULL,
+        failure_reason TEXT,
+        session_id TEXT,
+        ip TEXT,
+        user_agent TEXT,
+        created_at TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE user_action_tokens (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        purpose TEXT NOT NULL,
+        jti TEXT NOT NULL UNIQUE,
+        expires_at TEXT NOT NULL,
+        used_at TEXT,
+        created_at TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE user_oauth_bindings (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        provider TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        email TEXT,
+        display_name TEXT,
+        avatar TEXT,
+        created_at TEXT,
+        UNIQUE (provider, subject)
+    )
+    "#,
+    r#"
+    CREATE TABLE api_tokens (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        token_prefix TEXT NOT NULL UNIQUE,
+        token_hash TEXT NOT NULL,
+        scopes TEXT NOT NULL,
+        expires_at TEXT NOT NULL,
+        last_used_at TEXT,
+        revoked_at TEXT,
+        created_at TEXT
+    )
+    "#,
+];
+
+/// 创建内存数据库及全部表
+pub(crate) async fn setup_test_db() -> DatabaseConnection {
+    let db = Database::connect("sqlite::memory:")
+        .await
+        .expect("Failed to connect to test database");
+    for sql in TABLES {
+        db.execute(Statement::from_string(db.get_database_backend(), *sql))
+            .await
+            .expect("Failed to create test table");
+    }
+    db
+}
+
+/// 创建用户，密码为 `password123`
+pub(crate) async fn create_user(db: &DatabaseConnection, user_name: &str) -> users::Model {
+    insert_user(db, NotSet, user_name).await
+}
+
+/// 以指定ID创建用户。令牌撤销名单是按用户ID记录的全局状态，
+/// 断言令牌未被撤销的测试需要使用与其他测试不同的ID
+pub(crate) async fn create_user_with_id(
+    db: &DatabaseConnection,
+    id: i32,
+    user_name: &str,
+) -> users::Model {
+    insert_user(db, Set(id), user_name).await
+}
+
+async fn insert_user(
+    db: &DatabaseConnection,
+    id: ActiveValue<i32>,
+    user_name: &str,
+) -> users::Model {
+    users::ActiveModel {
+        id,
+        uuid: Set(Uuid::new_v4().to_string()),
+        user_name: Set(user_name.to_string()),
+        pass_word: Set(hash("password123").unwrap()),
+        email: Set(Some(format!("{user_name}@example.com"))),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
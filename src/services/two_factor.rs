@@ -0,0 +1,486 @@
+//! 基于 TOTP 的两步验证。
+//!
+//! 启用流程：`setup` 生成密钥 → 用户在认证器中添加 → `enable` 提交验证码确认并获得恢复码。
+//! 登录时密码校验通过后只返回挑战令牌，凭挑战令牌与验证码（或恢复码）才能换取登录会话。
+
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::dto::two_factor::{
+    RecoveryCodesResponse, TwoFactorSetupResponse, TwoFactorStatusResponse,
+};
+use crate::models::{two_factor_recovery_codes, user_two_factor, users};
+use crate::utils::jwt::TokenClaims;
+use base64::engine::{Engine as _, general_purpose};
+use chrono::Utc;
+use rand::{Rng, RngCore};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// 验证码位数
+const TOTP_DIGITS: usize = 6;
+/// 时间步长（秒）
+const TOTP_STEP: u64 = 30;
+/// 允许前后偏差的时间步数，容忍客户端时钟误差
+const TOTP_SKEW: u64 = 1;
+/// 每次生成的恢复码数量
+const RECOVERY_CODE_COUNT: usize = 10;
+/// 恢复码字符集，去掉了容易混淆的 0/1/i/l/o
+const RECOVERY_CODE_ALPHABET: &[u8] = b"23456789abcdefghjkmnpqrstuvwxyz";
+/// 单个挑战令牌允许的验证失败次数
+const MAX_CHALLENGE_ATTEMPTS: u32 = 5;
+
+/// 两步验证配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorSettings {
+    /// 认证器中显示的签发者名称
+    pub issuer: String,
+    /// 登录挑战令牌有效期（秒）
+    pub challenge_ttl: u64,
+}
+
+/// 两步验证服务
+pub struct TwoFactorService;
+
+impl TwoFactorService {
+    /// 查询两步验证状态
+    pub async fn status(
+        db: &DatabaseConnection,
+        user_id: i32,
+    ) -> Result<TwoFactorStatusResponse, AppError> {
+        let enabled = Self::is_enabled(db, user_id).await?;
+        let recovery_codes_remaining = if enabled {
+            two_factor_recovery_codes::Entity::find()
+                .filter(two_factor_recovery_codes::Column::UserId.eq(user_id))
+                .filter(two_factor_recovery_codes::Column::UsedAt.is_null())
+                .count(db)
+                .await?
+        } else {
+            0
+        };
+        Ok(TwoFactorStatusResponse {
+            enabled,
+            recovery_codes_remaining,
+        })
+    }
+
+    /// 用户是否已启用两步验证
+    pub async fn is_enabled(db: &DatabaseConnection, user_id: i32) -> Result<bool, AppError> {
+        Ok(user_two_factor::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .is_some_and(|record| record.enabled_at.is_some()))
+    }
+
+    /// 生成新的密钥，确认前不生效；重复调用会替换未确认的密钥
+    pub async fn setup(
+        db: &DatabaseConnection,
+        user: &TokenClaims,
+    ) -> Result<TwoFactorSetupResponse, AppError> {
+        ensure_session(user)?;
+        let existing = user_two_factor::Entity::find_by_id(user.user_id)
+            .one(db)
+            .await?;
+        if existing.as_ref().is_some_and(|r| r.enabled_at.is_some()) {
+            return Err(AppError::Conflict("已启用两步验证".to_string()));
+        }
+
+        let secret = Secret::generate_secret().to_encoded().to_string();
+        let totp = build_totp(&secret, &user.user_name)?;
+        match existing {
+            Some(record) => {
+                let mut record = record.into_active_model();
+                record.secret = Set(secret.clone());
+                record.last_used_step = Set(None);
+                record.update(db).await?;
+            }
+            None => {
+                user_two_factor::ActiveModel {
+                    user_id: Set(user.user_id),
+                    secret: Set(secret.clone()),
+                    enabled_at: Set(None),
+                    last_used_step: Set(None),
+                    created_at: Set(Some(Utc::now().into())),
+                }
+                .insert(db)
+                .await?;
+            }
+        }
+        Ok(TwoFactorSetupResponse {
+            secret,
+            otpauth_uri: totp.get_url(),
+        })
+    }
+
+    /// 提交认证器上的验证码确认启用，返回恢复码
+    pub async fn enable(
+        db: &DatabaseConnection,
+        user: &TokenClaims,
+        code: &str,
+    ) -> Result<RecoveryCodesResponse, AppError> {
+        ensure_session(user)?;
+        let record = user_two_factor::Entity::find_by_id(user.user_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("请先生成两步验证密钥".to_string()))?;
+        if record.enabled_at.is_some() {
+            return Err(AppError::Conflict("已启用两步验证".to_string()));
+        }
+        let step = match_totp(&record.secret, &user.user_name, code)?
+            .ok_or_else(|| AppError::BadRequest("验证码错误".to_string()))?;
+
+        let txn = db.begin().await?;
+        let mut record = record.into_active_model();
+        record.enabled_at = Set(Some(Utc::now().into()));
+        record.last_used_step = Set(Some(step));
+        record.update(&txn).await?;
+        let recovery_codes = replace_recovery_codes(&txn, user.user_id).await?;
+        txn.commit().await?;
+
+        log::info!("two-factor enabled for user {}", user.user_name);
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    /// 关闭两步验证，需要验证码或恢复码
+    pub async fn disable(
+        db: &DatabaseConnection,
+        user: &TokenClaims,
+        code: &str,
+    ) -> Result<(), AppError> {
+        ensure_session(user)?;
+        if !Self::verify(db, user.user_id, &user.user_name, code).await? {
+            return Err(AppError::BadRequest("验证码错误".to_string()));
+        }
+        let txn = db.begin().await?;
+        two_factor_recovery_codes::Entity::delete_many()
+            .filter(two_factor_recovery_codes::Column::UserId.eq(user.user_id))
+            .exec(&txn)
+            .await?;
+        user_two_factor::Entity::delete_by_id(user.user_id)
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+
+        log::warn!("two-factor disabled for user {}", user.user_name);
+        Ok(())
+    }
+
+    /// 重新生成恢复码，旧恢复码全部作废；只接受认证器验证码
+    pub async fn regenerate_recovery_codes(
+        db: &DatabaseConnection,
+        user: &TokenClaims,
+        code: &str,
+    ) -> Result<RecoveryCodesResponse, AppError> {
+        ensure_session(user)?;
+        let record = enabled_record(db, user.user_id).await?;
+        if !Self::check_totp(db, record, &user.user_name, code).await? {
+            return Err(AppError::BadRequest("验证码错误".to_string()));
+        }
+        let txn = db.begin().await?;
+        let recovery_codes = replace_recovery_codes(&txn, user.user_id).await?;
+        txn.commit().await?;
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    /// 校验验证码或恢复码。验证码同一时间步只能使用一次，恢复码使用后作废
+    pub async fn verify(
+        db: &DatabaseConnection,
+        user_id: i32,
+        user_name: &str,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        let record = enabled_record(db, user_id).await?;
+        let code = code.trim();
+        if code.len() == TOTP_DIGITS && code.bytes().all(|b| b.is_ascii_digit()) {
+            return Self::check_totp(db, record, user_name, code).await;
+        }
+
+        let code_hash = hash_code(&normalize_recovery_code(code));
+        let Some(recovery_code) = two_factor_recovery_codes::Entity::find()
+            .filter(two_factor_recovery_codes::Column::UserId.eq(user_id))
+            .filter(two_factor_recovery_codes::Column::CodeHash.eq(code_hash))
+            .filter(two_factor_recovery_codes::Column::UsedAt.is_null())
+            .one(db)
+            .await?
+        else {
+            return Ok(false);
+        };
+        let mut recovery_code = recovery_code.into_active_model();
+        recovery_code.used_at = Set(Some(Utc::now().into()));
+        recovery_code.update(db).await?;
+        log::warn!("recovery code used by user {user_name}");
+        Ok(true)
+    }
+
+    async fn check_totp(
+        db: &DatabaseConnection,
+        record: user_two_factor::Model,
+        user_name: &str,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        let Some(step) = match_totp(&record.secret, user_name, code)? else {
+            return Ok(false);
+        };
+        if record.last_used_step.is_some_and(|last| step <= last) {
+            return Ok(false);
+        }
+        let mut record = record.into_active_model();
+        record.last_used_step = Set(Some(step));
+        record.update(db).await?;
+        Ok(true)
+    }
+}
+
+/// 登录挑战
+struct Challenge {
+    user_id: i32,
+    created_at: u64,
+    failed_attempts: u32,
+}
+
+/// 登录挑战令牌管理器，挑战令牌一次性使用、过期失效
+pub struct TwoFactorChallengeManager {
+    /// 挑战令牌 → 挑战（生产环境应使用Redis等）
+    challenges: Arc<RwLock<HashMap<String, Challenge>>>,
+    /// 挑战令牌有效期（秒）
+    ttl: u64,
+}
+
+impl Default for TwoFactorChallengeManager {
+    fn default() -> Self {
+        Self::new(CONFIG.two_factor.challenge_ttl)
+    }
+}
+
+impl TwoFactorChallengeManager {
+    pub fn new(ttl: u64) -> Self {
+        Self {
+            challenges: Arc::new(RwLock::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// 挑战令牌有效期（秒）
+    pub fn ttl(&self) -> u64 {
+        self.ttl
+    }
+
+    /// 密码校验通过后签发挑战令牌
+    pub async fn issue(&self, user_id: i32) -> String {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+        self.challenges.write().await.insert(
+            token.clone(),
+            Challenge {
+                user_id,
+                created_at: now_secs(),
+                failed_attempts: 0,
+            },
+        );
+        token
+    }
+
+    /// 用挑战令牌与验证码完成登录，成功时返回用户。失败次数过多时挑战令牌作废
+    pub async fn complete(
+        &self,
+        db: &DatabaseConnection,
+        token: &str,
+        code: &str,
+    ) -> Result<users::Model, AppError> {
+        let expired = || AppError::Unauthorized("登录挑战无效或已过期，请重新登录".to_string());
+        let user_id = match self.challenges.read().await.get(token) {
+            Some(challenge) if now_secs() - challenge.created_at <= self.ttl => challenge.user_id,
+            _ => return Err(expired()),
+        };
+        let user = users::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .ok_or_else(expired)?;
+
+        if TwoFactorService::verify(db, user.id, &user.user_name, code).await? {
+            self.challenges.write().await.remove(token);
+            return Ok(user);
+        }
+
+        let mut challenges = self.challenges.write().await;
+        if let Some(challenge) = challenges.get_mut(token) {
+            challenge.failed_attempts += 1;
+            if challenge.failed_attempts >= MAX_CHALLENGE_ATTEMPTS {
+                challenges.remove(token);
+                return Err(AppError::Unauthorized(
+                    "验证码错误次数过多，请重新登录".to_string(),
+                ));
+            }
+        }
+        Err(AppError::Unauthorized("验证码错误".to_string()))
+    }
+
+    /// 启动定期清理过期挑战的任务
+    pub fn start_cleanup_task(&self) {
+        let challenges = self.challenges.clone();
+        let ttl = self.ttl;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60 * 5)); // 每5分钟清理一次
+
+            loop {
+                interval.tick().await;
+                let now = now_secs();
+                challenges
+                    .write()
+                    .await
+                    .retain(|_, challenge| now - challenge.created_at <= ttl);
+                log::debug!("Cleaned up expired two-factor challenges");
+            }
+        });
+    }
+}
+
+/// 两步验证设置只能在登录会话中进行，个人访问令牌无权修改
+fn ensure_session(user: &TokenClaims) -> Result<(), AppError> {
+    if user.scopes.is_some() {
+        return Err(AppError::Forbidden(
+            "个人访问令牌不能用于设置两步验证".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn enabled_record(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<user_two_factor::Model, AppError> {
+    user_two_factor::Entity::find_by_id(user_id)
+        .one(db)
+        .await?
+        .filter(|record| record.enabled_at.is_some())
+        .ok_or_else(|| AppError::BadRequest("未启用两步验证".to_string()))
+}
+
+fn build_totp(secret: &str, user_name: &str) -> Result<TOTP, AppError> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| AppError::InternalServerError(format!("TOTP 密钥无效: {e:?}")))?;
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        TOTP_SKEW as u8,
+        TOTP_STEP,
+        secret,
+        Some(CONFIG.two_factor.issuer.clone()),
+        // otpauth 标签不允许出现冒号
+        user_name.replace(':', "_"),
+    )
+    .map_err(|e| AppError::InternalServerError(format!("TOTP 配置无效: {e}")))
+}
+
+/// 校验验证码，返回匹配的时间步
+fn match_totp(secret: &str, user_name: &str, code: &str) -> Result<Option<i64>, AppError> {
+    let totp = build_totp(secret, user_name)?;
+    Ok(matching_step(&totp, code.trim(), now_secs()))
+}
+
+fn matching_step(totp: &TOTP, code: &str, now: u64) -> Option<i64> {
+    let current = now / TOTP_STEP;
+    (current.saturating_sub(TOTP_SKEW)..=current + TOTP_SKEW)
+        .find(|step| totp.generate(step * TOTP_STEP) == code)
+        .map(|step| step as i64)
+}
+
+/// 作废旧恢复码并生成新的一组，返回明文
+async fn replace_recovery_codes<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+) -> Result<Vec<String>, AppError> {
+    two_factor_recovery_codes::Entity::delete_many()
+        .filter(two_factor_recovery_codes::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+    let now = Utc::now();
+    two_factor_recovery_codes::Entity::insert_many(codes.iter().map(|code| {
+        two_factor_recovery_codes::ActiveModel {
+            user_id: Set(user_id),
+            code_hash: Set(hash_code(&normalize_recovery_code(code))),
+            used_at: Set(None),
+            created_at: Set(Some(now.into())),
+            ..Default::default()
+        }
+    }))
+    .exec(db)
+    .await?;
+    Ok(codes)
+}
+
+/// 恢复码格式 `xxxxx-xxxxx`
+fn generate_recovery_code() -> String {
+    let mut rng = rand::thread_rng();
+    let mut code: String = (0..10)
+        .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+        .collect();
+    code.insert(5, '-');
+    code
+}
+
+/// 忽略大小写、空格与连字符
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn hash_code(code: &str) -> String {
+    hex::encode(Sha256::digest(code.as_bytes()))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totp_accepts_adjacent_steps_only() {
+        let secret = Secret::generate_secret().to_encoded().to_string();
+        let totp = build_totp(&secret, "admin:root").unwrap();
+        assert!(totp.get_url().starts_with("otpauth://totp/"));
+
+        let now = 1_700_000_000;
+        let previous = totp.generate(now - TOTP_STEP);
+        assert_eq!(
+            matching_step(&totp, &previous, now),
+            Some((now / TOTP_STEP - 1) as i64)
+        );
+        let stale = totp.generate(now - 3 * TOTP_STEP);
+        assert_eq!(matching_step(&totp, &stale, now), None);
+    }
+
+    #[test]
+    fn recovery_code_normalization() {
+        let code = generate_recovery_code();
+        assert_eq!(code.len(), 11);
+        assert_eq!(
+            hash_code(&normalize_recovery_code(
+                &code.to_uppercase().replace('-', " ")
+            )),
+            hash_code(&normalize_recovery_code(&code))
+        );
+    }
+}
@@ -1,5 +1,16 @@
+use crate::dto::user::{UpdateUserRequest, UpdateUserStatusRequest};
+use crate::models::users::{self, UserStatus};
+use crate::models::{api_tokens, user_oauth_bindings};
+use crate::services::account::ensure_unique;
+use crate::services::refresh_token::RefreshTokenService;
+use crate::utils::token_revocation::{revoke_user_tokens, sync_user_status};
 use actix_web::HttpResponse;
-use sea_orm::{DatabaseConnection, EntityTrait, QuerySelect};
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    QuerySelect, Set, TransactionTrait,
+};
 
 // pub struct CategyService;
 pub struct UserService;
@@ -24,3 +35,317 @@ impl UserService {
         }
     }
 }
+
+impl UserService {
+    /// 按 UUID 查找用户
+    pub async fn find_by_uuid(
+        db: &DatabaseConnection,
+        uuid: &str,
+    ) -> Result<users::Model, AppError> {
+        users::Entity::find_by_uuid(uuid)
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("UUID为{uuid}的用户不存在")))
+    }
+
+    /// 管理员修改用户资料，只更新提交的字段
+    pub async fn update(
+        db: &DatabaseConnection,
+        uuid: &str,
+        request: UpdateUserRequest,
+    ) -> Result<users::Model, AppError> {
+        let user = Self::find_by_uuid(db, uuid).await?;
+        let mut active = user.clone().into_active_model();
+        if let Some(user_name) = request.user_name
+            && user_name != user.user_name
+        {
+            ensure_unique(db, users::Column::UserName, &user_name, "用户名已被使用").await?;
+            active.user_name = Set(user_name);
+        }
+        if let Some(image) = request.image {
+            active.image = Set(Some(image).filter(|image| !image.is_empty()));
+        }
+        if let Some(email) = request.email
+            && user.email.as_ref() != Some(&email)
+        {
+            ensure_unique(db, users::Column::Email, &email, "邮箱已被使用").await?;
+            active.email = Set(Some(email));
+            active.email_verified = Set(false);
+        }
+        if let Some(phone) = request.phone
+            && user.phone.as_ref() != Some(&phone)
+        {
+            ensure_unique(db, users::Column::Phone, &phone, "手机号已被使用").await?;
+            active.phone = Set(Some(phone));
+        }
+        if let Some(email_verified) = request.email_verified {
+            active.email_verified = Set(email_verified);
+        }
+        active.updated_at = Set(Utc::now());
+        Ok(active.update(db).await?)
+    }
+
+    /// 设置账号状态（正常、停用、封禁）；停用或封禁时作废该用户的全部会话
+    pub async fn set_status(
+        db: &DatabaseConnection,
+        operator_id: i32,
+        uuid: &str,
+        request: UpdateUserStatusRequest,
+    ) -> Result<users::Model, AppError> {
+        let UpdateUserStatusRequest {
+            status,
+            reason,
+            banned_until,
+        } = request;
+        let user = Self::find_by_uuid(db, uuid).await?;
+        if user.id == operator_id {
+            return Err(AppError::BadRequest("不能修改自己的账号状态".to_string()));
+        }
+        if user.status == UserStatus::Deleted {
+            return Err(AppError::BadRequest("账号已注销，不能修改状态".to_string()));
+        }
+        let now = Utc::now();
+        let (reason, banned_until) = match status {
+            UserStatus::Active => (None, None),
+            UserStatus::Disabled => (reason, None),
+            UserStatus::Banned => {
+                if banned_until.is_some_and(|until| until <= now) {
+                    return Err(AppError::BadRequest(
+                        "封禁截止时间必须晚于当前时间".to_string(),
+                    ));
+                }
+                (reason, banned_until)
+            }
+            UserStatus::Deleted => {
+                return Err(AppError::BadRequest("请使用删除接口注销账号".to_string()));
+            }
+        };
+
+        let mut active = user.into_active_model();
+        active.status = Set(status);
+        active.status_reason = Set(reason);
+        active.banned_until = Set(banned_until);
+        active.updated_at = Set(now);
+        let user = active.update(db).await?;
+        if user.is_blocked(now) {
+            RefreshTokenService::revoke_user(db, user.id).await?;
+            revoke_user_tokens(user.id).await;
+        }
+        sync_user_status(&user).await;
+        log::warn!(
+            "user {} status set to {status:?} by admin {operator_id}",
+            user.user_name
+        );
+        Ok(user)
+    }
+
+    /// 管理员注销用户（软删除）
+    pub async fn delete(
+        db: &DatabaseConnection,
+        operator_id: i32,
+        uuid: &str,
+    ) -> Result<(), AppError> {
+        let user = Self::find_by_uuid(db, uuid).await?;
+        if user.id == operator_id {
+            return Err(AppError::BadRequest(
+                "不能删除自己的账号，请使用注销接口".to_string(),
+            ));
+        }
+        if user.status == UserStatus::Deleted {
+            return Err(AppError::NotFound(format!("UUID为{uuid}的用户不存在")));
+        }
+        let user = Self::soft_delete(db, user).await?;
+        log::warn!("user {} deleted by admin {operator_id}", user.user_name);
+        Ok(())
+    }
+
+    /// 软删除用户。
+    ///
+    /// 保留用户记录（用户名仍被占用）及其文章等内容，释放邮箱与手机号、解除第三方绑定，
+    /// 并作废全部刷新令牌、访问令牌与个人访问令牌
+    pub async fn soft_delete(
+        db: &DatabaseConnection,
+        user: users::Model,
+    ) -> Result<users::Model, AppError> {
+        let user_id = user.id;
+        let now = Utc::now();
+        let txn = db.begin().await?;
+        let mut active = user.into_active_model();
+        active.status = Set(UserStatus::Deleted);
+        active.status_reason = Set(None);
+        active.banned_until = Set(None);
+        active.deleted_at = Set(Some(now));
+        active.email = Set(None);
+        active.email_verified = Set(false);
+        active.phone = Set(None);
+        active.image = Set(None);
+        active.updated_at = Set(now);
+        let user = active.update(&txn).await?;
+        user_oauth_bindings::Entity::delete_many()
+            .filter(user_oauth_bindings::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+        api_tokens::Entity::update_many()
+            .col_expr(
+                api_tokens::Column::RevokedAt,
+                Expr::current_timestamp().into(),
+            )
+            .filter(api_tokens::Column::UserId.eq(user_id))
+            .filter(api_tokens::Column::RevokedAt.is_null())
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+
+        RefreshTokenService::revoke_user(db, user_id).await?;
+        revoke_user_tokens(user_id).await;
+        sync_user_status(&user).await;
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::refresh_tokens;
+    use crate::services::test_db::{create_user, create_user_with_id, setup_test_db};
+    use crate::utils::jwt::TokenClaims;
+    use crate::utils::token_revocation::is_revoked;
+    use uuid::Uuid;
+
+    fn status_request(status: UserStatus) -> UpdateUserStatusRequest {
+        UpdateUserStatusRequest {
+            status,
+            reason: Some("spam".to_string()),
+            banned_until: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn admin_cannot_change_own_status() {
+        let db = setup_test_db().await;
+        let admin = create_user(&db, "adminself").await;
+
+        let err = UserService::set_status(
+            &db,
+            admin.id,
+            &admin.uuid,
+            status_request(UserStatus::Disabled),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+        let admin = UserService::find_by_uuid(&db, &admin.uuid).await.unwrap();
+        assert_eq!(admin.status, UserStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn disabling_user_revokes_sessions() {
+        let db = setup_test_db().await;
+        let admin = create_user_with_id(&db, 8601, "adminuser").await;
+        let user = create_user_with_id(&db, 8602, "disableduser").await;
+        RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+        RefreshTokenService::issue(&db, user.id, None)
+            .await
+            .unwrap();
+
+        let disabled = UserService::set_status(
+            &db,
+            admin.id,
+            &user.uuid,
+            status_request(UserStatus::Disabled),
+        )
+        .await
+        .unwrap();
+        assert_eq!(disabled.status, UserStatus::Disabled);
+        assert_eq!(disabled.status_reason.as_deref(), Some("spam"));
+        let active_tokens = refresh_tokens::Entity::find()
+            .filter(refresh_tokens::Column::UserId.eq(user.id))
+            .filter(refresh_tokens::Column::RevokedAt.is_null())
+            .all(&db)
+            .await
+            .unwrap();
+        assert!(active_tokens.is_empty(), "停用后刷新令牌应全部作废");
+
+        // 已签发的访问令牌也随之失效
+        let now = Utc::now().timestamp();
+        let claims = TokenClaims {
+            user_uuid: user.uuid.clone(),
+            user_name: user.user_name.clone(),
+            user_id: user.id,
+            jti: Uuid::new_v4().to_string(),
+            // 晚于停用时签发，只受用户状态名单影响
+            iat: now + 1,
+            iat_ms: (now + 1) * 1000,
+            exp: now + 600,
+            role_ids: Vec::new(),
+            scopes: None,
+            sid: None,
+        };
+        assert!(is_revoked(&claims).await);
+
+        // 封禁截止时间必须在未来
+        let err = UserService::set_status(
+            &db,
+            admin.id,
+            &user.uuid,
+            UpdateUserStatusRequest {
+                banned_until: Some(Utc::now() - chrono::Duration::hours(1)),
+                ..status_request(UserStatus::Banned)
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+
+        // 恢复正常后清除原因与名单
+        let restored = UserService::set_status(
+            &db,
+            admin.id,
+            &user.uuid,
+            status_request(UserStatus::Active),
+        )
+        .await
+        .unwrap();
+        assert_eq!(restored.status_reason, None);
+        assert!(!is_revoked(&claims).await);
+    }
+
+    #[tokio::test]
+    async fn update_rejects_taken_email_and_user_name() {
+        let db = setup_test_db().await;
+        let user = create_user(&db, "updateuser").await;
+        let other = create_user(&db, "takenuser").await;
+        let request = |user_name: Option<&str>, email: Option<&str>| UpdateUserRequest {
+            user_name: user_name.map(String::from),
+            image: None,
+            email: email.map(String::from),
+            phone: None,
+            email_verified: None,
+        };
+
+        let err = UserService::update(&db, &user.uuid, request(None, other.email.as_deref()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        let err = UserService::update(&db, &user.uuid, request(Some("takenuser"), None))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        // 提交原值不算冲突
+        let updated = UserService::update(
+            &db,
+            &user.uuid,
+            request(Some("updateuser"), user.email.as_deref()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.email, user.email);
+        let updated = UserService::update(&db, &user.uuid, request(Some("renamed"), None))
+            .await
+            .unwrap();
+        assert_eq!(updated.user_name, "renamed");
+    }
+}
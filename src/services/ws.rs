@@ -4,6 +4,7 @@ use chrono::Utc;
 
 use actix_ws::{Message, Session};
 use futures_util::StreamExt;
+use route_macros::route_permission;
 use sea_orm::{ActiveModelTrait as _, ActiveValue::Set, DatabaseConnection};
 use serde_json;
 use tokio::sync::Mutex;
@@ -49,6 +50,11 @@ pub async fn echo(req: HttpRequest, stream: web::Payload) -> Result<HttpResponse
 }
 
 // WebSocket路由处理函数
+#[route_permission(
+    path = "/api/v1/rooms/ws/{room_id}/{user_id}",
+    method = "get",
+    public = true
+)]
 pub async fn chat_route(
     req: HttpRequest,
     stream: web::Payload,
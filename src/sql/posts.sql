@@ -114,6 +114,8 @@ COMMENT ON COLUMN tags.name IS '标签名称';
 CREATE TABLE posts (
     id SERIAL PRIMARY KEY,
     uuid CHAR(36) NOT NULL,
+    -- 固定链接，由标题生成（中文转为拼音）或作者指定
+    slug VARCHAR(100) NOT NULL,
     author_id INT NOT NULL,
     category_id INT,
     title VARCHAR(255) NOT NULL,
@@ -122,15 +124,19 @@ CREATE TABLE posts (
     markdowncontent TEXT NOT NULL,
     cover_image VARCHAR(255),
     status SMALLINT NOT NULL DEFAULT 0,
-    -- 0 草稿 1 发布 2 下线
+    -- 0 草稿 1 发布 2 下线 3 定时发布
     featured BOOLEAN NOT NULL DEFAULT FALSE,
-    -- 文章大小
+    -- 字数（中日韩字符按字计，其他语言按词计）
     size INT NOT NULL DEFAULT 0,
+    reading_minutes INT NOT NULL DEFAULT 0,
+    -- 根据标题生成的目录
+    toc JSONB NOT NULL DEFAULT '[]',
     view_count INT NOT NULL DEFAULT 0,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     published_at TIMESTAMP WITH TIME ZONE,
     UNIQUE (uuid),
+    CONSTRAINT posts_slug_key UNIQUE (slug),
     FOREIGN KEY (author_id) REFERENCES users(id) ON DELETE CASCADE,
     FOREIGN KEY (category_id) REFERENCES categories(id) ON DELETE
     SET
@@ -149,17 +155,25 @@ COMMENT ON TABLE posts IS '文章主表';
 
 COMMENT ON COLUMN posts.uuid IS '全局唯一标识';
 
+COMMENT ON COLUMN posts.slug IS '固定链接中使用的 slug，小写字母、数字与连字符';
+
 COMMENT ON COLUMN posts.author_id IS '作者 FK → users.id';
 
 COMMENT ON COLUMN posts.category_id IS '分类 FK → categories.id';
 
-COMMENT ON COLUMN posts.status IS '0 草稿 1 发布 2 下线';
+COMMENT ON COLUMN posts.status IS '0 草稿 1 发布 2 下线 3 定时发布';
 
 COMMENT ON COLUMN posts.featured IS '是否置顶推荐';
 
 COMMENT ON COLUMN posts.view_count IS '浏览量';
 
-COMMENT ON COLUMN posts.published_at IS '首次发布时间';
+COMMENT ON COLUMN posts.size IS '字数，中日韩字符按字计，其他语言按词计';
+
+COMMENT ON COLUMN posts.reading_minutes IS '预计阅读分钟数';
+
+COMMENT ON COLUMN posts.toc IS '目录 [{level, title, anchor}]';
+
+COMMENT ON COLUMN posts.published_at IS '首次发布时间，定时发布时为计划发布时间，草稿为空';
 
 -- 4️⃣ 文章-标签 多对多中间表
 CREATE TABLE post_tags (
@@ -173,3 +187,65 @@ CREATE TABLE post_tags (
 CREATE INDEX idx_post_tags_tag ON post_tags(tag_id);
 
 COMMENT ON TABLE post_tags IS '文章与标签的多对多关系';
+
+-- 5️⃣ 文章修订记录表，每次更新文章前保存旧版本
+CREATE TABLE post_revisions (
+    id SERIAL PRIMARY KEY,
+    post_id INT NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    summary TEXT,
+    content TEXT NOT NULL,
+    markdowncontent TEXT NOT NULL,
+    category_id INT,
+    tag_ids JSONB NOT NULL DEFAULT '[]',
+    editor_id INT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE,
+    FOREIGN KEY (editor_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_post_revisions_post ON post_revisions(post_id, created_at DESC);
+
+COMMENT ON TABLE post_revisions IS '文章修订记录';
+
+COMMENT ON COLUMN post_revisions.tag_ids IS '当时的标签ID列表';
+
+COMMENT ON COLUMN post_revisions.editor_id IS '用这次修改覆盖该版本的用户 FK → users.id';
+
+COMMENT ON COLUMN post_revisions.created_at IS '被覆盖的时间';
+
+-- 6️⃣ 文章全文搜索索引，标题、摘要、正文由服务端预先分词（中日韩文字按相邻两字切分）后写入
+CREATE TABLE post_search (
+    post_id INT PRIMARY KEY,
+    title TEXT NOT NULL DEFAULT '',
+    summary TEXT NOT NULL DEFAULT '',
+    body TEXT NOT NULL DEFAULT '',
+    document TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', title), 'A')
+        || setweight(to_tsvector('simple', summary), 'B')
+        || setweight(to_tsvector('simple', body), 'C')
+    ) STORED,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_post_search_document ON post_search USING GIN (document);
+
+COMMENT ON TABLE post_search IS '文章全文搜索索引，已有文章可通过 reindex-posts 子命令重建';
+
+COMMENT ON COLUMN post_search.document IS '按标题 A、摘要 B、正文 C 加权的 tsvector';
+
+-- 7️⃣ 文章 slug 历史，修改 slug 后旧的 slug 重定向到文章当前的 slug
+CREATE TABLE post_slug_history (
+    id SERIAL PRIMARY KEY,
+    post_id INT NOT NULL,
+    slug VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT post_slug_history_slug_key UNIQUE (slug),
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_post_slug_history_post ON post_slug_history(post_id);
+
+COMMENT ON TABLE post_slug_history IS '文章曾用的 slug';
+
+COMMENT ON COLUMN post_slug_history.created_at IS '停止使用的时间';
@@ -0,0 +1,98 @@
+-- 已有数据库的文章表升级脚本，新建数据库直接执行 posts.sql 即可。
+--
+-- 执行顺序：
+-- 1. psql -f src/sql/upgrade_posts.sql
+-- 2. cargo run -- reindex-posts
+--    为已有文章生成 slug（由标题生成，重名时追加 -2、-3），
+--    重新渲染正文 HTML、字数、阅读时长与目录，重建搜索索引，
+--    最后为 slug 加上 NOT NULL 与唯一约束（posts_slug_key）。
+-- 本脚本可重复执行。
+-- 1️⃣ 文章表新增字段，slug 先允许为空，由 reindex-posts 回填后再加约束
+ALTER TABLE
+    posts
+ADD
+    COLUMN IF NOT EXISTS slug VARCHAR(100);
+
+ALTER TABLE
+    posts
+ADD
+    COLUMN IF NOT EXISTS reading_minutes INT NOT NULL DEFAULT 0;
+
+ALTER TABLE
+    posts
+ADD
+    COLUMN IF NOT EXISTS toc JSONB NOT NULL DEFAULT '[]';
+
+COMMENT ON COLUMN posts.slug IS '固定链接中使用的 slug，小写字母、数字与连字符';
+
+COMMENT ON COLUMN posts.status IS '0 草稿 1 发布 2 下线 3 定时发布';
+
+COMMENT ON COLUMN posts.size IS '字数，中日韩字符按字计，其他语言按词计';
+
+COMMENT ON COLUMN posts.reading_minutes IS '预计阅读分钟数';
+
+COMMENT ON COLUMN posts.toc IS '目录 [{level, title, anchor}]';
+
+COMMENT ON COLUMN posts.published_at IS '首次发布时间，定时发布时为计划发布时间，草稿为空';
+
+-- 2️⃣ 文章修订记录表
+CREATE TABLE IF NOT EXISTS post_revisions (
+    id SERIAL PRIMARY KEY,
+    post_id INT NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    summary TEXT,
+    content TEXT NOT NULL,
+    markdowncontent TEXT NOT NULL,
+    category_id INT,
+    tag_ids JSONB NOT NULL DEFAULT '[]',
+    editor_id INT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE,
+    FOREIGN KEY (editor_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_revisions_post ON post_revisions(post_id, created_at DESC);
+
+COMMENT ON TABLE post_revisions IS '文章修订记录';
+
+COMMENT ON COLUMN post_revisions.tag_ids IS '当时的标签ID列表';
+
+COMMENT ON COLUMN post_revisions.editor_id IS '用这次修改覆盖该版本的用户 FK → users.id';
+
+COMMENT ON COLUMN post_revisions.created_at IS '被覆盖的时间';
+
+-- 3️⃣ 文章全文搜索索引，内容由 reindex-posts 写入
+CREATE TABLE IF NOT EXISTS post_search (
+    post_id INT PRIMARY KEY,
+    title TEXT NOT NULL DEFAULT '',
+    summary TEXT NOT NULL DEFAULT '',
+    body TEXT NOT NULL DEFAULT '',
+    document TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', title), 'A')
+        || setweight(to_tsvector('simple', summary), 'B')
+        || setweight(to_tsvector('simple', body), 'C')
+    ) STORED,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_search_document ON post_search USING GIN (document);
+
+COMMENT ON TABLE post_search IS '文章全文搜索索引，已有文章可通过 reindex-posts 子命令重建';
+
+COMMENT ON COLUMN post_search.document IS '按标题 A、摘要 B、正文 C 加权的 tsvector';
+
+-- 4️⃣ 文章 slug 历史
+CREATE TABLE IF NOT EXISTS post_slug_history (
+    id SERIAL PRIMARY KEY,
+    post_id INT NOT NULL,
+    slug VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT post_slug_history_slug_key UNIQUE (slug),
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_slug_history_post ON post_slug_history(post_id);
+
+COMMENT ON TABLE post_slug_history IS '文章曾用的 slug';
+
+COMMENT ON COLUMN post_slug_history.created_at IS '停止使用的时间';
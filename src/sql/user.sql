@@ -7,9 +7,14 @@ CREATE TABLE users (
     user_name VARCHAR(255) NOT NULL,
     pass_word VARCHAR(255) NOT NULL,
     email VARCHAR(255),
+    email_verified BOOLEAN NOT NULL DEFAULT FALSE,
     image VARCHAR(255),
     phone VARCHAR(20),
     binding VARCHAR(255),
+    status VARCHAR(16) NOT NULL DEFAULT 'active',
+    status_reason VARCHAR(255),
+    banned_until TIMESTAMP WITH TIME ZONE,
+    deleted_at TIMESTAMP WITH TIME ZONE,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     UNIQUE (uuid),
@@ -82,6 +87,131 @@ CREATE TABLE user_permissions (
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
 );
 
+DROP TABLE IF EXISTS refresh_tokens;
+
+-- 刷新令牌表（只保存哈希，按 family 轮换）
+CREATE TABLE refresh_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash CHAR(64) UNIQUE NOT NULL,
+    family_id CHAR(36) NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_refresh_tokens_user ON refresh_tokens(user_id);
+
+CREATE INDEX idx_refresh_tokens_family ON refresh_tokens(family_id);
+
+DROP TABLE IF EXISTS api_tokens;
+
+-- 个人访问令牌表（用于 CI / 脚本，只保存哈希）
+CREATE TABLE api_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    token_prefix VARCHAR(16) UNIQUE NOT NULL,
+    token_hash VARCHAR(255) NOT NULL,
+    scopes TEXT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_api_tokens_user ON api_tokens(user_id);
+
+DROP TABLE IF EXISTS user_oauth_bindings;
+
+-- 第三方账号绑定表（一个用户可绑定多个服务商）
+CREATE TABLE user_oauth_bindings (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider VARCHAR(50) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    display_name VARCHAR(255),
+    avatar VARCHAR(512),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provider, subject),
+    UNIQUE (user_id, provider)
+);
+
+DROP TABLE IF EXISTS user_two_factor;
+
+-- 两步验证表（TOTP，每个用户一条）
+CREATE TABLE user_two_factor (
+    user_id INT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret VARCHAR(128) NOT NULL,
+    enabled_at TIMESTAMP WITH TIME ZONE,
+    last_used_step BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+DROP TABLE IF EXISTS two_factor_recovery_codes;
+
+-- 两步验证恢复码表（一次性使用，只保存哈希）
+CREATE TABLE two_factor_recovery_codes (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_recovery_codes_user ON two_factor_recovery_codes(user_id);
+
+DROP TABLE IF EXISTS user_action_tokens;
+
+-- 一次性操作令牌表（邮箱验证、密码重置），令牌本身为签名 JWT，这里记录 jti 用于单次使用
+CREATE TABLE user_action_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    purpose VARCHAR(32) NOT NULL,
+    jti VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_user_action_tokens_user ON user_action_tokens(user_id, purpose);
+
+DROP TABLE IF EXISTS user_sessions;
+
+-- 登录会话表，一次登录对应一个刷新令牌家族（id 即 refresh_tokens.family_id）
+CREATE TABLE user_sessions (
+    id CHAR(36) PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    login_method VARCHAR(16) NOT NULL,
+    ip VARCHAR(64),
+    user_agent VARCHAR(512),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_user_sessions_user ON user_sessions(user_id);
+
+DROP TABLE IF EXISTS login_history;
+
+-- 登录记录表，成功与失败的登录都会记录
+CREATE TABLE login_history (
+    id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(id) ON DELETE CASCADE,
+    account VARCHAR(255),
+    login_method VARCHAR(16) NOT NULL,
+    success BOOLEAN NOT NULL,
+    failure_reason VARCHAR(255),
+    session_id CHAR(36),
+    ip VARCHAR(64),
+    user_agent VARCHAR(512),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_login_history_user ON login_history(user_id, created_at DESC);
+
 -- 初始化数据
 INSERT INTO
     roles (code, name, description, is_system)
@@ -119,9 +249,13 @@ VALUES
     ('user:create', '创建用户', '创建新用户', 'user'),
     ('user:update', '更新用户', '更新用户信息', 'user'),
     ('user:delete', '删除用户', '删除用户账户', 'user'),
+    ('user:session:revoke', '强制下线', '作废用户的全部登录会话', 'user'),
+    ('user:token', '个人访问令牌', '创建、查看和撤销自己的个人访问令牌', 'user'),
+    ('user:profile', '个人资料', '查看和管理自己的资料与第三方账号绑定', 'user'),
     -- 系统权限
     ('system:settings', '系统设置', '修改系统设置', 'system'),
-    ('system:backup', '系统备份', '执行系统备份', 'system');
+    ('system:backup', '系统备份', '执行系统备份', 'system'),
+    ('system:rbac', '权限管理', '管理角色、权限及用户授权', 'system');
 
 -- 为角色分配权限
 INSERT INTO
@@ -176,6 +310,32 @@ WHERE
         OR p.code LIKE 'post:read'
     );
 
+-- 编辑与作者可以创建个人访问令牌（用于 CI 发布文章）
+INSERT INTO
+    role_permissions (role_id, permission_id)
+SELECT
+    r.id,
+    p.id
+FROM
+    roles r,
+    permissions p
+WHERE
+    r.code IN ('EDITOR', 'AUTHOR')
+    AND p.code = 'user:token';
+
+-- 所有角色都可以管理自己的资料
+INSERT INTO
+    role_permissions (role_id, permission_id)
+SELECT
+    r.id,
+    p.id
+FROM
+    roles r,
+    permissions p
+WHERE
+    r.code IN ('EDITOR', 'AUTHOR', 'VIEWER')
+    AND p.code = 'user:profile';
+
 INSERT INTO
     role_permissions (role_id, permission_id)
 SELECT
@@ -201,6 +361,22 @@ COMMENT ON TABLE role_permissions IS '角色-权限多对多关联';
 
 COMMENT ON TABLE user_permissions IS '用户特殊权限表（可覆盖角色权限，支持拒绝与过期）';
 
+COMMENT ON TABLE refresh_tokens IS '刷新令牌表（轮换使用，重复使用时整个 family 作废）';
+
+COMMENT ON TABLE api_tokens IS '个人访问令牌表（权限为令牌范围与用户权限的交集）';
+
+COMMENT ON TABLE user_oauth_bindings IS '第三方账号绑定表（provider + subject 唯一）';
+
+COMMENT ON TABLE user_two_factor IS '两步验证表（TOTP）';
+
+COMMENT ON TABLE two_factor_recovery_codes IS '两步验证恢复码表（一次性使用）';
+
+COMMENT ON TABLE user_action_tokens IS '一次性操作令牌表（邮箱验证、密码重置）';
+
+COMMENT ON TABLE user_sessions IS '登录会话表（对应刷新令牌家族）';
+
+COMMENT ON TABLE login_history IS '登录记录表';
+
 -------------------- users 字段注释 --------------------
 COMMENT ON COLUMN users.id IS '主键';
 
@@ -212,11 +388,21 @@ COMMENT ON COLUMN users.pass_word IS '密码哈希（bcrypt/argon2）';
 
 COMMENT ON COLUMN users.email IS '邮箱（可空，唯一）';
 
+COMMENT ON COLUMN users.email_verified IS '邮箱是否已验证';
+
 COMMENT ON COLUMN users.image IS '头像 URL（可空）';
 
 COMMENT ON COLUMN users.phone IS '手机号（可空，唯一）';
 
-COMMENT ON COLUMN users.binding IS '第三方绑定信息（可空，JSON）';
+COMMENT ON COLUMN users.binding IS '已废弃，第三方绑定见 user_oauth_bindings';
+
+COMMENT ON COLUMN users.status IS '账号状态：active 正常 / disabled 已停用 / banned 已封禁 / deleted 已注销';
+
+COMMENT ON COLUMN users.status_reason IS '停用或封禁原因';
+
+COMMENT ON COLUMN users.banned_until IS '封禁截止时间，为空表示永久封禁';
+
+COMMENT ON COLUMN users.deleted_at IS '注销（软删除）时间';
 
 COMMENT ON COLUMN users.created_at IS '记录创建时间（UTC）';
 
@@ -278,3 +464,133 @@ COMMENT ON COLUMN user_permissions.granted IS 'TRUE=授予，FALSE=拒绝（覆
 COMMENT ON COLUMN user_permissions.expires_at IS '权限过期时间（NULL=永不过期）';
 
 COMMENT ON COLUMN user_permissions.created_at IS '记录创建时间（UTC）';
+
+-------------------- refresh_tokens 字段注释 --------------------
+COMMENT ON COLUMN refresh_tokens.id IS '主键';
+
+COMMENT ON COLUMN refresh_tokens.user_id IS '用户外键';
+
+COMMENT ON COLUMN refresh_tokens.token_hash IS '刷新令牌的 SHA-256 十六进制哈希（不保存明文）';
+
+COMMENT ON COLUMN refresh_tokens.family_id IS '令牌家族ID，同一次登录轮换出的令牌共享';
+
+COMMENT ON COLUMN refresh_tokens.expires_at IS '过期时间';
+
+COMMENT ON COLUMN refresh_tokens.revoked_at IS '作废时间（已轮换、登出或被撤销，NULL=有效）';
+
+COMMENT ON COLUMN refresh_tokens.created_at IS '记录创建时间（UTC）';
+
+-------------------- api_tokens 字段注释 --------------------
+COMMENT ON COLUMN api_tokens.id IS '主键';
+
+COMMENT ON COLUMN api_tokens.user_id IS '令牌所属用户外键';
+
+COMMENT ON COLUMN api_tokens.name IS '令牌名称，便于识别用途';
+
+COMMENT ON COLUMN api_tokens.token_prefix IS '令牌明文中的公开前缀，用于查找记录';
+
+COMMENT ON COLUMN api_tokens.token_hash IS '令牌的 argon2 哈希（不保存明文）';
+
+COMMENT ON COLUMN api_tokens.scopes IS '授权范围（权限码，空格分隔）';
+
+COMMENT ON COLUMN api_tokens.expires_at IS '过期时间';
+
+COMMENT ON COLUMN api_tokens.last_used_at IS '最后使用时间';
+
+COMMENT ON COLUMN api_tokens.revoked_at IS '撤销时间（NULL=有效）';
+
+COMMENT ON COLUMN api_tokens.created_at IS '记录创建时间（UTC）';
+
+-------------------- user_oauth_bindings 字段注释 --------------------
+COMMENT ON COLUMN user_oauth_bindings.id IS '主键';
+
+COMMENT ON COLUMN user_oauth_bindings.user_id IS '用户外键';
+
+COMMENT ON COLUMN user_oauth_bindings.provider IS '服务商名称（与 OAUTH_PROVIDERS 配置一致）';
+
+COMMENT ON COLUMN user_oauth_bindings.subject IS '服务商侧的用户唯一标识（OIDC sub / GitHub id）';
+
+COMMENT ON COLUMN user_oauth_bindings.email IS '服务商返回的邮箱（可空）';
+
+COMMENT ON COLUMN user_oauth_bindings.display_name IS '服务商返回的昵称（可空）';
+
+COMMENT ON COLUMN user_oauth_bindings.avatar IS '服务商返回的头像 URL（可空）';
+
+COMMENT ON COLUMN user_oauth_bindings.created_at IS '绑定时间（UTC）';
+
+-------------------- user_two_factor 字段注释 --------------------
+COMMENT ON COLUMN user_two_factor.user_id IS '用户外键（主键）';
+
+COMMENT ON COLUMN user_two_factor.secret IS 'TOTP 密钥（base32）';
+
+COMMENT ON COLUMN user_two_factor.enabled_at IS '启用时间（NULL=已生成密钥但未确认）';
+
+COMMENT ON COLUMN user_two_factor.last_used_step IS '最近一次通过校验的时间步，防止验证码重放';
+
+COMMENT ON COLUMN user_two_factor.created_at IS '记录创建时间（UTC）';
+
+-------------------- two_factor_recovery_codes 字段注释 --------------------
+COMMENT ON COLUMN two_factor_recovery_codes.id IS '主键';
+
+COMMENT ON COLUMN two_factor_recovery_codes.user_id IS '用户外键';
+
+COMMENT ON COLUMN two_factor_recovery_codes.code_hash IS '恢复码的 SHA-256 哈希';
+
+COMMENT ON COLUMN two_factor_recovery_codes.used_at IS '使用时间（NULL=未使用）';
+
+COMMENT ON COLUMN two_factor_recovery_codes.created_at IS '记录创建时间（UTC）';
+
+-------------------- user_action_tokens 字段注释 --------------------
+COMMENT ON COLUMN user_action_tokens.id IS '主键';
+
+COMMENT ON COLUMN user_action_tokens.user_id IS '用户外键';
+
+COMMENT ON COLUMN user_action_tokens.purpose IS '用途：verify_email / reset_password';
+
+COMMENT ON COLUMN user_action_tokens.jti IS '令牌唯一ID';
+
+COMMENT ON COLUMN user_action_tokens.expires_at IS '过期时间';
+
+COMMENT ON COLUMN user_action_tokens.used_at IS '使用时间（NULL=未使用）';
+
+COMMENT ON COLUMN user_action_tokens.created_at IS '记录创建时间（UTC）';
+
+-------------------- user_sessions 字段注释 --------------------
+COMMENT ON COLUMN user_sessions.id IS '会话ID，同 refresh_tokens.family_id';
+
+COMMENT ON COLUMN user_sessions.user_id IS '用户外键';
+
+COMMENT ON COLUMN user_sessions.login_method IS '登录方式：password / email / phone / oauth / two_factor';
+
+COMMENT ON COLUMN user_sessions.ip IS '登录时的客户端 IP';
+
+COMMENT ON COLUMN user_sessions.user_agent IS '登录时的 User-Agent';
+
+COMMENT ON COLUMN user_sessions.expires_at IS '过期时间，刷新令牌时顺延';
+
+COMMENT ON COLUMN user_sessions.last_used_at IS '最后一次刷新令牌的时间';
+
+COMMENT ON COLUMN user_sessions.revoked_at IS '退出或被踢下线的时间（NULL=有效）';
+
+COMMENT ON COLUMN user_sessions.created_at IS '登录时间（UTC）';
+
+-------------------- login_history 字段注释 --------------------
+COMMENT ON COLUMN login_history.id IS '主键';
+
+COMMENT ON COLUMN login_history.user_id IS '用户外键，账号不存在时为空';
+
+COMMENT ON COLUMN login_history.account IS '提交的账号：用户名 / 邮箱 / 手机号 / 第三方服务商';
+
+COMMENT ON COLUMN login_history.login_method IS '登录方式：password / email / phone / oauth / two_factor';
+
+COMMENT ON COLUMN login_history.success IS '是否登录成功';
+
+COMMENT ON COLUMN login_history.failure_reason IS '失败原因';
+
+COMMENT ON COLUMN login_history.session_id IS '成功登录时创建的会话ID';
+
+COMMENT ON COLUMN login_history.ip IS '客户端 IP';
+
+COMMENT ON COLUMN login_history.user_agent IS 'User-Agent';
+
+COMMENT ON COLUMN login_history.created_at IS '登录时间（UTC）';
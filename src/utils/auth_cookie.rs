@@ -0,0 +1,135 @@
+use crate::config::manager::{CONFIG, TokenSource};
+use actix_web::{
+    HttpRequest,
+    cookie::{Cookie, SameSite, time::Duration as ActixDuration},
+    http::header,
+};
+
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+pub const OAUTH_STATE_COOKIE: &str = "oauth_state";
+/// 刷新令牌只在认证接口下发送
+const REFRESH_TOKEN_PATH: &str = "/api/v1/auth";
+/// 第三方授权的 state 只在授权接口下发送
+const OAUTH_STATE_PATH: &str = "/api/v1/auth/oauth";
+
+/// 构造访问令牌 Cookie，有效期与 `CONFIG.jwt.expiry` 一致
+pub fn access_token_cookie(token: String) -> Cookie<'static> {
+    build(ACCESS_TOKEN_COOKIE, token, "/", CONFIG.jwt.expiry)
+}
+
+/// 构造刷新令牌 Cookie，有效期与 `CONFIG.jwt.refresh_expiry` 一致
+pub fn refresh_token_cookie(token: String) -> Cookie<'static> {
+    build(
+        REFRESH_TOKEN_COOKIE,
+        token,
+        REFRESH_TOKEN_PATH,
+        CONFIG.jwt.refresh_expiry,
+    )
+}
+
+/// 登出时用于清除两个令牌的 Cookie
+pub fn clear_auth_cookies() -> [Cookie<'static>; 2] {
+    [
+        build(ACCESS_TOKEN_COOKIE, String::new(), "/", 0),
+        build(REFRESH_TOKEN_COOKIE, String::new(), REFRESH_TOKEN_PATH, 0),
+    ]
+}
+
+/// 构造保存第三方授权 state 的 Cookie，把授权请求绑定到发起它的浏览器。
+/// 回调是从服务商页面跳转回来的跨站导航，需使用 `SameSite=Lax` 才会携带
+pub fn oauth_state_cookie(state: String, max_age: i64) -> Cookie<'static> {
+    let mut cookie = build(OAUTH_STATE_COOKIE, state, OAUTH_STATE_PATH, max_age);
+    cookie.set_same_site(SameSite::Lax);
+    cookie
+}
+
+/// 授权回调完成后清除 state Cookie
+pub fn clear_oauth_state_cookie() -> Cookie<'static> {
+    oauth_state_cookie(String::new(), 0)
+}
+
+/// 请求携带的 state Cookie 是否与回调参数中的 state 一致
+pub fn oauth_state_matches(req: &HttpRequest, state: &str) -> bool {
+    req.cookie(OAUTH_STATE_COOKIE)
+        .is_some_and(|cookie| !state.is_empty() && cookie.value() == state)
+}
+
+/// 按 `CONFIG.jwt.token_sources` 的顺序从请求中读取访问令牌
+pub fn access_token(req: &HttpRequest) -> Option<String> {
+    CONFIG
+        .jwt
+        .token_sources
+        .iter()
+        .find_map(|source| match source {
+            TokenSource::Bearer => bearer_token(req).map(String::from),
+            TokenSource::Cookie => req
+                .cookie(ACCESS_TOKEN_COOKIE)
+                .map(|cookie| cookie.value().to_string()),
+        })
+}
+
+/// 解析 `Authorization: Bearer <jwt>`，scheme 不区分大小写
+fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    let value = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+fn build(name: &'static str, value: String, path: &'static str, max_age: i64) -> Cookie<'static> {
+    Cookie::build(name, value)
+        .http_only(true) // 防 XSS
+        .same_site(SameSite::Strict) // 防 CSRF
+        .secure(true) // 生产必须 true，本地可 false
+        .max_age(ActixDuration::seconds(max_age))
+        .path(path)
+        .finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn parse_bearer_token() {
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer abc.def.ghi"))
+            .to_http_request();
+        assert_eq!(bearer_token(&req), Some("abc.def.ghi"));
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "bearer  abc"))
+            .to_http_request();
+        assert_eq!(bearer_token(&req), Some("abc"));
+
+        for value in ["Basic dXNlcjpwYXNz", "Bearer ", "Bearer"] {
+            let req = TestRequest::default()
+                .insert_header((header::AUTHORIZATION, value))
+                .to_http_request();
+            assert_eq!(bearer_token(&req), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn oauth_state_must_match_cookie() {
+        let req = TestRequest::default()
+            .cookie(oauth_state_cookie("abc".to_string(), 600))
+            .to_http_request();
+        assert!(oauth_state_matches(&req, "abc"));
+        assert!(!oauth_state_matches(&req, "xyz"));
+
+        let req = TestRequest::default().to_http_request();
+        assert!(!oauth_state_matches(&req, "abc"));
+
+        let req = TestRequest::default()
+            .cookie(clear_oauth_state_cookie())
+            .to_http_request();
+        assert!(!oauth_state_matches(&req, ""));
+
+        let cookie = oauth_state_cookie("abc".to_string(), 600);
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+        assert_eq!(cookie.http_only(), Some(true));
+    }
+}
@@ -1,20 +1,184 @@
 //! 密码哈希/校验工具，统一使用 argon2id
+//!
+//! - 哈希参数（内存、迭代次数、并行度）来自配置，登录成功时若库中哈希的参数弱于当前配置则透明地重新哈希；
+//! - 注册、修改密码、重置密码时按配置的密码策略校验新密码：长度、字符种类、常见弱密码、不得包含用户名。
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
 use argon2::{
-    Argon2,
+    Algorithm, Argon2, Params, Version,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::LazyLock;
 
 type Result<T> = std::result::Result<T, argon2::password_hash::Error>;
 
+/// 内置的常见弱密码，可通过 `PASSWORD_BLOCKLIST_FILE` 追加
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "1234567",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "111111",
+    "000000",
+    "666666",
+    "888888",
+    "88888888",
+    "123123",
+    "654321",
+    "a123456",
+    "abc123",
+    "abc123456",
+    "qwerty",
+    "qwerty123",
+    "1q2w3e4r",
+    "1qaz2wsx",
+    "password",
+    "passw0rd",
+    "iloveyou",
+    "admin",
+    "admin123",
+    "welcome",
+    "letmein",
+    "woaini1314",
+];
+
+/// 从文件加载的弱密码，每行一个，忽略空行与 `#` 开头的注释
+static BLOCKLIST_FILE: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    let Some(path) = CONFIG.password.blocklist_file.as_deref() else {
+        return HashSet::new();
+    };
+    match std::fs::read_to_string(path) {
+        Ok(content) => content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_lowercase)
+            .collect(),
+        Err(e) => {
+            log::error!("读取弱密码列表 {path} 失败: {e}");
+            HashSet::new()
+        }
+    }
+});
+
+/// 密码策略与 argon2 参数配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordSettings {
+    /// 最短长度（字符数）
+    pub min_length: usize,
+    /// 最长长度（字符数）
+    pub max_length: usize,
+    /// 至少包含几类字符（小写字母、大写字母、数字、其他符号）
+    pub min_char_classes: usize,
+    /// 是否禁止密码包含用户名
+    pub reject_username: bool,
+    /// 额外的弱密码列表文件
+    pub blocklist_file: Option<String>,
+    /// argon2 内存开销（KiB）
+    pub argon2_memory_kib: u32,
+    /// argon2 迭代次数
+    pub argon2_iterations: u32,
+    /// argon2 并行度
+    pub argon2_parallelism: u32,
+}
+
+impl Default for PasswordSettings {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 100,
+            min_char_classes: 2,
+            reject_username: true,
+            blocklist_file: None,
+            argon2_memory_kib: Params::DEFAULT_M_COST,
+            argon2_iterations: Params::DEFAULT_T_COST,
+            argon2_parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl PasswordSettings {
+    /// 按当前配置构造 argon2id 实例
+    fn argon2(&self) -> Result<Argon2<'static>> {
+        let params = Params::new(
+            self.argon2_memory_kib,
+            self.argon2_iterations,
+            self.argon2_parallelism,
+            None,
+        )?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    /// 校验新密码是否符合策略，不符合时返回原因
+    pub fn check(&self, password: &str, user_name: &str) -> std::result::Result<(), String> {
+        let length = password.chars().count();
+        if length < self.min_length || length > self.max_length {
+            return Err(format!(
+                "密码长度必须在{}到{}之间",
+                self.min_length, self.max_length
+            ));
+        }
+        let classes = [
+            password.chars().any(|c| c.is_ascii_lowercase()),
+            password.chars().any(|c| c.is_ascii_uppercase()),
+            password.chars().any(|c| c.is_ascii_digit()),
+            password.chars().any(|c| !c.is_ascii_alphanumeric()),
+        ]
+        .into_iter()
+        .filter(|&present| present)
+        .count();
+        if classes < self.min_char_classes {
+            return Err(format!(
+                "密码至少需要包含小写字母、大写字母、数字、符号中的{}类",
+                self.min_char_classes
+            ));
+        }
+        let lowered = password.to_lowercase();
+        if self.reject_username
+            && !user_name.is_empty()
+            && lowered.contains(&user_name.to_lowercase())
+        {
+            return Err("密码不能包含用户名".to_string());
+        }
+        if COMMON_PASSWORDS.contains(&lowered.as_str()) || BLOCKLIST_FILE.contains(&lowered) {
+            return Err("密码过于常见，请换一个".to_string());
+        }
+        Ok(())
+    }
+
+    /// 库中哈希的参数弱于当前配置（或不是 argon2id）时需要重新哈希
+    fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return false;
+        };
+        if parsed.algorithm != Algorithm::Argon2id.ident() {
+            return true;
+        }
+        match Params::try_from(&parsed) {
+            Ok(params) => {
+                params.m_cost() < self.argon2_memory_kib
+                    || params.t_cost() < self.argon2_iterations
+                    || params.p_cost() < self.argon2_parallelism
+            }
+            Err(_) => false,
+        }
+    }
+}
+
 /// 明文 → 哈希字符串（存入数据库）
 pub fn hash(password: &str) -> Result<String> {
     let salt = SaltString::generate(&mut OsRng);
-    Ok(Argon2::default()
+    Ok(CONFIG
+        .password
+        .argon2()?
         .hash_password(password.as_bytes(), &salt)?
         .to_string())
 }
 
-/// 校验用户输入
+/// 校验用户输入，使用哈希字符串中记录的参数
 pub fn verify(password: &str, hash: &str) -> Result<bool> {
     let parsed = PasswordHash::new(hash)?;
     Ok(Argon2::default()
@@ -22,6 +186,19 @@ pub fn verify(password: &str, hash: &str) -> Result<bool> {
         .is_ok())
 }
 
+/// 是否需要按当前配置的参数重新哈希
+pub fn needs_rehash(hash: &str) -> bool {
+    CONFIG.password.needs_rehash(hash)
+}
+
+/// 按配置的密码策略校验新密码
+pub fn validate_password(password: &str, user_name: &str) -> std::result::Result<(), AppError> {
+    CONFIG
+        .password
+        .check(password, user_name)
+        .map_err(AppError::BadRequest)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,4 +211,36 @@ mod tests {
         assert!(verify(pwd, &hash).unwrap());
         assert!(!verify("wrong", &hash).unwrap());
     }
+
+    #[test]
+    fn policy_rules() {
+        let settings = PasswordSettings {
+            min_char_classes: 3,
+            ..Default::default()
+        };
+        assert!(settings.check("Ab1!", "alice").is_err());
+        assert!(settings.check("abcdefgh1", "alice").is_err());
+        assert!(settings.check("Alice-2024", "alice").is_err());
+        assert!(settings.check("Sunrise-42", "alice").is_ok());
+        assert!(settings.check("Passw0rd", "alice").is_err());
+    }
+
+    #[test]
+    fn weaker_params_need_rehash() {
+        let weak = PasswordSettings {
+            argon2_memory_kib: 1024,
+            argon2_iterations: 1,
+            ..Default::default()
+        };
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = weak
+            .argon2()
+            .unwrap()
+            .hash_password(b"secret", &salt)
+            .unwrap()
+            .to_string();
+        assert!(verify("secret", &hash).unwrap());
+        assert!(PasswordSettings::default().needs_rehash(&hash));
+        assert!(!weak.needs_rehash(&hash));
+    }
 }
@@ -1,5 +1,5 @@
 use actix_web::error::JsonPayloadError::{self};
-use sea_orm::DbErr;
+use sea_orm::{DbErr, SqlErr};
 use validator::ValidationErrors;
 
 use crate::{ApiResponse, config::AppError, dto::user::ValidationErrorJson};
@@ -8,7 +8,7 @@ pub fn db_err_map(e: DbErr) -> &'static str {
     // Sea-ORM 的 DbErr::Query 里会带数据库原始错误信息
     let detail = e.to_string();
 
-    if detail.contains("duplicate key value violates unique constraint") {
+    if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) {
         // 可以细化到字段
         if detail.contains("categories_slug_key") {
             "英文名（slug）已存在，请更换"
@@ -20,10 +20,14 @@ pub fn db_err_map(e: DbErr) -> &'static str {
             "用户名已被使用"
         } else if detail.contains("posts_slug_key") {
             "文章英文名（slug）已存在"
+        } else if detail.contains("roles_code_key") {
+            "角色代码已存在"
+        } else if detail.contains("permissions_code_key") {
+            "权限代码已存在"
         } else {
             "数据重复，请检查唯一字段"
         }
-    } else if detail.contains("foreign key constraint") {
+    } else if matches!(e.sql_err(), Some(SqlErr::ForeignKeyConstraintViolation(_))) {
         "关联数据不存在，无法操作"
     } else if detail.contains("violates not-null constraint") {
         "必填字段不能为空"
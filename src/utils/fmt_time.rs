@@ -6,11 +6,26 @@ fn to_beijing(dt: DateTime<Utc>) -> DateTime<FixedOffset> {
     dt.with_timezone(&beijing)
 }
 
+/// 格式化为北京时间：2025-09-24 13:44:29
+pub fn format_beijing(dt: DateTime<Utc>) -> String {
+    to_beijing(dt).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
 /// 序列化函数：2025-09-24 13:44:29
 pub fn fmt_beijing<S>(dt: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
 {
-    let bj = to_beijing(*dt);
-    s.serialize_str(&bj.format("%Y-%m-%d %H:%M:%S").to_string())
+    s.serialize_str(&format_beijing(*dt))
+}
+
+/// 可选时间的序列化函数，为空时输出 null
+pub fn fmt_beijing_opt<S>(dt: &Option<DateTime<Utc>>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match dt {
+        Some(dt) => s.serialize_str(&format_beijing(*dt)),
+        None => s.serialize_none(),
+    }
 }
@@ -1,74 +1,154 @@
 use crate::config::manager::CONFIG;
 use crate::models::roles::{self};
 use crate::models::user_roles;
+use crate::utils::jwt_keys::jwt_keys;
 use crate::{config::AppError, models::users::Model};
-use base64::engine::{Engine as _, general_purpose};
 use chrono::{Duration as ChronoDuration, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode};
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 访问令牌载荷，认证中间件校验通过后会放入请求扩展（`req.extensions()`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenClaims {
     pub user_uuid: String,
     pub user_name: String,
-    pub exp: i64,     // u64 更安全
-    pub role_id: i32, //
+    pub user_id: i32,
+    /// 令牌唯一ID，用于登出 / 撤销
+    pub jti: String,
+    /// 签发时间
+    pub iat: i64,
+    /// 签发时间（毫秒），与撤销时间比较，区分同一秒内先后发生的签发与撤销；
+    /// 旧令牌没有该字段，按 `iat` 换算
+    #[serde(default)]
+    pub iat_ms: i64,
+    pub exp: i64, // u64 更安全
+    /// 用户的全部角色ID，主要角色在前
+    pub role_ids: Vec<i32>,
+    /// 个人访问令牌的授权范围；JWT 登录会话为空，表示不额外限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    /// 登录会话ID（`user_sessions.id`）；个人访问令牌为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
 impl TokenClaims {
     async fn from_user(
         db_pool: &sea_orm::DatabaseConnection,
         user: &Model,
+        session_id: &str,
     ) -> Result<Self, AppError> {
-        let exp = (Utc::now() + ChronoDuration::seconds(CONFIG.jwt.expiry)).timestamp();
-        let role_id = Self::get_permission_codes(db_pool, user).await?;
+        let now = Utc::now();
+        let exp = (now + ChronoDuration::seconds(CONFIG.jwt.expiry)).timestamp();
+        let role_ids = Self::get_role_ids(db_pool, user).await?;
 
         Ok(TokenClaims {
             user_uuid: user.uuid.clone(),
             user_name: user.user_name.clone(),
+            user_id: user.id,
+            jti: Uuid::new_v4().to_string(),
+            iat: now.timestamp(),
+            iat_ms: now.timestamp_millis(),
             exp,
-            role_id,
+            role_ids,
+            scopes: None,
+            sid: Some(session_id.to_string()),
         })
     }
-    /// 获取权限码集合
-    async fn get_permission_codes(
+
+    /// 签发时间（毫秒）
+    pub fn issued_at_ms(&self) -> i64 {
+        if self.iat_ms > 0 {
+            self.iat_ms
+        } else {
+            self.iat * 1000
+        }
+    }
+
+    /// 令牌范围是否允许访问该权限码，最终权限还需与用户自身权限取交集
+    pub fn scope_allows(&self, code: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.iter().any(|scope| scope == code))
+    }
+
+    /// 获取用户的全部角色ID（主要角色在前），没有角色时返回空列表
+    pub(crate) async fn get_role_ids(
         db_pool: &sea_orm::DatabaseConnection,
         user: &Model,
-    ) -> Result<i32, AppError> {
-        let roles = user_roles::Entity::find()
+    ) -> Result<Vec<i32>, AppError> {
+        let mut roles = user_roles::Entity::find()
             .filter(user_roles::Column::UserId.eq(user.id))
             .find_with_related(roles::Entity)
             .all(db_pool)
             .await
             .map_err(|e| AppError::DatabaseError(format!("Failed to get user roles: {}", e)))?;
 
-        // 生成角色哈希值
+        roles.sort_by_key(|(user_role, _)| !user_role.is_primary.unwrap_or(false));
         let role_ids: Vec<i32> = roles
             .iter()
             .flat_map(|(_, related_roles)| related_roles.iter().map(|role| role.id))
             .collect();
-        Ok(role_ids[0])
+        Ok(role_ids)
     }
 }
 
+/// 一次性操作令牌（邮箱验证、密码重置）载荷。
+///
+/// 与访问令牌共用签名密钥，但字段不同，两者无法互相冒用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionClaims {
+    /// 用户ID
+    pub sub: i32,
+    /// 用途，如 `verify_email`、`reset_password`
+    pub purpose: String,
+    /// 签发时的邮箱，邮箱变更后旧令牌随之失效
+    pub email: String,
+    pub jti: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// 签发一次性操作令牌
+pub fn encode_action_token(claims: &ActionClaims) -> Result<String, AppError> {
+    let keys = jwt_keys()?;
+    let mut header = Header::new(keys.algorithm);
+    header.kid = keys.kid.clone();
+    encode(&header, claims, &keys.encoding).map_err(|e| {
+        log::error!("操作令牌生成失败: {}", e);
+        AppError::InternalServerError("令牌生成失败".into())
+    })
+}
+
+/// 校验一次性操作令牌的签名、有效期与用途
+pub fn decode_action_token(token: &str, purpose: &str) -> Result<ActionClaims, AppError> {
+    let invalid = || AppError::BadRequest("链接无效或已过期".into());
+    let keys = jwt_keys()?;
+    let header = decode_header(token).map_err(|_| invalid())?;
+    let key = keys
+        .decoding_key(header.kid.as_deref())
+        .ok_or_else(invalid)?;
+    decode::<ActionClaims>(token, key, &Validation::new(keys.algorithm))
+        .map(|data| data.claims)
+        .ok()
+        .filter(|claims| claims.purpose == purpose)
+        .ok_or_else(invalid)
+}
+
 /// 生成 JWT
 pub async fn generate_jwt(
     db_pool: &sea_orm::DatabaseConnection,
     user: &Model,
+    session_id: &str,
 ) -> Result<String, AppError> {
-    let claims = TokenClaims::from_user(db_pool, user).await?;
-    log::info!("claims: {:?},{}", claims, &CONFIG.jwt.secret);
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(
-            &general_purpose::STANDARD
-                .decode(&CONFIG.jwt.secret)
-                .map_err(|_| AppError::InternalServerError("JWT 密钥格式错误".into()))?,
-        ),
-    )
-    .map_err(|e| {
+    let claims = TokenClaims::from_user(db_pool, user, session_id).await?;
+    log::debug!("claims: {:?}", claims);
+    let keys = jwt_keys()?;
+    let mut header = Header::new(keys.algorithm);
+    header.kid = keys.kid.clone();
+    encode(&header, &claims, &keys.encoding).map_err(|e| {
         log::error!("JWT 生成失败: {}", e);
         AppError::InternalServerError("登录服务暂时不可用".into())
     })
@@ -76,27 +156,23 @@ pub async fn generate_jwt(
 
 /// 解析 JWT
 pub fn decode_jwt(token: &str) -> Result<TokenClaims, AppError> {
-    let validation = Validation::new(Algorithm::HS256);
-    decode::<TokenClaims>(
-        token,
-        &DecodingKey::from_secret(
-            &general_purpose::STANDARD
-                .decode(&CONFIG.jwt.secret)
-                .map_err(|_| AppError::InternalServerError("JWT 密钥格式错误".into()))?,
-        ),
-        &validation,
-    )
-    .map_err(|e| match e.kind() {
-        jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
-            AppError::Unauthorized("令牌已过期，请重新登录".into())
-        }
-        jsonwebtoken::errors::ErrorKind::InvalidSignature => {
-            AppError::Unauthorized("令牌签名无效".into())
-        }
-        _ => {
-            log::error!("JWT 解析失败: {}", e);
-            AppError::Unauthorized("令牌无效".into())
-        }
-    })
-    .map(|data| data.claims)
+    let keys = jwt_keys()?;
+    let header = decode_header(token).map_err(|_| AppError::Unauthorized("令牌无效".into()))?;
+    let key = keys
+        .decoding_key(header.kid.as_deref())
+        .ok_or_else(|| AppError::Unauthorized("令牌签名密钥未知".into()))?;
+    decode::<TokenClaims>(token, key, &Validation::new(keys.algorithm))
+        .map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
+                AppError::Unauthorized("令牌已过期，请重新登录".into())
+            }
+            jsonwebtoken::errors::ErrorKind::InvalidSignature => {
+                AppError::Unauthorized("令牌签名无效".into())
+            }
+            _ => {
+                log::error!("JWT 解析失败: {}", e);
+                AppError::Unauthorized("令牌无效".into())
+            }
+        })
+        .map(|data| data.claims)
 }
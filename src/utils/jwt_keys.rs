@@ -0,0 +1,266 @@
+//! JWT 签名 / 验签密钥。
+//!
+//! - `HS256`：沿用 `JWT_SECRET`（base64），不对外公开任何密钥；
+//! - `EdDSA` / `RS256`：用 `JWT_PRIVATE_KEY_PATH` 指向的私钥签名，令牌头带上 `JWT_KEY_ID`；
+//!   `JWT_PUBLIC_KEYS`（`kid=公钥PEM路径`，逗号分隔）列出所有仍可用于验签的公钥，
+//!   并通过 `/.well-known/jwks.json` 公开，供其他服务验签。
+//!
+//! 轮换密钥时生成新密钥对并切换私钥与 `JWT_KEY_ID`，把旧公钥保留在 `JWT_PUBLIC_KEYS` 中，
+//! 直到旧访问令牌全部过期即可，已登录用户不受影响。
+
+use crate::config::AppError;
+use crate::config::manager::{CONFIG, JwtSettings};
+use base64::engine::{Engine as _, general_purpose};
+use ed25519_dalek::VerifyingKey;
+use ed25519_dalek::pkcs8::DecodePublicKey as _;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use rsa::RsaPublicKey;
+use rsa::traits::PublicKeyParts;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+static JWT_KEYS: LazyLock<Result<JwtKeys, String>> = LazyLock::new(|| {
+    // 单元测试不依赖 JWT_SECRET 等环境配置
+    if cfg!(test) {
+        return Ok(JwtKeys::hmac(b"unit-test-secret"));
+    }
+    JwtKeys::from_settings(&CONFIG.jwt)
+});
+
+/// 获取全局密钥。启动时调用一次，配置错误可以尽早暴露。
+///
+/// # Errors
+///
+/// 密钥配置或 PEM 文件无效时返回 `AppError::ConfigurationError`。
+pub fn jwt_keys() -> Result<&'static JwtKeys, AppError> {
+    JWT_KEYS
+        .as_ref()
+        .map_err(|e| AppError::ConfigurationError(e.clone()))
+}
+
+pub struct JwtKeys {
+    pub algorithm: Algorithm,
+    /// 当前签名密钥的 kid，HS256 时为空
+    pub kid: Option<String>,
+    pub encoding: EncodingKey,
+    /// kid → 验签密钥；HS256 时只有一个空 kid
+    decoding: HashMap<String, DecodingKey>,
+    jwks: JwkSet,
+}
+
+impl JwtKeys {
+    fn from_settings(settings: &JwtSettings) -> Result<Self, String> {
+        let algorithm: Algorithm = settings
+            .algorithm
+            .parse()
+            .map_err(|_| format!("不支持的 JWT 算法: {}", settings.algorithm))?;
+        if algorithm == Algorithm::HS256 {
+            let secret = general_purpose::STANDARD
+                .decode(&settings.secret)
+                .map_err(|_| "JWT 密钥格式错误".to_string())?;
+            return Ok(Self::hmac(&secret));
+        }
+
+        let read = |path: &str| {
+            std::fs::read_to_string(path).map_err(|e| format!("读取密钥文件 {path} 失败: {e}"))
+        };
+        let private_key_path = settings
+            .private_key_path
+            .as_deref()
+            .ok_or("未配置 JWT_PRIVATE_KEY_PATH")?;
+        let kid = settings.key_id.clone().ok_or("未配置 JWT_KEY_ID")?;
+        let mut public_keys = Vec::new();
+        for entry in &settings.public_keys {
+            let (kid, path) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("JWT_PUBLIC_KEYS 格式应为 kid=路径: {entry}"))?;
+            public_keys.push((kid.trim().to_string(), read(path.trim())?));
+        }
+        Self::asymmetric(algorithm, kid, &read(private_key_path)?, &public_keys)
+    }
+
+    fn hmac(secret: &[u8]) -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            kid: None,
+            encoding: EncodingKey::from_secret(secret),
+            decoding: HashMap::from([(String::new(), DecodingKey::from_secret(secret))]),
+            jwks: JwkSet { keys: Vec::new() },
+        }
+    }
+
+    /// 由私钥与公钥 PEM 构造，`public_keys` 必须包含当前签名密钥 `kid` 对应的公钥，
+    /// 且该公钥能验证私钥的签名
+    fn asymmetric(
+        algorithm: Algorithm,
+        kid: String,
+        private_pem: &str,
+        public_keys: &[(String, String)],
+    ) -> Result<Self, String> {
+        let encoding = match algorithm {
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(private_pem.as_bytes()),
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(private_pem.as_bytes()),
+            other => return Err(format!("不支持的 JWT 算法: {other:?}")),
+        }
+        .map_err(|e| format!("JWT 私钥无效: {e}"))?;
+
+        let mut decoding = HashMap::new();
+        let mut keys = Vec::new();
+        for (key_id, pem) in public_keys {
+            let jwk = public_jwk(algorithm, key_id, pem)?;
+            let key =
+                DecodingKey::from_jwk(&jwk).map_err(|e| format!("JWT 公钥 {key_id} 无效: {e}"))?;
+            decoding.insert(key_id.clone(), key);
+            keys.push(jwk);
+        }
+        let Some(current) = decoding.get(&kid) else {
+            return Err(format!("JWT_PUBLIC_KEYS 中缺少当前签名密钥 {kid} 的公钥"));
+        };
+        // 私钥与公钥不配对时签出的令牌处处验签失败，启动时用探针令牌检查
+        let mut validation = Validation::new(algorithm);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        let probe = encode(&Header::new(algorithm), &serde_json::json!({}), &encoding)
+            .map_err(|e| format!("JWT 私钥无法签名: {e}"))?;
+        decode::<serde_json::Value>(&probe, current, &validation)
+            .map_err(|_| format!("JWT 私钥与 JWT_PUBLIC_KEYS 中 {kid} 的公钥不匹配"))?;
+
+        Ok(Self {
+            algorithm,
+            kid: Some(kid),
+            encoding,
+            decoding,
+            jwks: JwkSet { keys },
+        })
+    }
+
+    /// 按令牌头中的 kid 选择验签密钥
+    pub fn decoding_key(&self, kid: Option<&str>) -> Option<&DecodingKey> {
+        self.decoding.get(kid.unwrap_or_default())
+    }
+
+    /// 对外公开的公钥集合
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+}
+
+/// 把公钥 PEM 转换为 JWK
+fn public_jwk(algorithm: Algorithm, kid: &str, pem: &str) -> Result<Jwk, String> {
+    let invalid = |e: &dyn std::fmt::Display| format!("JWT 公钥 {kid} 无效: {e}");
+    let (key_algorithm, parameters) = match algorithm {
+        Algorithm::EdDSA => {
+            let key = VerifyingKey::from_public_key_pem(pem).map_err(|e| invalid(&e))?;
+            (
+                KeyAlgorithm::EdDSA,
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: general_purpose::URL_SAFE_NO_PAD.encode(key.to_bytes()),
+                }),
+            )
+        }
+        Algorithm::RS256 => {
+            let key = RsaPublicKey::from_public_key_pem(pem).map_err(|e| invalid(&e))?;
+            (
+                KeyAlgorithm::RS256,
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: general_purpose::URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                    e: general_purpose::URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                }),
+            )
+        }
+        other => return Err(format!("不支持的 JWT 算法: {other:?}")),
+    };
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey, spki::der::pem::LineEnding};
+    use jsonwebtoken::decode_header;
+    use serde_json::json;
+
+    fn ed25519_pems() -> (String, String) {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let private = key.to_pkcs8_pem(LineEnding::LF).unwrap().to_string();
+        let public = key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        (private, public)
+    }
+
+    #[test]
+    fn eddsa_tokens_verify_with_rotated_keys() {
+        let (old_private, old_public) = ed25519_pems();
+        let (new_private, new_public) = ed25519_pems();
+        let public_keys = vec![
+            ("2024".to_string(), old_public),
+            ("2025".to_string(), new_public),
+        ];
+        let old = JwtKeys::asymmetric(Algorithm::EdDSA, "2024".into(), &old_private, &public_keys)
+            .unwrap();
+        let new = JwtKeys::asymmetric(Algorithm::EdDSA, "2025".into(), &new_private, &public_keys)
+            .unwrap();
+
+        let claims = json!({ "sub": "1", "exp": 4_102_444_800_i64 });
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = old.kid.clone();
+        let token = encode(&header, &claims, &old.encoding).unwrap();
+
+        // 轮换后仍能用旧 kid 的公钥验签
+        let kid = decode_header(&token).unwrap().kid;
+        let key = new.decoding_key(kid.as_deref()).unwrap();
+        assert!(
+            decode::<serde_json::Value>(&token, key, &Validation::new(Algorithm::EdDSA)).is_ok()
+        );
+        assert!(new.decoding_key(Some("unknown")).is_none());
+
+        let jwks = serde_json::to_value(new.jwks()).unwrap();
+        assert_eq!(jwks["keys"][1]["kid"], "2025");
+        assert_eq!(jwks["keys"][1]["kty"], "OKP");
+        assert_eq!(jwks["keys"][1]["crv"], "Ed25519");
+    }
+
+    #[test]
+    fn signing_kid_must_have_public_key() {
+        let (private, public) = ed25519_pems();
+        let result = JwtKeys::asymmetric(
+            Algorithm::EdDSA,
+            "missing".into(),
+            &private,
+            &[("other".to_string(), public)],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn signing_key_must_match_published_public_key() {
+        let (private, _) = ed25519_pems();
+        let (_, other_public) = ed25519_pems();
+        let result = JwtKeys::asymmetric(
+            Algorithm::EdDSA,
+            "2025".into(),
+            &private,
+            &[("2025".to_string(), other_public)],
+        );
+        let err = result.err().unwrap();
+        assert!(err.contains("不匹配"), "{err}");
+    }
+}
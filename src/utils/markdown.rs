@@ -0,0 +1,329 @@
+//! Markdown 渲染与 HTML 清洗。
+//!
+//! 文章的 HTML 一律由服务端根据 Markdown 渲染（CommonMark + GFM 表格、任务列表、删除线、脚注），
+//! 再经 ammonia 按白名单清洗后入库，客户端提交的 HTML 不再被信任。
+//! 代码块保留 `language-xxx` 类名，供前端语法高亮使用；
+//! 页面内锚点的 `id` 统一加上 `user-content-` 前缀，避免覆盖页面上的其他元素。
+//!
+//! 渲染时同时统计字数与阅读时长，并根据标题生成目录，标题锚点由标题文字生成，内容不变时保持不变。
+
+use ammonia::Builder;
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, TagEnd, html};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+use utoipa::ToSchema;
+
+/// 页面内锚点 `id` 的前缀
+pub const ID_PREFIX: &str = "user-content-";
+
+/// 每分钟阅读的中日韩字符数
+const CJK_CHARS_PER_MINUTE: usize = 300;
+
+/// 每分钟阅读的其他语言单词数
+const WORDS_PER_MINUTE: usize = 200;
+
+/// 目录项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TocEntry {
+    /// 标题级别 1-6
+    pub level: u8,
+    pub title: String,
+    /// 页面中标题元素的 `id`（已带前缀），可直接用作 `#锚点`
+    pub anchor: String,
+}
+
+/// 渲染结果
+#[derive(Debug, Clone)]
+pub struct RenderedPost {
+    /// 清洗后的 HTML
+    pub html: String,
+    /// 字数：中日韩字符每字计 1，其他语言每个单词计 1
+    pub word_count: usize,
+    /// 预计阅读分钟数，有内容时至少 1 分钟
+    pub reading_minutes: usize,
+    pub toc: Vec<TocEntry>,
+}
+
+/// 允许保留的 `class`（代码块的 `language-xxx` 另行校验）
+const ALLOWED_CLASSES: &[&str] = &[
+    "footnote-reference",
+    "footnote-definition",
+    "footnote-definition-label",
+];
+
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::default();
+    builder
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("sup", &["class"])
+        .add_tag_attributes("div", &["class", "id"])
+        .add_tag_attributes("th", &["style"])
+        .add_tag_attributes("td", &["style"])
+        .add_tag_attributes("h1", &["id"])
+        .add_tag_attributes("h2", &["id"])
+        .add_tag_attributes("h3", &["id"])
+        .add_tag_attributes("h4", &["id"])
+        .add_tag_attributes("h5", &["id"])
+        .add_tag_attributes("h6", &["id"])
+        .id_prefix(Some(ID_PREFIX))
+        .attribute_filter(filter_attribute);
+    builder
+});
+
+/// 进一步限制白名单中属性的取值
+fn filter_attribute<'a>(element: &str, attribute: &str, value: &'a str) -> Option<Cow<'a, str>> {
+    match (element, attribute) {
+        ("input", "type") => (value == "checkbox").then_some(Cow::Borrowed(value)),
+        ("code", "class") => is_language_class(value).then_some(Cow::Borrowed(value)),
+        (_, "class") => ALLOWED_CLASSES
+            .contains(&value)
+            .then_some(Cow::Borrowed(value)),
+        // 表格对齐
+        (_, "style") => matches!(
+            value,
+            "text-align: left" | "text-align: center" | "text-align: right"
+        )
+        .then_some(Cow::Borrowed(value)),
+        // 页面内锚点与加了前缀的 id 保持一致
+        ("a", "href") => match value.strip_prefix('#') {
+            Some(fragment) if !fragment.starts_with(ID_PREFIX) => {
+                Some(Cow::Owned(format!("#{ID_PREFIX}{fragment}")))
+            }
+            _ => Some(Cow::Borrowed(value)),
+        },
+        _ => Some(Cow::Borrowed(value)),
+    }
+}
+
+/// 代码块的语言类名，如 `language-rust`、`language-c++`
+fn is_language_class(value: &str) -> bool {
+    value.strip_prefix("language-").is_some_and(|lang| {
+        !lang.is_empty()
+            && lang
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+' | '#' | '.'))
+    })
+}
+
+/// 渲染选项：CommonMark + GFM 扩展
+fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES
+}
+
+/// Markdown → 清洗后的 HTML
+pub fn render(markdown: &str) -> String {
+    render_post(markdown).html
+}
+
+/// Markdown → 清洗后的 HTML、字数、阅读时长与目录
+pub fn render_post(markdown: &str) -> RenderedPost {
+    let mut events: Vec<Event> = Parser::new_ext(markdown, options()).collect();
+
+    let mut counter = WordCounter::default();
+    let mut toc = Vec::new();
+    let mut used_ids = HashSet::new();
+    // 当前标题的起始事件下标与文字
+    let mut heading: Option<(usize, String)> = None;
+    for idx in 0..events.len() {
+        match &events[idx] {
+            Event::Start(Tag::Heading { .. }) => heading = Some((idx, String::new())),
+            Event::End(TagEnd::Heading(level)) => {
+                let level = *level as u8;
+                if let Some((start, title)) = heading.take() {
+                    let title = title.trim().to_string();
+                    let slug = unique_slug(&title, &mut used_ids);
+                    if let Event::Start(Tag::Heading { id, .. }) = &mut events[start] {
+                        *id = Some(CowStr::from(slug.clone()));
+                    }
+                    toc.push(TocEntry {
+                        level,
+                        title,
+                        anchor: format!("{ID_PREFIX}{slug}"),
+                    });
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                counter.add(text);
+                if let Some((_, title)) = heading.as_mut() {
+                    title.push_str(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, events.into_iter());
+    RenderedPost {
+        html: sanitize(&unsafe_html),
+        word_count: counter.total(),
+        reading_minutes: counter.reading_minutes(),
+        toc,
+    }
+}
+
+/// Markdown → 纯文本，供全文搜索建立索引与生成摘要片段，块级元素之间以换行分隔
+pub fn plain_text(markdown: &str) -> String {
+    let mut text = String::with_capacity(markdown.len());
+    for event in Parser::new_ext(markdown, options()) {
+        match event {
+            Event::Text(s) | Event::Code(s) => text.push_str(&s),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::CodeBlock
+                | TagEnd::TableCell,
+            ) if !text.is_empty() && !text.ends_with('\n') => text.push('\n'),
+            _ => {}
+        }
+    }
+    text.trim_end().to_string()
+}
+
+/// 中日韩文字
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'   // 平假名、片假名
+        | '\u{3400}'..='\u{4dbf}' // 汉字扩展 A
+        | '\u{4e00}'..='\u{9fff}' // 汉字
+        | '\u{ac00}'..='\u{d7af}' // 韩文音节
+        | '\u{f900}'..='\u{faff}' // 兼容汉字
+        | '\u{20000}'..='\u{2fa1f}')
+}
+
+/// 按中日韩字符与其他语言单词分别计数
+#[derive(Debug, Default)]
+struct WordCounter {
+    cjk: usize,
+    words: usize,
+}
+
+impl WordCounter {
+    fn add(&mut self, text: &str) {
+        let mut in_word = false;
+        for c in text.chars() {
+            if is_cjk(c) {
+                self.cjk += 1;
+                in_word = false;
+            } else if c.is_alphanumeric() {
+                if !in_word {
+                    self.words += 1;
+                }
+                in_word = true;
+            } else {
+                // 撇号、连字符不拆分单词，如 don't、well-known
+                in_word = in_word && matches!(c, '\'' | '-' | '_');
+            }
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.cjk + self.words
+    }
+
+    fn reading_minutes(&self) -> usize {
+        if self.total() == 0 {
+            return 0;
+        }
+        let seconds = self.cjk * 60 / CJK_CHARS_PER_MINUTE + self.words * 60 / WORDS_PER_MINUTE;
+        seconds.div_ceil(60).max(1)
+    }
+}
+
+/// 由标题文字生成锚点：保留字母、数字与中日韩文字，空白与连字符合并为 `-`，重复时追加 `-1`、`-2`
+fn unique_slug(title: &str, used: &mut HashSet<String>) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() || c == '_' {
+            slug.push(c);
+        } else if (c.is_whitespace() || c == '-') && !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = match slug.trim_end_matches('-') {
+        "" => "section".to_string(),
+        slug => slug.to_string(),
+    };
+    let mut candidate = slug.clone();
+    let mut n = 1;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{slug}-{n}");
+        n += 1;
+    }
+    candidate
+}
+
+/// 按白名单清洗 HTML
+pub fn sanitize(html: &str) -> String {
+    SANITIZER.clean(html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_gfm_and_strips_scripts() {
+        let html = render(
+            "| a | b |\n|:-:|---|\n| 1 | 2 |\n\n- [x] done\n\n```rust\nfn main() {}\n```\n\n\
+             <script>alert(1)</script><a href=\"javascript:alert(1)\" onclick=\"x()\">x</a>",
+        );
+        assert!(html.contains("<table>"));
+        assert!(html.contains(r#"<th style="text-align: center">"#));
+        assert!(html.contains(r#"type="checkbox""#));
+        assert!(html.contains(r#"<code class="language-rust">"#));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("onclick"));
+    }
+
+    #[test]
+    fn toc_and_word_count() {
+        let post = render_post(
+            "# 简介\n\nRust 是一门系统编程语言。\n\n## Getting Started\n\nHello, world!\n\n## 简介\n",
+        );
+        let anchors: Vec<&str> = post.toc.iter().map(|e| e.anchor.as_str()).collect();
+        assert_eq!(
+            anchors,
+            [
+                "user-content-简介",
+                "user-content-getting-started",
+                "user-content-简介-1"
+            ]
+        );
+        assert_eq!(post.toc[1].level, 2);
+        assert!(
+            post.html
+                .contains(r#"<h2 id="user-content-getting-started">"#)
+        );
+        // 简介×2（4 字）、Getting Started、Rust、是一门系统编程语言（9 字）、Hello world
+        assert_eq!(post.word_count, 4 + 2 + 1 + 9 + 2);
+        assert_eq!(post.reading_minutes, 1);
+        assert_eq!(render_post("").reading_minutes, 0);
+    }
+
+    #[test]
+    fn footnote_anchors_are_prefixed() {
+        let html = render("text[^1]\n\n[^1]: note\n");
+        assert!(html.contains(r##"href="#user-content-1""##));
+        assert!(html.contains(r#"id="user-content-1""#));
+        assert!(!render("<code class=\"evil\">x</code>").contains("evil"));
+    }
+
+    #[test]
+    fn plain_text_drops_markup() {
+        assert_eq!(
+            plain_text("# 标题\n\n**粗体** 与 `code`\n\n- 一\n- 二\n\n<b>x</b>"),
+            "标题\n粗体 与 code\n一\n二\nx"
+        );
+    }
+}
@@ -3,10 +3,17 @@ pub use sse::*;
 mod db_error;
 pub use db_error::{db_err_map, json_err_map};
 mod fmt_time;
-pub use fmt_time::fmt_beijing;
+pub use fmt_time::{fmt_beijing, fmt_beijing_opt, format_beijing};
+pub mod auth_cookie;
 pub mod crypto_pwd;
 pub mod data_processing;
 pub mod file_size;
 pub mod jwt;
+pub mod jwt_keys;
+pub mod markdown;
 pub mod perm_cache;
+pub mod rate_limit;
+pub mod slug;
+pub mod text_diff;
+pub mod token_revocation;
 pub mod websocket;
@@ -1,9 +1,10 @@
 use crate::config::AppError;
 use crate::models::{
     permissions::{self},
-    role_permissions,
+    role_permissions, user_permissions, user_roles, users,
 };
-use sea_orm::{DatabaseConnection, EntityTrait};
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, EntityTrait, QuerySelect};
 use std::collections::{HashMap, HashSet};
 use std::sync::LazyLock;
 use tokio::sync::RwLock;
@@ -12,6 +13,34 @@ use tokio::sync::RwLock;
 pub static ROLE_PERMS: LazyLock<RwLock<HashMap<i32, HashSet<String>>>> =
     LazyLock::new(|| RwLock::new(HashMap::new()));
 
+/// 用户 → 角色ID列表（主要角色在前）。没有任何角色的用户对应空列表
+pub static USER_ROLES: LazyLock<RwLock<HashMap<i32, Vec<i32>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// 用户 → 特殊权限（授予或拒绝，覆盖角色权限）
+pub static USER_PERMS: LazyLock<RwLock<HashMap<i32, Vec<UserPermOverride>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// 权限缓存是全局的，读写缓存的测试需要串行执行
+#[cfg(test)]
+pub(crate) static CACHE_TEST_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// 用户级权限覆盖项，对应 `user_permissions` 表中的一行
+#[derive(Debug, Clone)]
+pub struct UserPermOverride {
+    pub code: String,
+    /// true 表示授予，false 表示拒绝
+    pub granted: bool,
+    /// 过期时间，None 表示永不过期
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl UserPermOverride {
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
 pub async fn load_perm_cache(db: &DatabaseConnection) -> Result<(), AppError> {
     // 1. 角色权限模板
     let rp = role_permissions::Entity::find()
@@ -20,12 +49,194 @@ pub async fn load_perm_cache(db: &DatabaseConnection) -> Result<(), AppError> {
         .await?;
     let mut role_map: HashMap<i32, HashSet<String>> = HashMap::new();
     for (rp, perms) in rp {
+        let Some(role_id) = rp.role_id else {
+            continue;
+        };
         let codes: HashSet<_> = perms.into_iter().map(|p| p.code).collect();
         role_map
-            .entry(rp.role_id.unwrap()) // 先拿 Entry
+            .entry(role_id) // 先拿 Entry
             .or_default() // 没有就新建空 Set
             .extend(codes); // 再把本次权限码合并进去
     }
+
+    // 2. 用户角色（支持多角色，主要角色排在最前）
+    let user_role_map = load_user_roles(db).await?;
+
+    // 3. 用户特殊权限
+    let up = user_permissions::Entity::find()
+        .find_also_related(permissions::Entity)
+        .all(db)
+        .await?;
+    let mut user_perm_map: HashMap<i32, Vec<UserPermOverride>> = HashMap::new();
+    for (up, perm) in up {
+        if let (Some(user_id), Some(perm)) = (up.user_id, perm) {
+            user_perm_map
+                .entry(user_id)
+                .or_default()
+                .push(UserPermOverride {
+                    code: perm.code,
+                    granted: up.granted.unwrap_or(true),
+                    expires_at: up.expires_at.map(|t| t.with_timezone(&Utc)),
+                });
+        }
+    }
+
     *ROLE_PERMS.write().await = role_map;
+    *USER_ROLES.write().await = user_role_map;
+    *USER_PERMS.write().await = user_perm_map;
     Ok(())
 }
+
+/// 读取所有用户的角色。每个现有用户都有一项，角色被全部移除的用户对应空列表，
+/// 以免权限检查回退到令牌中过时的 `role_ids`
+async fn load_user_roles(db: &DatabaseConnection) -> Result<HashMap<i32, Vec<i32>>, AppError> {
+    let user_ids: Vec<i32> = users::Entity::find()
+        .select_only()
+        .column(users::Column::Id)
+        .into_tuple()
+        .all(db)
+        .await?;
+    let mut user_role_map: HashMap<i32, Vec<i32>> =
+        user_ids.into_iter().map(|id| (id, Vec::new())).collect();
+
+    let mut ur = user_roles::Entity::find().all(db).await?;
+    ur.sort_by_key(|r| !r.is_primary.unwrap_or(false));
+    for r in ur {
+        if let (Some(user_id), Some(role_id)) = (r.user_id, r.role_id) {
+            user_role_map.entry(user_id).or_default().push(role_id);
+        }
+    }
+    Ok(user_role_map)
+}
+
+/// 计算用户的有效权限码集合：所有角色权限的并集 + 显式授予 − 显式拒绝（忽略已过期项）。
+///
+/// 只有缓存加载后才注册的用户不在缓存中，此时回退到令牌中携带的 `role_ids`；
+/// 缓存中角色为空的用户没有任何角色权限。
+pub async fn effective_permissions(user_id: i32, fallback_role_ids: &[i32]) -> HashSet<String> {
+    let role_ids = role_ids_of(&*USER_ROLES.read().await, user_id, fallback_role_ids);
+    let overrides = USER_PERMS
+        .read()
+        .await
+        .get(&user_id)
+        .cloned()
+        .unwrap_or_default();
+    let role_perms = ROLE_PERMS.read().await;
+    resolve_permissions(&role_perms, &role_ids, &overrides, Utc::now())
+}
+
+/// 判断用户是否拥有指定权限码
+pub async fn has_permission(user_id: i32, fallback_role_ids: &[i32], code: &str) -> bool {
+    effective_permissions(user_id, fallback_role_ids)
+        .await
+        .contains(code)
+}
+
+fn role_ids_of(
+    user_roles: &HashMap<i32, Vec<i32>>,
+    user_id: i32,
+    fallback_role_ids: &[i32],
+) -> Vec<i32> {
+    user_roles
+        .get(&user_id)
+        .cloned()
+        .unwrap_or_else(|| fallback_role_ids.to_vec())
+}
+
+fn resolve_permissions(
+    role_perms: &HashMap<i32, HashSet<String>>,
+    role_ids: &[i32],
+    overrides: &[UserPermOverride],
+    now: DateTime<Utc>,
+) -> HashSet<String> {
+    let mut perms: HashSet<String> = role_ids
+        .iter()
+        .filter_map(|role_id| role_perms.get(role_id))
+        .flatten()
+        .cloned()
+        .collect();
+
+    let active = overrides.iter().filter(|o| o.is_active(now));
+    // 先授予后拒绝，拒绝优先级更高
+    let (granted, denied): (Vec<_>, Vec<_>) = active.partition(|o| o.granted);
+    perms.extend(granted.into_iter().map(|o| o.code.clone()));
+    for o in denied {
+        perms.remove(&o.code);
+    }
+    perms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use sea_orm::{ConnectionTrait, Database, Statement};
+
+    fn set(codes: &[&str]) -> HashSet<String> {
+        codes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn union_of_roles_with_user_overrides() {
+        let now = Utc::now();
+        let role_perms = HashMap::from([
+            (1, set(&["post:read"])),
+            (2, set(&["post:create", "post:delete"])),
+        ]);
+        let overrides = vec![
+            UserPermOverride {
+                code: "user:read".into(),
+                granted: true,
+                expires_at: None,
+            },
+            UserPermOverride {
+                code: "post:delete".into(),
+                granted: false,
+                expires_at: None,
+            },
+            UserPermOverride {
+                code: "system:backup".into(),
+                granted: true,
+                expires_at: Some(now - Duration::hours(1)),
+            },
+        ];
+
+        let perms = resolve_permissions(&role_perms, &[1, 2], &overrides, now);
+        assert_eq!(perms, set(&["post:read", "post:create", "user:read"]));
+        assert!(resolve_permissions(&role_perms, &[], &[], now).is_empty());
+    }
+
+    #[tokio::test]
+    async fn user_without_roles_does_not_fall_back_to_token() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        for sql in [
+            "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT)",
+            r#"
+            CREATE TABLE user_roles (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER,
+                role_id INTEGER,
+                is_primary INTEGER,
+                created_at TEXT NOT NULL
+            )
+            "#,
+            "INSERT INTO users (id) VALUES (1), (2)",
+            r#"
+            INSERT INTO user_roles (user_id, role_id, is_primary, created_at)
+            VALUES (1, 3, 0, '2024-01-01T00:00:00Z'), (1, 2, 1, '2024-01-01T00:00:00Z')
+            "#,
+        ] {
+            db.execute(Statement::from_string(db.get_database_backend(), sql))
+                .await
+                .unwrap();
+        }
+
+        let user_roles = load_user_roles(&db).await.unwrap();
+        // 主要角色在前
+        assert_eq!(role_ids_of(&user_roles, 1, &[9]), vec![2, 3]);
+        // 角色已被全部移除：不回退到令牌中的角色
+        assert_eq!(role_ids_of(&user_roles, 2, &[9]), Vec::<i32>::new());
+        // 缓存加载后才注册的用户：回退到令牌中的角色
+        assert_eq!(role_ids_of(&user_roles, 3, &[9]), vec![9]);
+    }
+}
@@ -0,0 +1,275 @@
+//! 进程内限流与登录失败锁定。
+//!
+//! - 令牌桶：每个键（如 `login:ip:1.2.3.4`、`email:a@b.com`）一个桶，容量为策略的次数，
+//!   在策略的周期内匀速补满；桶空时返回 `AppError::RateLimited` 与需要等待的秒数；
+//! - 登录锁定：同一账号连续密码错误达到阈值后锁定，之后每次失败锁定时长翻倍，登录成功后清零。
+//!   只记录存在的账号（按用户ID），不存在的用户名不占用内存。
+//!
+//! 状态只保存在当前进程中，多实例部署时每个实例单独计数；
+//! 已经补满的桶与过期的失败记录每隔 `PRUNE_INTERVAL` 清理一次。
+
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use actix_web::HttpRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// 清理空闲状态的间隔
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 限流配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitSettings {
+    /// 是否启用限流与登录锁定
+    pub enabled: bool,
+    /// 是否信任 `X-Forwarded-For` / `Forwarded` 请求头（部署在反向代理之后时开启）
+    pub trust_proxy: bool,
+    /// 登录接口，按 IP
+    pub login: RateLimitPolicy,
+    /// 发送验证码接口，按 IP
+    pub verification_code: RateLimitPolicy,
+    /// 发送验证码接口，按邮箱 / 手机号
+    pub verification_code_target: RateLimitPolicy,
+    /// 连续密码错误多少次后锁定账号
+    pub lockout_threshold: u32,
+    /// 首次锁定时长（秒）
+    pub lockout_base: u64,
+    /// 最长锁定时长（秒）
+    pub lockout_max: u64,
+}
+
+/// 限流策略：`period` 秒内最多 `capacity` 次，配置格式为 `次数/秒数`，如 `10/60`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitPolicy {
+    pub capacity: u32,
+    pub period: u64,
+}
+
+impl FromStr for RateLimitPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("限流策略格式应为 次数/秒数: {s}");
+        let (capacity, period) = s.split_once('/').ok_or_else(invalid)?;
+        let policy = Self {
+            capacity: capacity.trim().parse().map_err(|_| invalid())?,
+            period: period.trim().parse().map_err(|_| invalid())?,
+        };
+        if policy.capacity == 0 || policy.period == 0 {
+            return Err(invalid());
+        }
+        Ok(policy)
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+    /// 策略的周期，空闲超过该时长的桶已经补满，可以丢弃
+    period: Duration,
+}
+
+struct LoginFailures {
+    count: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// 定期清理的状态表，清理在持有锁时进行，因此限制为每 `PRUNE_INTERVAL` 最多一次
+struct Entries<K, V> {
+    map: HashMap<K, V>,
+    last_prune: Instant,
+}
+
+impl<K: Eq + Hash, V> Entries<K, V> {
+    fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            last_prune: Instant::now(),
+        }
+    }
+
+    /// 距上次清理超过 `PRUNE_INTERVAL` 时，只保留 `keep` 返回 true 的条目
+    fn prune(&mut self, now: Instant, mut keep: impl FnMut(&V) -> bool) {
+        if now.duration_since(self.last_prune) < PRUNE_INTERVAL {
+            return;
+        }
+        self.map.retain(|_, v| keep(v));
+        self.last_prune = now;
+    }
+}
+
+/// 键 → 令牌桶
+static BUCKETS: LazyLock<Mutex<Entries<String, Bucket>>> =
+    LazyLock::new(|| Mutex::new(Entries::new()));
+
+/// 用户ID → 连续登录失败记录
+static LOGIN_FAILURES: LazyLock<Mutex<Entries<i32, LoginFailures>>> =
+    LazyLock::new(|| Mutex::new(Entries::new()));
+
+/// 按策略消耗 `key` 的一个令牌，桶空时返回 429
+pub fn check(policy: RateLimitPolicy, key: &str) -> Result<(), AppError> {
+    if !CONFIG.rate_limit.enabled {
+        return Ok(());
+    }
+    take(policy, key, Instant::now())
+        .map_err(|retry_after| AppError::RateLimited("请稍后再试".to_string(), retry_after))
+}
+
+/// 消耗一个令牌，失败时返回需要等待的秒数
+fn take(policy: RateLimitPolicy, key: &str, now: Instant) -> Result<(), u64> {
+    let capacity = f64::from(policy.capacity);
+    let rate = capacity / policy.period as f64;
+    let period = Duration::from_secs(policy.period);
+    let mut buckets = BUCKETS.lock().unwrap();
+    // 空闲满一个周期的桶已经补满，与新建的桶没有区别
+    buckets.prune(now, |b| now.duration_since(b.updated_at) < b.period);
+    let bucket = buckets.map.entry(key.to_string()).or_insert(Bucket {
+        tokens: capacity,
+        updated_at: now,
+        period,
+    });
+    let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+    bucket.updated_at = now;
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        Ok(())
+    } else {
+        Err(((1.0 - bucket.tokens) / rate).ceil() as u64)
+    }
+}
+
+/// 账号处于锁定期时返回 429
+pub fn ensure_login_allowed(user_id: i32) -> Result<(), AppError> {
+    if !CONFIG.rate_limit.enabled {
+        return Ok(());
+    }
+    let failures = LOGIN_FAILURES.lock().unwrap();
+    let now = Instant::now();
+    match failures.map.get(&user_id).and_then(|f| f.locked_until) {
+        Some(until) if until > now => Err(AppError::RateLimited(
+            "密码错误次数过多，账号已临时锁定".to_string(),
+            (until - now).as_secs().max(1),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// 记录一次密码错误，只对存在的账号调用
+pub fn record_login_failure(user_id: i32, user_name: &str) {
+    if !CONFIG.rate_limit.enabled {
+        return;
+    }
+    let settings = &CONFIG.rate_limit;
+    let mut failures = LOGIN_FAILURES.lock().unwrap();
+    let now = Instant::now();
+    let max = Duration::from_secs(settings.lockout_max);
+    // 距上次失败超过最长锁定时长的记录已无意义
+    failures.prune(now, |f| now.duration_since(f.last_failure) < max);
+    let entry = failures.map.entry(user_id).or_insert(LoginFailures {
+        count: 0,
+        last_failure: now,
+        locked_until: None,
+    });
+    entry.count += 1;
+    entry.last_failure = now;
+    if let Some(lock) = lockout_duration(
+        entry.count,
+        settings.lockout_threshold,
+        settings.lockout_base,
+        settings.lockout_max,
+    ) {
+        entry.locked_until = Some(now + lock);
+        log::warn!(
+            "account {user_name} locked for {}s after {} failed logins",
+            lock.as_secs(),
+            entry.count
+        );
+    }
+}
+
+/// 登录成功后清除失败记录
+pub fn record_login_success(user_id: i32) {
+    LOGIN_FAILURES.lock().unwrap().map.remove(&user_id);
+}
+
+/// 第 `count` 次失败后的锁定时长：达到阈值时为 `base`，之后每次翻倍，不超过 `max`
+fn lockout_duration(count: u32, threshold: u32, base: u64, max: u64) -> Option<Duration> {
+    if threshold == 0 || count < threshold {
+        return None;
+    }
+    let doublings = (count - threshold).min(32);
+    Some(Duration::from_secs(
+        base.saturating_mul(1u64 << doublings).min(max),
+    ))
+}
+
+/// 客户端 IP；只有开启 `RATE_LIMIT_TRUST_PROXY` 时才采用代理转发的地址
+pub fn client_ip(req: &HttpRequest) -> String {
+    if CONFIG.rate_limit.trust_proxy
+        && let Some(ip) = req.connection_info().realip_remote_addr()
+    {
+        return ip.to_string();
+    }
+    req.peer_addr()
+        .map_or_else(|| "unknown".to_string(), |addr| addr.ip().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let policy: RateLimitPolicy = "2/10".parse().unwrap();
+        let key = "test:bucket_refills_over_time";
+        let start = Instant::now();
+        assert_eq!(take(policy, key, start), Ok(()));
+        assert_eq!(take(policy, key, start), Ok(()));
+        assert_eq!(take(policy, key, start), Err(5));
+        assert_eq!(take(policy, key, start + Duration::from_secs(5)), Ok(()));
+        assert!("0/10".parse::<RateLimitPolicy>().is_err());
+        assert!("10".parse::<RateLimitPolicy>().is_err());
+    }
+
+    #[test]
+    fn idle_entries_pruned_at_interval() {
+        let mut entries = Entries::new();
+        let start = entries.last_prune;
+        entries.map.insert("idle", 1);
+        entries.map.insert("busy", 2);
+        // 未到清理间隔时不扫描
+        entries.prune(start, |_| false);
+        assert_eq!(entries.map.len(), 2);
+
+        let later = start + PRUNE_INTERVAL;
+        entries.prune(later, |v| *v == 2);
+        assert_eq!(entries.map.len(), 1);
+        assert!(entries.map.contains_key("busy"));
+        // 刚清理过，下一次要再等一个间隔
+        entries.prune(later, |_| false);
+        assert_eq!(entries.map.len(), 1);
+    }
+
+    #[test]
+    fn lockout_grows_progressively() {
+        assert_eq!(lockout_duration(4, 5, 60, 3600), None);
+        assert_eq!(
+            lockout_duration(5, 5, 60, 3600),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            lockout_duration(7, 5, 60, 3600),
+            Some(Duration::from_secs(240))
+        );
+        assert_eq!(
+            lockout_duration(40, 5, 60, 3600),
+            Some(Duration::from_secs(3600))
+        );
+    }
+}
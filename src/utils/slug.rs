@@ -0,0 +1,95 @@
+//! 文章 slug：用于 SEO 友好的固定链接，只含小写字母、数字与连字符。
+//!
+//! 自动生成时先将标题转写为 ASCII（中文转为不带声调的拼音，如 `你好世界` → `ni-hao-shi-jie`），
+//! 再把其他字符替换为连字符。
+use deunicode::deunicode;
+use uuid::Uuid;
+
+/// slug 的最大长度
+pub const MAX_LEN: usize = 100;
+
+/// 自动生成时截取的长度，为重名时追加的 `-2`、`-3` 留出空间
+const GENERATED_LEN: usize = 80;
+
+/// 标题中没有可用字符时使用的 slug
+const FALLBACK: &str = "post";
+
+/// 与固定路由冲突的 slug
+const RESERVED: &[&str] = &["search"];
+
+/// 由标题生成 slug
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in deunicode(title).chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    // 截断时尽量停在单词边界
+    if slug.len() > GENERATED_LEN {
+        let cut = slug[..GENERATED_LEN].rfind('-').unwrap_or(GENERATED_LEN);
+        slug.truncate(cut);
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() || check(slug).is_err() {
+        FALLBACK.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// 重名时追加序号，如 `hello-world-2`
+pub fn with_suffix(slug: &str, n: usize) -> String {
+    let suffix = format!("-{n}");
+    let keep = slug.len().min(MAX_LEN - suffix.len());
+    format!("{}{suffix}", slug[..keep].trim_end_matches('-'))
+}
+
+/// 校验用户指定的 slug，不合法时返回原因
+pub fn check(slug: &str) -> Result<(), &'static str> {
+    if slug.is_empty() || slug.len() > MAX_LEN {
+        return Err("slug 长度必须在1-100个字符之间");
+    }
+    let well_formed = slug.split('-').all(|part| {
+        !part.is_empty()
+            && part
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+    });
+    if !well_formed {
+        return Err("slug 只能包含小写字母、数字和连字符，且不能以连字符开头或结尾");
+    }
+    // UUID 形式的路径按 UUID 查找文章
+    if RESERVED.contains(&slug) || Uuid::parse_str(slug).is_ok() {
+        return Err("该 slug 为保留字，请换一个");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugify_transliterates_chinese() {
+        assert_eq!(slugify("你好，世界"), "ni-hao-shi-jie");
+        assert_eq!(slugify("Rust 入门：所有权"), "rust-ru-men-suo-you-quan");
+        assert_eq!(slugify("  Hello,   World!  "), "hello-world");
+        assert_eq!(slugify("!!!"), "post");
+        assert_eq!(slugify("search"), "post");
+        assert!(slugify(&"长标题".repeat(40)).len() <= 80);
+        assert_eq!(with_suffix("hello-world", 2), "hello-world-2");
+    }
+
+    #[test]
+    fn check_rejects_malformed_slugs() {
+        assert!(check("hello-world-2").is_ok());
+        assert!(check("Hello").is_err());
+        assert!(check("-hello").is_err());
+        assert!(check("a--b").is_err());
+        assert!(check("search").is_err());
+        assert!(check(&Uuid::new_v4().to_string()).is_err());
+    }
+}
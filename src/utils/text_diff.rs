@@ -0,0 +1,141 @@
+//! 按行比较两段文本，用于文章修订记录的差异展示
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// 去掉首尾相同的行后，剩余部分超过该规模（行数乘积）时不再逐行比较，整体视为删除后插入
+const MAX_TABLE_CELLS: usize = 4_000_000;
+
+/// 差异行的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    /// 两边相同
+    Equal,
+    /// 仅在新文本中
+    Insert,
+    /// 仅在旧文本中
+    Delete,
+}
+
+/// 一行差异
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub content: String,
+}
+
+impl DiffLine {
+    fn new(op: DiffOp, content: &str) -> Self {
+        Self {
+            op,
+            content: content.to_string(),
+        }
+    }
+}
+
+/// 基于最长公共子序列的逐行差异，删除行排在同位置的插入行之前
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::new(DiffOp::Equal, line))
+        .collect();
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_TABLE_CELLS {
+        lines.extend(
+            old_mid
+                .iter()
+                .map(|line| DiffLine::new(DiffOp::Delete, line)),
+        );
+        lines.extend(
+            new_mid
+                .iter()
+                .map(|line| DiffLine::new(DiffOp::Insert, line)),
+        );
+    } else {
+        // lcs[i][j]：old_mid[i..] 与 new_mid[j..] 的最长公共子序列长度
+        let width = new_mid.len() + 1;
+        let mut lcs = vec![0u32; (old_mid.len() + 1) * width];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() && j < new_mid.len() {
+            if old_mid[i] == new_mid[j] {
+                lines.push(DiffLine::new(DiffOp::Equal, old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                lines.push(DiffLine::new(DiffOp::Delete, old_mid[i]));
+                i += 1;
+            } else {
+                lines.push(DiffLine::new(DiffOp::Insert, new_mid[j]));
+                j += 1;
+            }
+        }
+        lines.extend(
+            old_mid[i..]
+                .iter()
+                .map(|line| DiffLine::new(DiffOp::Delete, line)),
+        );
+        lines.extend(
+            new_mid[j..]
+                .iter()
+                .map(|line| DiffLine::new(DiffOp::Insert, line)),
+        );
+    }
+
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::new(DiffOp::Equal, line)),
+    );
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let diff = line_diff("a\nb\nc\nd", "a\nc\nx\nd");
+        let ops: Vec<(DiffOp, &str)> = diff
+            .iter()
+            .map(|line| (line.op, line.content.as_str()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                (DiffOp::Equal, "a"),
+                (DiffOp::Delete, "b"),
+                (DiffOp::Equal, "c"),
+                (DiffOp::Insert, "x"),
+                (DiffOp::Equal, "d"),
+            ]
+        );
+        assert!(
+            line_diff("same\ntext", "same\ntext")
+                .iter()
+                .all(|line| line.op == DiffOp::Equal)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! 访问令牌撤销表。
+//!
+//! 访问令牌是无状态的短期 JWT，登出或封禁账号后在过期前仍然有效，
+//! 因此在进程内维护四份名单，由 `AuthMiddleware` 在验签后检查：
+//! - 按 `jti` 拉黑的单个令牌（登出）；
+//! - 按用户记录的撤销时间，早于该时间签发的令牌全部失效（踢下线）；
+//! - 被撤销的登录会话，该会话签发的令牌全部失效；
+//! - 停用、封禁或注销的用户，其令牌在状态恢复前全部失效。
+//!
+//! 前三份名单只需保留到对应令牌过期为止，重启后丢失也只影响剩余的访问令牌有效期，
+//! 刷新令牌的作废状态保存在数据库中；用户状态名单在启动时从数据库加载。
+
+use crate::config::AppError;
+use crate::config::manager::CONFIG;
+use crate::models::users::{self, UserStatus};
+use crate::utils::jwt::TokenClaims;
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use tokio::sync::RwLock;
+
+/// jti → 令牌过期时间（时间戳）
+static REVOKED_JTI: LazyLock<RwLock<HashMap<String, i64>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// 用户ID → 撤销时间（毫秒时间戳），早于该时间签发的令牌全部失效
+static USER_REVOKED_AT: LazyLock<RwLock<HashMap<i32, i64>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// 会话ID → 撤销时间（时间戳）
+static REVOKED_SESSIONS: LazyLock<RwLock<HashMap<String, i64>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// 用户ID → 不可用截止时间，`None` 表示直到状态恢复
+static BLOCKED_USERS: LazyLock<RwLock<HashMap<i32, Option<DateTime<Utc>>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// 启动时从数据库加载非正常状态的用户
+pub async fn load_blocked_users(db: &DatabaseConnection) -> Result<(), AppError> {
+    let now = Utc::now();
+    let blocked = users::Entity::find()
+        .filter(users::Column::Status.ne(UserStatus::Active))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|user| user.is_blocked(now))
+        .map(|user| (user.id, user.banned_until))
+        .collect();
+    *BLOCKED_USERS.write().await = blocked;
+    Ok(())
+}
+
+/// 用户状态变更后同步名单
+pub async fn sync_user_status(user: &users::Model) {
+    let mut blocked = BLOCKED_USERS.write().await;
+    if user.is_blocked(Utc::now()) {
+        blocked.insert(user.id, user.banned_until);
+    } else {
+        blocked.remove(&user.id);
+    }
+}
+
+/// 拉黑单个访问令牌
+pub async fn revoke_jti(jti: &str, exp: i64) {
+    let now = Utc::now().timestamp();
+    let mut revoked = REVOKED_JTI.write().await;
+    // 顺便清理已经过期的记录
+    revoked.retain(|_, exp| *exp > now);
+    revoked.insert(jti.to_string(), exp);
+}
+
+/// 撤销用户当前已签发的全部访问令牌
+pub async fn revoke_user_tokens(user_id: i32) {
+    let now = Utc::now().timestamp_millis();
+    let mut revoked = USER_REVOKED_AT.write().await;
+    revoked.retain(|_, at| *at + CONFIG.jwt.expiry * 1000 > now);
+    revoked.insert(user_id, now);
+}
+
+/// 撤销登录会话已签发的全部访问令牌
+pub async fn revoke_session(session_id: &str) {
+    let now = Utc::now().timestamp();
+    let mut revoked = REVOKED_SESSIONS.write().await;
+    revoked.retain(|_, at| *at + CONFIG.jwt.expiry > now);
+    revoked.insert(session_id.to_string(), now);
+}
+
+/// 判断访问令牌是否已被撤销
+pub async fn is_revoked(claims: &TokenClaims) -> bool {
+    if REVOKED_JTI.read().await.contains_key(&claims.jti) {
+        return true;
+    }
+    if let Some(sid) = &claims.sid
+        && REVOKED_SESSIONS.read().await.contains_key(sid)
+    {
+        return true;
+    }
+    let now = Utc::now();
+    if BLOCKED_USERS
+        .read()
+        .await
+        .get(&claims.user_id)
+        .is_some_and(|until| until.is_none_or(|until| until > now))
+    {
+        return true;
+    }
+    USER_REVOKED_AT
+        .read()
+        .await
+        .get(&claims.user_id)
+        .is_some_and(|revoked_at| claims.issued_at_ms() < *revoked_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn claims(user_id: i32, issued_at: DateTime<Utc>) -> TokenClaims {
+        TokenClaims {
+            user_uuid: String::new(),
+            user_name: String::new(),
+            user_id,
+            jti: uuid::Uuid::new_v4().to_string(),
+            iat: issued_at.timestamp(),
+            iat_ms: issued_at.timestamp_millis(),
+            exp: issued_at.timestamp() + 600,
+            role_ids: Vec::new(),
+            scopes: None,
+            sid: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn token_issued_in_same_second_after_revocation_is_valid() {
+        let user_id = 8901;
+        let before = claims(user_id, Utc::now());
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        revoke_user_tokens(user_id).await;
+        let revoked_at = USER_REVOKED_AT.read().await[&user_id];
+        let after = claims(user_id, Utc::now());
+
+        assert!(is_revoked(&before).await);
+        assert!(!is_revoked(&after).await);
+        // 撤销后同一秒内签发的令牌仍然有效，即使秒级 iat 相同
+        let same_second = DateTime::from_timestamp_millis(revoked_at + 1).unwrap();
+        assert!(!is_revoked(&claims(user_id, same_second)).await);
+        // 没有毫秒签发时间的旧令牌按 iat 换算
+        let mut legacy = claims(user_id, same_second - Duration::seconds(1));
+        legacy.iat_ms = 0;
+        assert!(is_revoked(&legacy).await);
+    }
+}